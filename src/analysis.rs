@@ -0,0 +1,1736 @@
+use crate::{
+    diagnostic::{Diagnostic, DiagnosticKind},
+    errors::{ClipMismatchError, JointIndexError, NonRootTranslationError},
+    joint::{JointData, Offset},
+    math, tensor, Axis, Bvh, ChannelType,
+};
+use bstr::BString;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+/// Statistics of the values of a single channel over every frame of a `Bvh`.
+///
+/// See [`Bvh::channel_stats`][`Bvh::channel_stats`].
+///
+/// [`Bvh::channel_stats`]: struct.Bvh.html#method.channel_stats
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelStats {
+    /// The smallest value of the channel.
+    pub min: f32,
+    /// The largest value of the channel.
+    pub max: f32,
+    /// The mean value of the channel.
+    pub mean: f32,
+    /// The population standard deviation of the values of the channel.
+    pub std_dev: f32,
+}
+
+impl ChannelStats {
+    /// Returns the difference between the largest and smallest values of the channel.
+    #[inline]
+    pub fn range(&self) -> f32 {
+        self.max - self.min
+    }
+}
+
+/// How the difference between two clips is measured by
+/// [`Bvh::pose_error`][`Bvh::pose_error`].
+///
+/// [`Bvh::pose_error`]: struct.Bvh.html#method.pose_error
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum PoseMetric {
+    /// The distance between the world-space positions of each joint, averaged over
+    /// every joint and frame.
+    MeanJointPositionError,
+    /// The angle in degrees between the local rotations of each joint, averaged over
+    /// every joint and frame.
+    MeanQuaternionAngle,
+}
+
+/// A difference between a `Bvh` and the rig it is expected to match, returned by
+/// [`Bvh::matches_rig`][`Bvh::matches_rig`].
+///
+/// [`Bvh::matches_rig`]: struct.Bvh.html#method.matches_rig
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum RigMismatch {
+    /// There is no joint with the expected name.
+    MissingJoint {
+        /// The name of the joint.
+        name: BString,
+    },
+    /// The joint does not have some of the expected channel types.
+    MissingChannels {
+        /// The name of the joint.
+        name: BString,
+        /// The index of the joint.
+        joint_index: usize,
+        /// The expected channel types which the joint does not have.
+        channels: Vec<ChannelType>,
+    },
+}
+
+impl Bvh {
+    /// The name patterns used by [`Bvh::infer_symmetry_pairs`] for common naming
+    /// conventions, as pairs of a left and a right prefix or suffix.
+    ///
+    /// [`Bvh::infer_symmetry_pairs`]: struct.Bvh.html#method.infer_symmetry_pairs
+    pub const DEFAULT_SYMMETRY_PATTERNS: &'static [(&'static [u8], &'static [u8])] = &[
+        (b"Left", b"Right"),
+        (b"left", b"right"),
+        (b"L_", b"R_"),
+        (b"_L", b"_R"),
+        (b"l_", b"r_"),
+        (b"_l", b"_r"),
+        (b".L", b".R"),
+        (b".l", b".r"),
+    ];
+
+    /// Compute the approximate center of mass of the skeleton for every frame.
+    ///
+    /// The center of mass is the average of the world-space positions of each
+    /// joint, weighted by the mass of the joint in `weights`, which is keyed by
+    /// joint name. Joints which are not present in `weights` have a mass of `1.0`.
+    ///
+    /// If the total mass of the skeleton is zero, then the origin is returned for
+    /// that frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// # use std::collections::HashMap;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    /// };
+    ///
+    /// let center = bvh.center_of_mass(&HashMap::new());
+    /// assert_eq!(center, [[0.0, 5.0, 0.0]]);
+    /// ```
+    pub fn center_of_mass(&self, weights: &HashMap<Vec<u8>, f32>) -> Vec<Offset> {
+        let joint_weights = self
+            .joints
+            .iter()
+            .map(|joint| weights.get(joint.name()).copied().unwrap_or(1.0))
+            .collect::<Vec<_>>();
+        let total_weight: f32 = joint_weights.iter().sum();
+
+        self.frames()
+            .map(|frame| {
+                if total_weight == 0.0 {
+                    return [0.0; 3];
+                }
+
+                let mut center = [0.0; 3];
+                for (position, weight) in self
+                    .world_positions(&frame)
+                    .iter()
+                    .zip(joint_weights.iter())
+                {
+                    for axis in 0..3 {
+                        center[axis] += position[axis] * weight;
+                    }
+                }
+
+                for c in center.iter_mut() {
+                    *c /= total_weight;
+                }
+                center
+            })
+            .collect()
+    }
+
+    /// Heuristically detect the frames in which the joint named `joint_name` is in
+    /// contact with the ground, such as a planted foot.
+    ///
+    /// A joint is considered to be in contact for a frame when its world-space height
+    /// (the `Y` axis) is at most `height_threshold`, and its speed is at most
+    /// `speed_threshold`, in units per second. The speed is estimated from the
+    /// difference in position to the previous frame, or to the next frame for the
+    /// first frame.
+    ///
+    /// Returns one value per frame. If there is no joint named `joint_name`, then
+    /// every frame is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Foot
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.5
+    ///     0.0 0.0 0.0
+    ///     0.0 0.0 0.0
+    ///     0.0 4.0 0.0
+    /// };
+    ///
+    /// let contacts = bvh.detect_contacts(b"Foot", 1.0, 1.0);
+    /// assert_eq!(contacts, [true, true, false]);
+    /// ```
+    pub fn detect_contacts(
+        &self,
+        joint_name: &[u8],
+        height_threshold: f32,
+        speed_threshold: f32,
+    ) -> Vec<bool> {
+        let index = match self.joints.iter().position(|j| j.name() == joint_name) {
+            Some(index) => index,
+            None => return vec![false; self.frames().len()],
+        };
+
+        let positions = self
+            .frames()
+            .map(|frame| self.world_positions(&frame)[index])
+            .collect::<Vec<_>>();
+        let frame_time = self.frame_time.as_secs_f32();
+
+        (0..positions.len())
+            .map(|i| {
+                let neighbour = if i > 0 {
+                    i - 1
+                } else {
+                    (i + 1).min(positions.len() - 1)
+                };
+                let distance = positions[i]
+                    .iter()
+                    .zip(positions[neighbour].iter())
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<f32>()
+                    .sqrt();
+                let speed = if frame_time > 0.0 {
+                    distance / frame_time
+                } else {
+                    0.0
+                };
+
+                positions[i][1] <= height_threshold && speed <= speed_threshold
+            })
+            .collect()
+    }
+
+    /// Returns how far the joint named `joint_name` is below the ground in every
+    /// frame, such as to find and clean up frames where a foot sinks into the floor.
+    ///
+    /// The ground is the plane where the world-space height (the `Y` axis) is `0`.
+    /// The value for a frame is the depth of the joint below the ground, or `0.0` if
+    /// the joint is on or above the ground.
+    ///
+    /// Returns one value per frame. If there is no joint named `joint_name`, then
+    /// every frame is `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Foot
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.5
+    ///     0.0 1.0 0.0
+    ///     0.0 0.0 0.0
+    ///     0.0 -2.0 0.0
+    /// };
+    ///
+    /// assert_eq!(bvh.ground_penetration(b"Foot"), [0.0, 0.0, 2.0]);
+    /// ```
+    pub fn ground_penetration(&self, joint_name: &[u8]) -> Vec<f32> {
+        let index = match self.joints.iter().position(|j| j.name() == joint_name) {
+            Some(index) => index,
+            None => return vec![0.0; self.frames().len()],
+        };
+
+        let mut transforms = Vec::with_capacity(self.joints.len());
+        self.frames()
+            .map(|frame| {
+                self.world_transforms_into(&frame, &mut transforms);
+                let height = math::mat_get_translation(&transforms[index])[1];
+                (-height).max(0.0)
+            })
+            .collect()
+    }
+
+    /// Returns the distance between the world-space positions of the joints at
+    /// indices `a` and `b` in every frame of the `Bvh`.
+    ///
+    /// Returns an error if either `a` or `b` is not the index of a joint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Chest
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT LeftHand
+    ///         {
+    ///             OFFSET 3.0 0.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 1.0 0.0 0.0
+    ///             }
+    ///         }
+    ///         JOINT RightHand
+    ///         {
+    ///             OFFSET -3.0 0.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET -1.0 0.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    ///     5.0 1.0 0.0
+    /// };
+    ///
+    /// assert_eq!(bvh.joint_distance_series(1, 2).unwrap(), [6.0, 6.0]);
+    /// assert!(bvh.joint_distance_series(1, 3).is_err());
+    /// ```
+    pub fn joint_distance_series(&self, a: usize, b: usize) -> Result<Vec<f32>, JointIndexError> {
+        let num_joints = self.joints.len();
+        if let Some(&index) = [a, b].iter().find(|&&index| index >= num_joints) {
+            return Err(JointIndexError::out_of_range(index, num_joints));
+        }
+
+        Ok(self
+            .frames()
+            .map(|frame| {
+                let positions = self.world_positions(&frame);
+                let (pa, pb) = (positions[a], positions[b]);
+                length(&[pa[0] - pb[0], pa[1] - pb[1], pa[2] - pb[2]])
+            })
+            .collect())
+    }
+
+    /// Returns the world-space acceleration of the joint at index `joint`, such as to
+    /// measure how smooth its motion is.
+    ///
+    /// The acceleration is the second finite difference of the joint's world
+    /// positions divided by the square of the frame time, in units per second
+    /// squared. If the frame time is zero, then the differences are not divided.
+    /// Since the first and the last frame have only one neighbour, one value is
+    /// returned for every other frame, so the value at index `i` is the
+    /// acceleration at frame `i + 1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `joint` is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 4
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     0.0
+    ///     1.0
+    ///     3.0
+    /// };
+    ///
+    /// let acceleration = bvh.joint_acceleration_series(1)?;
+    /// assert_eq!(acceleration, [[4.0, 0.0, 0.0], [4.0, 0.0, 0.0]]);
+    /// assert!(bvh.joint_acceleration_series(2).is_err());
+    /// # Result::<(), bvh_anim::errors::JointIndexError>::Ok(())
+    /// ```
+    pub fn joint_acceleration_series(&self, joint: usize) -> Result<Vec<Offset>, JointIndexError> {
+        let num_joints = self.joints.len();
+        if joint >= num_joints {
+            return Err(JointIndexError::out_of_range(joint, num_joints));
+        }
+
+        let positions = self
+            .frames()
+            .map(|frame| self.world_positions(&frame)[joint])
+            .collect::<Vec<_>>();
+        let scale = self.frame_time_scale(2);
+        Ok(positions
+            .windows(3)
+            .map(|p| {
+                let mut acceleration = [0.0; 3];
+                for (axis, value) in acceleration.iter_mut().enumerate() {
+                    *value = (p[2][axis] - 2.0 * p[1][axis] + p[0][axis]) * scale;
+                }
+                acceleration
+            })
+            .collect())
+    }
+
+    /// Returns the sum of the squared jerk of every joint in every frame, such as to
+    /// score the quality of captured or generated motion. Lower is smoother.
+    ///
+    /// The jerk is the third finite difference of the world positions divided by
+    /// the cube of the frame time, in units per second cubed. If the frame time is
+    /// zero, then the differences are not divided. Returns `0.0` if the `Bvh` has
+    /// fewer than four frames.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///     }
+    ///     MOTION
+    ///     Frames: 4
+    ///     Frame Time: 1.0
+    ///     0.0
+    ///     0.0
+    ///     0.0
+    ///     2.0
+    /// };
+    ///
+    /// assert_eq!(bvh.smoothness_score(), 4.0);
+    /// ```
+    pub fn smoothness_score(&self) -> f64 {
+        let positions = self
+            .frames()
+            .map(|frame| self.world_positions(&frame))
+            .collect::<Vec<_>>();
+        let scale = f64::from(self.frame_time_scale(3));
+
+        let mut score = 0.0;
+        for p in positions.windows(4) {
+            let joints = p[0].iter().zip(&p[1]).zip(&p[2]).zip(&p[3]);
+            for (((p0, p1), p2), p3) in joints {
+                let axes = p0.iter().zip(p1).zip(p2).zip(p3);
+                for (((&a, &b), &c), &d) in axes {
+                    let jerk =
+                        f64::from(d) - 3.0 * f64::from(c) + 3.0 * f64::from(b) - f64::from(a);
+                    let jerk = jerk * scale;
+                    score += jerk * jerk;
+                }
+            }
+        }
+        score
+    }
+
+    /// Returns the factor which converts an `order`th difference per frame into a
+    /// difference per second, or `1.0` if the frame time is zero.
+    fn frame_time_scale(&self, order: i32) -> f32 {
+        let frame_time = self.frame_time.as_secs_f32();
+        if frame_time > 0.0 {
+            frame_time.powi(-order)
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns the heading of the root joint in every frame, in degrees, such as to
+    /// analyse the turning of a character during locomotion.
+    ///
+    /// The heading is the rotation about the up axis, which is assumed to be the `Y`
+    /// axis with the character facing along `Z` in the rest pose. Use
+    /// [`Bvh::root_heading_series_about`] for a different up axis. See that method
+    /// for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Zrotation Xrotation Yrotation
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    ///     0.0 0.0 45.0
+    /// };
+    ///
+    /// let headings = bvh.root_heading_series();
+    /// assert!(headings[0].abs() < 1.0e-4);
+    /// assert!((headings[1] - 45.0).abs() < 1.0e-4);
+    /// ```
+    ///
+    /// [`Bvh::root_heading_series_about`]: struct.Bvh.html#method.root_heading_series_about
+    #[inline]
+    pub fn root_heading_series(&self) -> Vec<f32> {
+        self.root_heading_series_about(Axis::Y)
+    }
+
+    /// Returns the heading of the root joint about the `up` axis in every frame, in
+    /// degrees.
+    ///
+    /// The heading is measured from the axis after `up` towards the axis after that,
+    /// so for `Y` up it is measured from `Z` towards `X`, and for `Z` up it is
+    /// measured from `X` towards `Y`. It is derived from the combined rotation of the
+    /// root's rotation channels, so it does not depend on the rotation order, and
+    /// any tilt of the root about the other axes is ignored.
+    ///
+    /// The first heading is in the range `-180.0..=180.0`, and each later heading is
+    /// chosen to be within `180.0` of the previous one, so that the series is
+    /// continuous when the character turns around. Returns `0.0` for every frame if
+    /// the `Bvh` has no root joint.
+    pub fn root_heading_series_about(&self, up: Axis) -> Vec<f32> {
+        let root = match self.joints.iter().find(|joint| joint.is_root()) {
+            Some(root) => root,
+            None => return vec![0.0; self.frames().len()],
+        };
+        let forward_index = (up as usize + 1) % 3;
+        let side_index = (up as usize + 2) % 3;
+        let mut forward = [0.0; 3];
+        forward[forward_index] = 1.0;
+
+        let mut headings: Vec<f32> = Vec::with_capacity(self.frames().len());
+        for frame in self.frames() {
+            let rotation = math::quat_to_mat(tensor::joint_rotation(root, frame.as_slice()));
+            let facing = math::transform_point(&rotation, forward);
+            let mut heading = facing[side_index].atan2(facing[forward_index]).to_degrees();
+            if let Some(&previous) = headings.last() {
+                heading += ((previous - heading) / 360.0).round() * 360.0;
+            }
+            headings.push(heading);
+        }
+        headings
+    }
+
+    /// Measure the difference between `self` and `other` using `metric`, such as to
+    /// evaluate a retargeted or generated clip against a reference clip.
+    ///
+    /// Both clips must have the same skeleton hierarchy and the same number of
+    /// frames, although offsets and channels may differ. Returns `0.0` if the clips
+    /// have no frames.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, PoseMetric};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    /// };
+    ///
+    /// let mut shifted = bvh.clone();
+    /// shifted.frames_mut().next().unwrap()[0] = 2.0;
+    ///
+    /// assert_eq!(bvh.pose_error(&bvh, PoseMetric::MeanJointPositionError)?, 0.0);
+    /// assert_eq!(bvh.pose_error(&shifted, PoseMetric::MeanJointPositionError)?, 2.0);
+    /// # Result::<(), bvh_anim::errors::ClipMismatchError>::Ok(())
+    /// ```
+    pub fn pose_error(&self, other: &Bvh, metric: PoseMetric) -> Result<f64, ClipMismatchError> {
+        if self.joints.len() != other.joints.len() {
+            return Err(ClipMismatchError::JointCount {
+                expected: self.joints.len(),
+                actual: other.joints.len(),
+            });
+        }
+        if let Some(joint_index) = self
+            .joints
+            .iter()
+            .zip(other.joints.iter())
+            .position(|(a, b)| a.parent_index() != b.parent_index())
+        {
+            return Err(ClipMismatchError::Hierarchy { joint_index });
+        }
+        if self.frames().len() != other.frames().len() {
+            return Err(ClipMismatchError::FrameCount {
+                expected: self.frames().len(),
+                actual: other.frames().len(),
+            });
+        }
+
+        let mut total = 0.0f64;
+        let mut count = 0usize;
+        for (frame_a, frame_b) in self.frames().zip(other.frames()) {
+            match metric {
+                PoseMetric::MeanJointPositionError => {
+                    let positions_a = self.world_positions(&frame_a);
+                    let positions_b = other.world_positions(&frame_b);
+                    for (a, b) in positions_a.iter().zip(positions_b.iter()) {
+                        total += f64::from(length(&[a[0] - b[0], a[1] - b[1], a[2] - b[2]]));
+                    }
+                }
+                PoseMetric::MeanQuaternionAngle => {
+                    for (a, b) in self.joints.iter().zip(other.joints.iter()) {
+                        let quat_a = tensor::joint_rotation(a, frame_a.as_slice());
+                        let quat_b = tensor::joint_rotation(b, frame_b.as_slice());
+                        total += f64::from(math::quat_angle_between(quat_a, quat_b));
+                    }
+                }
+            }
+            count += self.joints.len();
+        }
+
+        Ok(if count > 0 { total / count as f64 } else { 0.0 })
+    }
+
+    /// Estimate the size of the character, as the distance from the root joint to
+    /// the lowest end site in the rest pose, which is usually the length of a leg.
+    ///
+    /// This can be used to normalize clips from different sources to the same scale.
+    /// In the rest pose, every position and rotation channel is `0`. The lowest end
+    /// site is the one with the smallest `y` coordinate. Returns `0.0` if the `Bvh`
+    /// has no end sites.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 90.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Leg
+    ///         {
+    ///             OFFSET 0.0 -40.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 -50.0 0.0
+    ///             }
+    ///         }
+    ///         JOINT Head
+    ///         {
+    ///             OFFSET 0.0 60.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 10.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.estimate_character_scale(), 90.0);
+    /// ```
+    pub fn estimate_character_scale(&self) -> f32 {
+        let transforms = self.rest_world_transforms();
+        let root = match self.joints.iter().position(JointData::is_root) {
+            Some(root) => math::mat_get_translation(&transforms[root]),
+            None => return 0.0,
+        };
+
+        self.joints
+            .iter()
+            .zip(transforms.iter())
+            .filter_map(|(joint, transform)| {
+                joint
+                    .end_site()
+                    .map(|end_site| math::transform_point(transform, *end_site))
+            })
+            .min_by(|a, b| a[1].partial_cmp(&b[1]).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|lowest| {
+                length(&[
+                    lowest[0] - root[0],
+                    lowest[1] - root[1],
+                    lowest[2] - root[2],
+                ])
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// Guesses the up axis of the `Bvh` from its rest pose, such as to choose a
+    /// default coordinate system for a file from an unknown exporter.
+    ///
+    /// This is a heuristic. The bones of the rest pose are weighted by their
+    /// squared length, and the axis along which they are mostly aligned is
+    /// returned. For a humanoid in a T-pose or an A-pose, the longest bones are
+    /// those of the legs and the spine, which point along the up axis, while the
+    /// many short bones of the hands contribute little. Skeletons which are lying
+    /// down in their rest pose, or which are not humanoid, may give a wrong
+    /// result. The sign of the axis is not inferred.
+    ///
+    /// Returns [`Axis::Y`] if the skeleton has no bones with a length.
+    ///
+    /// The result can be passed to [`Bvh::root_heading_series_about`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, Axis};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 90.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Leg
+    ///         {
+    ///             OFFSET 10.0 0.0 -45.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 0.0 -45.0
+    ///             }
+    ///         }
+    ///         JOINT Arm
+    ///         {
+    ///             OFFSET 20.0 0.0 50.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 60.0 0.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.infer_up_axis(), Axis::Z);
+    /// ```
+    ///
+    /// [`Axis::Y`]: enum.Axis.html#variant.Y
+    /// [`Bvh::root_heading_series_about`]: struct.Bvh.html#method.root_heading_series_about
+    pub fn infer_up_axis(&self) -> Axis {
+        let mut weights = [0.0f64; 3];
+        let mut add_bone = |offset: &Offset| {
+            for (weight, &value) in weights.iter_mut().zip(offset.iter()) {
+                if value.is_finite() {
+                    *weight += f64::from(value) * f64::from(value);
+                }
+            }
+        };
+        for joint in &self.joints {
+            if !joint.is_root() {
+                add_bone(joint.offset());
+            }
+            if let Some(end_site) = joint.end_site() {
+                add_bone(end_site);
+            }
+        }
+
+        [Axis::Z, Axis::X].iter().fold(Axis::Y, |best, &axis| {
+            if weights[axis as usize] > weights[best as usize] {
+                axis
+            } else {
+                best
+            }
+        })
+    }
+
+    /// Checks the skeleton of the `Bvh` for offsets which are likely to be corrupt,
+    /// such as before computing forward kinematics in a pipeline. The returned
+    /// diagnostics are in joint order, and have no line.
+    ///
+    /// The following problems are reported:
+    ///
+    /// * [`DiagnosticKind::NonFiniteOffset`] for any offset or end site offset
+    ///   with an infinite or `NaN` value.
+    /// * [`DiagnosticKind::LargeOffset`] for any offset or end site offset longer
+    ///   than `max_offset_length`. The offset of a root joint only places the
+    ///   skeleton, so it is not checked.
+    /// * [`DiagnosticKind::ZeroLengthEndSite`] for any end site with a zero
+    ///   offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, diagnostic::DiagnosticKind};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5000.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 0.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let kinds: Vec<_> = bvh
+    ///     .sanity_check_skeleton(1000.0)
+    ///     .into_iter()
+    ///     .map(|diagnostic| diagnostic.kind)
+    ///     .collect();
+    /// assert_eq!(
+    ///     kinds,
+    ///     [
+    ///         DiagnosticKind::LargeOffset { joint_index: 1, end_site: false, length: 5000.0 },
+    ///         DiagnosticKind::ZeroLengthEndSite { joint_index: 1 },
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// [`DiagnosticKind::NonFiniteOffset`]: diagnostic/enum.DiagnosticKind.html#variant.NonFiniteOffset
+    /// [`DiagnosticKind::LargeOffset`]: diagnostic/enum.DiagnosticKind.html#variant.LargeOffset
+    /// [`DiagnosticKind::ZeroLengthEndSite`]: diagnostic/enum.DiagnosticKind.html#variant.ZeroLengthEndSite
+    pub fn sanity_check_skeleton(&self, max_offset_length: f32) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        let mut check = |joint_index, offset: &Offset, end_site, check_length| {
+            let kind = if offset.iter().any(|v| !v.is_finite()) {
+                DiagnosticKind::NonFiniteOffset {
+                    joint_index,
+                    end_site,
+                }
+            } else if check_length && length(offset) > max_offset_length {
+                DiagnosticKind::LargeOffset {
+                    joint_index,
+                    end_site,
+                    length: length(offset),
+                }
+            } else if end_site && *offset == [0.0; 3] {
+                DiagnosticKind::ZeroLengthEndSite { joint_index }
+            } else {
+                return;
+            };
+            diagnostics.push(Diagnostic::new(kind, None));
+        };
+
+        for (index, joint) in self.joints.iter().enumerate() {
+            check(index, joint.offset(), false, !joint.is_root());
+            if let Some(end_site) = joint.end_site() {
+                check(index, end_site, true, true);
+            }
+        }
+        diagnostics
+    }
+
+    /// Checks that only the root joints of the `Bvh` have position channels, as
+    /// assumed by many retargeting pipelines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 1 Yposition
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let err = bvh.assert_root_only_translation().unwrap_err();
+    /// assert_eq!(err.joints(), [(1, "Chest".into())]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing every non-root joint which has a position channel.
+    pub fn assert_root_only_translation(&self) -> Result<(), NonRootTranslationError> {
+        let joints: Vec<_> = self
+            .joints
+            .iter()
+            .enumerate()
+            .filter(|(_, joint)| {
+                !joint.is_root()
+                    && joint
+                        .channels()
+                        .iter()
+                        .any(|channel| channel.channel_type().is_position())
+            })
+            .map(|(index, joint)| (index, BString::from(joint.name())))
+            .collect();
+
+        if joints.is_empty() {
+            Ok(())
+        } else {
+            Err(NonRootTranslationError::new(joints))
+        }
+    }
+
+    /// Checks that the `Bvh` has every joint in `expected`, each with at least the
+    /// listed channel types, such as to reject files which do not fit a target rig
+    /// before importing them.
+    ///
+    /// `expected` lists `(name, channel types)` pairs. The joints may have other
+    /// channels, and the `Bvh` may have other joints. The channels are compared by
+    /// type only, so their order does not matter.
+    ///
+    /// # Errors
+    ///
+    /// Returns every mismatch which was found, in the order of `expected`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, ChannelType, RigMismatch};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let rig: &[(&[u8], &[ChannelType])] = &[
+    ///     (b"Hips", &[ChannelType::PositionY]),
+    ///     (b"Head", &[]),
+    /// ];
+    /// assert_eq!(
+    ///     bvh.matches_rig(rig),
+    ///     Err(vec![RigMismatch::MissingJoint { name: "Head".into() }])
+    /// );
+    /// ```
+    pub fn matches_rig(
+        &self,
+        expected: &[(&[u8], &[ChannelType])],
+    ) -> Result<(), Vec<RigMismatch>> {
+        let mut mismatches = vec![];
+        for &(name, channels) in expected {
+            let found = self
+                .joints
+                .iter()
+                .enumerate()
+                .find(|(_, joint)| joint.name() == name);
+            let (joint_index, joint) = match found {
+                Some(found) => found,
+                None => {
+                    mismatches.push(RigMismatch::MissingJoint { name: name.into() });
+                    continue;
+                }
+            };
+
+            let missing: Vec<_> = channels
+                .iter()
+                .copied()
+                .filter(|&ty| {
+                    !joint
+                        .channels()
+                        .iter()
+                        .any(|channel| channel.channel_type() == ty)
+                })
+                .collect();
+            if !missing.is_empty() {
+                mismatches.push(RigMismatch::MissingChannels {
+                    name: name.into(),
+                    joint_index,
+                    channels: missing,
+                });
+            }
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+
+    /// Pairs each left joint of the `Bvh` with its right counterpart, such as to
+    /// mirror an animation.
+    ///
+    /// A left and a right joint are paired if their names differ only by one of the
+    /// `(left, right)` prefixes or suffixes in `patterns`, such as
+    /// [`Bvh::DEFAULT_SYMMETRY_PATTERNS`], and their positions in the rest pose are
+    /// mirror images across the `YZ` plane to within a distance of `tolerance`.
+    /// The pairs are returned as `(left, right)` joint indices, in the order of the
+    /// left joints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, Bvh};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         JOINT LeftLeg
+    ///         {
+    ///             OFFSET 1.0 -1.0 0.0
+    ///             CHANNELS 0
+    ///         }
+    ///         JOINT RightLeg
+    ///         {
+    ///             OFFSET -1.0 -1.0 0.0
+    ///             CHANNELS 0
+    ///         }
+    ///         JOINT LeftTail
+    ///         {
+    ///             OFFSET 0.0 0.0 -1.0
+    ///             CHANNELS 0
+    ///         }
+    ///         JOINT RightTail
+    ///         {
+    ///             OFFSET 0.0 0.0 -2.0
+    ///             CHANNELS 0
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let pairs = bvh.infer_symmetry_pairs(Bvh::DEFAULT_SYMMETRY_PATTERNS, 0.1);
+    /// assert_eq!(pairs, [(1, 2)]);
+    /// ```
+    ///
+    /// [`Bvh::DEFAULT_SYMMETRY_PATTERNS`]: struct.Bvh.html#associatedconstant.DEFAULT_SYMMETRY_PATTERNS
+    pub fn infer_symmetry_pairs(
+        &self,
+        patterns: &[(&[u8], &[u8])],
+        tolerance: f32,
+    ) -> Vec<(usize, usize)> {
+        let positions: Vec<Offset> = self
+            .rest_world_transforms()
+            .iter()
+            .map(math::mat_get_translation)
+            .collect();
+        let mut paired = vec![false; self.joints.len()];
+        let mut pairs = vec![];
+
+        for (left, joint) in self.joints.iter().enumerate() {
+            if paired[left] {
+                continue;
+            }
+            let name = joint.name();
+            let right = patterns.iter().find_map(|&(left_pattern, right_pattern)| {
+                let right_name = if let Some(rest) = name.strip_prefix(left_pattern) {
+                    [right_pattern, rest].concat()
+                } else if let Some(rest) = name.strip_suffix(left_pattern) {
+                    [rest, right_pattern].concat()
+                } else {
+                    return None;
+                };
+
+                let [x, y, z] = positions[left];
+                (0..self.joints.len()).find(|&right| {
+                    let [rx, ry, rz] = positions[right];
+                    !paired[right]
+                        && right != left
+                        && self.joints[right].name() == &right_name[..]
+                        && length(&[x + rx, y - ry, z - rz]) <= tolerance
+                })
+            });
+
+            if let Some(right) = right {
+                paired[left] = true;
+                paired[right] = true;
+                pairs.push((left, right));
+            }
+        }
+        pairs
+    }
+
+    /// Returns the most common rotation order among the joints of the `Bvh`, such as
+    /// to pick a default order when converting rotations. The order lists the axes
+    /// in the order in which the rotation channels are listed.
+    ///
+    /// Only joints with exactly three rotation channels, one about each axis, are
+    /// counted. `None` is returned if there are no such joints, or if no single
+    /// order is used by more than half of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, Axis};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.dominant_rotation_order(), Some([Axis::Z, Axis::X, Axis::Y]));
+    /// ```
+    pub fn dominant_rotation_order(&self) -> Option<[Axis; 3]> {
+        let mut counts = HashMap::<[Axis; 3], usize>::new();
+        let mut total = 0;
+        for order in self.joints.iter().filter_map(rotation_order) {
+            *counts.entry(order).or_insert(0) += 1;
+            total += 1;
+        }
+
+        counts
+            .into_iter()
+            .find(|&(_, count)| count * 2 > total)
+            .map(|(order, _)| order)
+    }
+
+    /// Returns a hash of the skeleton of the `Bvh`, which is the same for any two
+    /// clips with the same skeleton, regardless of their motion.
+    ///
+    /// The hash includes the name, parent, offset, end site and channels of every
+    /// joint. It is computed with [`DefaultHasher`], so it should not be persisted, as
+    /// it may change between versions of Rust or of this crate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    /// };
+    ///
+    /// let mut moved = bvh.clone();
+    /// moved.frames_mut().next().unwrap()[0] = 1.0;
+    /// assert_eq!(bvh.skeleton_hash(), moved.skeleton_hash());
+    /// ```
+    ///
+    /// [`DefaultHasher`]: https://doc.rust-lang.org/std/collections/hash_map/struct.DefaultHasher.html
+    pub fn skeleton_hash(&self) -> u64 {
+        // Adding `0.0` maps `-0.0` to `0.0`, so that equal offsets hash equally.
+        fn hash_offset<H: Hasher>(offset: &Offset, hasher: &mut H) {
+            for v in offset {
+                (v + 0.0).to_bits().hash(hasher);
+            }
+        }
+
+        let mut hasher = DefaultHasher::new();
+        self.joints.len().hash(&mut hasher);
+        for joint in &self.joints {
+            joint.name().hash(&mut hasher);
+            joint.parent_index().hash(&mut hasher);
+            hash_offset(joint.offset(), &mut hasher);
+            match joint.end_site() {
+                Some(end_site) => {
+                    true.hash(&mut hasher);
+                    hash_offset(end_site, &mut hasher);
+                }
+                None => false.hash(&mut hasher),
+            }
+            joint.channels().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Returns the indices of the joints along the longest path from the root joint
+    /// to a joint without children, measured by the number of joints in the path.
+    ///
+    /// The path starts at the root joint. If several paths have the same length,
+    /// then the first one in the order of [`Bvh::joints`] is returned. Returns an
+    /// empty `Vec` if the `Bvh` has no joints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         JOINT Leg
+    ///         {
+    ///             OFFSET 0.0 -10.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 -10.0 0.0
+    ///             }
+    ///         }
+    ///         JOINT Spine
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             JOINT Neck
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///                 CHANNELS 0
+    ///                 End Site
+    ///                 {
+    ///                     OFFSET 0.0 1.0 0.0
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.longest_chain(), [0, 2, 3]);
+    /// assert_eq!(bvh.longest_chain_by_length(), [0, 1]);
+    /// ```
+    ///
+    /// [`Bvh::joints`]: struct.Bvh.html#method.joints
+    pub fn longest_chain(&self) -> Vec<usize> {
+        self.longest_chain_by(|_| 1.0)
+    }
+
+    /// Returns the indices of the joints along the longest path from the root joint
+    /// to a joint without children, measured by the summed length of the bones in
+    /// the path, including the end site of the last joint.
+    ///
+    /// See [`Bvh::longest_chain`] for more information.
+    ///
+    /// [`Bvh::longest_chain`]: struct.Bvh.html#method.longest_chain
+    pub fn longest_chain_by_length(&self) -> Vec<usize> {
+        self.longest_chain_by(|joint| {
+            let bone = if joint.is_root() {
+                0.0
+            } else {
+                length(joint.offset())
+            };
+            bone + joint.end_site().map(length).unwrap_or(0.0)
+        })
+    }
+
+    fn longest_chain_by<F: Fn(&JointData) -> f32>(&self, weight: F) -> Vec<usize> {
+        let mut totals: Vec<f32> = Vec::with_capacity(self.joints.len());
+        let mut has_children = vec![false; self.joints.len()];
+        for joint in &self.joints {
+            let parent_total = match joint.parent_index() {
+                Some(parent) => {
+                    has_children[parent] = true;
+                    totals[parent]
+                }
+                None => 0.0,
+            };
+            totals.push(parent_total + weight(joint));
+        }
+
+        let mut leaf = None::<usize>;
+        for (i, total) in totals.iter().enumerate() {
+            if !has_children[i] && leaf.map(|l| *total > totals[l]).unwrap_or(true) {
+                leaf = Some(i);
+            }
+        }
+
+        let mut chain = Vec::new();
+        let mut current = leaf;
+        while let Some(index) = current {
+            chain.push(index);
+            current = self.joints[index].parent_index();
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Heuristically match the joints of `self` to the joints of `other` by their
+    /// position in the skeleton, to help build a name map for retargeting.
+    ///
+    /// Starting from the root joints, the children of each matched pair of joints
+    /// are matched greedily, preferring children with similar names, similar offset
+    /// directions, the same sibling index, and the same number of children. Names
+    /// are compared ignoring case.
+    ///
+    /// Returns pairs of `(self_index, other_index)`, in the order of the joints of
+    /// `self`. Joints which could not be matched are omitted. The result is a best
+    /// effort, and should be checked before it is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let a = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         JOINT LeftLeg
+    ///         {
+    ///             OFFSET 1.0 -1.0 0.0
+    ///             CHANNELS 0
+    ///         }
+    ///         JOINT RightLeg
+    ///         {
+    ///             OFFSET -1.0 -1.0 0.0
+    ///             CHANNELS 0
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let b = bvh! {
+    ///     HIERARCHY
+    ///     ROOT pelvis
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         JOINT r_leg
+    ///         {
+    ///             OFFSET -1.0 -1.0 0.0
+    ///             CHANNELS 0
+    ///         }
+    ///         JOINT l_leg
+    ///         {
+    ///             OFFSET 1.0 -1.0 0.0
+    ///             CHANNELS 0
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(a.auto_name_map(&b), [(0, 0), (1, 2), (2, 1)]);
+    /// ```
+    pub fn auto_name_map(&self, other: &Bvh) -> Vec<(usize, usize)> {
+        let self_children = children_of(&self.joints);
+        let other_children = children_of(&other.joints);
+
+        let mut pairs = Vec::new();
+        let mut pending = match (self.joints.first(), other.joints.first()) {
+            (Some(_), Some(_)) => vec![(0, 0)],
+            _ => vec![],
+        };
+
+        while let Some((a, b)) = pending.pop() {
+            pairs.push((a, b));
+
+            let mut candidates = other_children[b].clone();
+            for (sibling, &child) in self_children[a].iter().enumerate() {
+                let best = candidates
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &candidate)| {
+                        let other_sibling = other_children[b]
+                            .iter()
+                            .position(|&c| c == candidate)
+                            .unwrap_or(0);
+                        let mut score = 3.0
+                            * name_similarity(
+                                self.joints[child].name(),
+                                other.joints[candidate].name(),
+                            );
+                        score += 2.0
+                            * direction_similarity(
+                                self.joints[child].offset(),
+                                other.joints[candidate].offset(),
+                            );
+                        if sibling == other_sibling {
+                            score += 1.0;
+                        }
+                        if self_children[child].len() == other_children[candidate].len() {
+                            score += 1.0;
+                        }
+                        (i, score)
+                    })
+                    .max_by(|x, y| x.1.partial_cmp(&y.1).unwrap_or(std::cmp::Ordering::Equal));
+
+                if let Some((i, _)) = best {
+                    pending.push((child, candidates.remove(i)));
+                }
+            }
+        }
+
+        pairs.sort_unstable();
+        pairs
+    }
+
+    /// Compute the minimum, maximum, mean and standard deviation of every channel
+    /// over all frames, keyed by the index of the joint and the type of the channel.
+    ///
+    /// This is useful to find channels which never move, or which have suspicious
+    /// ranges. If a joint has several channels of the same type, then only the last
+    /// one is included. If the `Bvh` has no frames, then the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, ChannelType};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 2 Xposition Zrotation
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     1.0 10.0
+    ///     3.0 10.0
+    /// };
+    ///
+    /// let stats = bvh.channel_stats();
+    /// let x = stats[&(0, ChannelType::PositionX)];
+    /// assert_eq!((x.min, x.max, x.mean, x.std_dev), (1.0, 3.0, 2.0, 1.0));
+    /// assert_eq!(stats[&(0, ChannelType::RotationZ)].range(), 0.0);
+    /// ```
+    pub fn channel_stats(&self) -> HashMap<(usize, ChannelType), ChannelStats> {
+        let mut stats = HashMap::new();
+        let num_frames = self.frames().len();
+        if num_frames == 0 {
+            return stats;
+        }
+
+        for (index, joint) in self.joints.iter().enumerate() {
+            for channel in joint.channels() {
+                let values = self
+                    .frames()
+                    .map(|frame| frame[channel])
+                    .collect::<Vec<_>>();
+                let (mut min, mut max, mut sum) = (f32::INFINITY, f32::NEG_INFINITY, 0.0f64);
+                for &value in &values {
+                    min = min.min(value);
+                    max = max.max(value);
+                    sum += f64::from(value);
+                }
+                let mean = sum / num_frames as f64;
+                let variance = values
+                    .iter()
+                    .map(|&value| (f64::from(value) - mean).powi(2))
+                    .sum::<f64>()
+                    / num_frames as f64;
+
+                stats.insert(
+                    (index, channel.channel_type()),
+                    ChannelStats {
+                        min,
+                        max,
+                        mean: mean as f32,
+                        std_dev: variance.sqrt() as f32,
+                    },
+                );
+            }
+        }
+
+        stats
+    }
+
+    /// Returns the magnitude spectrum of the values of the channel of type `ty` of
+    /// the joint at index `joint` over every frame, such as to find the dominant
+    /// frequencies of a channel when choosing a smoothing cutoff.
+    ///
+    /// The spectrum is one-sided: for `n` frames, it has `n / 2 + 1` bins, and bin
+    /// `k` is the magnitude of the discrete Fourier transform at a frequency of
+    /// `k / (n * frame_time)` hertz, so that bin `0` is the sum of the values.
+    ///
+    /// Returns `None` if `joint` is out of range or the joint does not have a
+    /// channel of type `ty`. If the `Bvh` has no frames, then the spectrum is
+    /// empty.
+    ///
+    /// The transform is computed directly, which takes time proportional to the
+    /// square of the number of frames.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, ChannelType};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///     }
+    ///     MOTION
+    ///     Frames: 4
+    ///     Frame Time: 0.25
+    ///     1.0
+    ///     -1.0
+    ///     1.0
+    ///     -1.0
+    /// };
+    ///
+    /// let spectrum = bvh.channel_spectrum(0, ChannelType::PositionX).unwrap();
+    /// assert_eq!(spectrum.len(), 3);
+    /// assert!(spectrum[0].abs() < 1.0e-4 && spectrum[1].abs() < 1.0e-4);
+    /// assert!((spectrum[2] - 4.0).abs() < 1.0e-4);
+    /// assert!(bvh.channel_spectrum(0, ChannelType::PositionY).is_none());
+    /// ```
+    pub fn channel_spectrum(&self, joint: usize, ty: ChannelType) -> Option<Vec<f32>> {
+        let column = self.channel_index(joint, ty)?;
+        let values = self
+            .frame_rows()
+            .map(|row| f64::from(row[column]))
+            .collect::<Vec<_>>();
+        let n = values.len();
+        if n == 0 {
+            return Some(vec![]);
+        }
+
+        let spectrum = (0..=n / 2)
+            .map(|k| {
+                let (mut re, mut im) = (0.0f64, 0.0f64);
+                for (j, &value) in values.iter().enumerate() {
+                    let angle = -2.0 * std::f64::consts::PI * ((k * j) % n) as f64 / n as f64;
+                    re += value * angle.cos();
+                    im += value * angle.sin();
+                }
+                re.hypot(im) as f32
+            })
+            .collect();
+        Some(spectrum)
+    }
+
+    /// Returns the indices of the joints whose channels never change by more than
+    /// `epsilon` over the whole clip, in joint order, such as to find joints which
+    /// could be collapsed into their parent when simplifying a skeleton.
+    ///
+    /// A joint is static if the range of every one of its channels, as returned by
+    /// [`ChannelStats::range`], is at most `epsilon`. Joints without any channels
+    /// are always static. If the `Bvh` has no frames, then every joint is static.
+    /// [`Bvh::prune_constant_channels`] removes the constant channels themselves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hand
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Finger
+    ///         {
+    ///             OFFSET 0.0 2.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0 10.0 0.0 0.0
+    ///     1.0 0.0 0.0 10.0 0.0 0.0
+    /// };
+    ///
+    /// assert_eq!(bvh.static_joints(1.0e-4), [1]);
+    /// ```
+    ///
+    /// [`ChannelStats::range`]: struct.ChannelStats.html#method.range
+    /// [`Bvh::prune_constant_channels`]: struct.Bvh.html#method.prune_constant_channels
+    pub fn static_joints(&self, epsilon: f32) -> Vec<usize> {
+        let mut ranges = vec![(f32::INFINITY, f32::NEG_INFINITY); self.num_channels];
+        for frame in self.frames() {
+            for ((min, max), &value) in ranges.iter_mut().zip(frame.as_slice()) {
+                *min = min.min(value);
+                *max = max.max(value);
+            }
+        }
+        let is_constant = |index: usize| {
+            let (min, max) = ranges[index];
+            min > max || max - min <= epsilon
+        };
+
+        self.joints
+            .iter()
+            .enumerate()
+            .filter(|(_, joint)| {
+                joint
+                    .channels()
+                    .iter()
+                    .all(|channel| is_constant(channel.motion_index()))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+#[inline]
+/// Returns the order of the rotation channels of `joint`, if it has exactly three
+/// rotation channels about different axes.
+fn rotation_order(joint: &JointData) -> Option<[Axis; 3]> {
+    let mut axes = joint
+        .channels()
+        .iter()
+        .map(|channel| channel.channel_type())
+        .filter(|ty| ty.is_rotation())
+        .map(|ty| ty.axis());
+    match (axes.next(), axes.next(), axes.next(), axes.next()) {
+        (Some(a), Some(b), Some(c), None) if a != b && b != c && a != c => Some([a, b, c]),
+        _ => None,
+    }
+}
+
+fn length(offset: &Offset) -> f32 {
+    offset.iter().map(|v| v * v).sum::<f32>().sqrt()
+}
+
+/// Returns the indices of the children of each joint.
+fn children_of(joints: &[JointData]) -> Vec<Vec<usize>> {
+    let mut children = vec![Vec::new(); joints.len()];
+    for (i, joint) in joints.iter().enumerate() {
+        if let Some(parent) = joint.parent_index() {
+            children[parent].push(i);
+        }
+    }
+    children
+}
+
+/// Returns the cosine of the angle between `a` and `b`, or `0.0` if either has
+/// zero length.
+fn direction_similarity(a: &Offset, b: &Offset) -> f32 {
+    let lengths = length(a) * length(b);
+    if lengths > 0.0 {
+        (a[0] * b[0] + a[1] * b[1] + a[2] * b[2]) / lengths
+    } else {
+        0.0
+    }
+}
+
+/// Returns the similarity of the names `a` and `b` ignoring case, from `0.0` for
+/// completely different names to `1.0` for equal names, based on the length of
+/// their longest common subsequence.
+fn name_similarity(a: &[u8], b: &[u8]) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let mut lengths = vec![0usize; b.len() + 1];
+    for &ca in a {
+        let mut diagonal = 0;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = lengths[j + 1];
+            lengths[j + 1] = if ca.eq_ignore_ascii_case(&cb) {
+                diagonal + 1
+            } else {
+                above.max(lengths[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    lengths[b.len()] as f32 / a.len().max(b.len()) as f32
+}
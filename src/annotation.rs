@@ -0,0 +1,95 @@
+use crate::Bvh;
+use bstr::BString;
+use std::collections::BTreeMap;
+
+impl Bvh {
+    /// Attach the label `annotation` to the frame at index `frame`, such as
+    /// `"footstep"` or `"attack start"`.
+    ///
+    /// The bvh format cannot store annotations, so they are ignored when writing
+    /// the `Bvh`. Annotations are keyed by frame index, and so are not moved when
+    /// frames are inserted or removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// let mut bvh = Bvh::new();
+    /// bvh.annotate_frame(5, "footstep");
+    /// bvh.annotate_frame(5, "attack start");
+    ///
+    /// assert_eq!(bvh.annotations_at(5), &["footstep", "attack start"]);
+    /// assert!(bvh.annotations_at(4).is_empty());
+    /// ```
+    #[inline]
+    pub fn annotate_frame<A: Into<BString>>(&mut self, frame: usize, annotation: A) {
+        self.annotations
+            .entry(frame)
+            .or_default()
+            .push(annotation.into());
+    }
+
+    /// Returns the labels attached to the frame at index `frame`, in the order in
+    /// which they were added.
+    #[inline]
+    pub fn annotations_at(&self, frame: usize) -> &[BString] {
+        self.annotations
+            .get(&frame)
+            .map(|annotations| &annotations[..])
+            .unwrap_or(&[])
+    }
+
+    /// Returns all of the frame labels in the `Bvh`, keyed by frame index.
+    #[inline]
+    pub fn annotations(&self) -> &BTreeMap<usize, Vec<BString>> {
+        &self.annotations
+    }
+
+    /// Remove all of the labels attached to the frame at index `frame`,
+    /// returning them.
+    #[inline]
+    pub fn clear_annotations_at(&mut self, frame: usize) -> Vec<BString> {
+        self.annotations.remove(&frame).unwrap_or_default()
+    }
+
+    /// Returns the key-value metadata of the `Bvh`, such as the provenance of a
+    /// clip.
+    ///
+    /// Metadata is read from `# key: value` comments before the `HIERARCHY`
+    /// keyword when parsing with [`ParseOptions::with_metadata_comments`], and is
+    /// written in the same form with [`WriteOptions::with_metadata_comments`].
+    /// Otherwise, the `Bvh` has no metadata unless it is added with
+    /// [`Bvh::metadata_mut`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// use bstr::ByteSlice;
+    ///
+    /// let mut bvh = Bvh::new();
+    /// bvh.metadata_mut().insert("source".into(), "studio capture".into());
+    ///
+    /// assert_eq!(bvh.metadata()[b"source".as_bstr()], "studio capture");
+    /// ```
+    ///
+    /// [`ParseOptions::with_metadata_comments`]: read/struct.ParseOptions.html#method.with_metadata_comments
+    /// [`WriteOptions::with_metadata_comments`]: write/struct.WriteOptions.html#method.with_metadata_comments
+    /// [`Bvh::metadata_mut`]: struct.Bvh.html#method.metadata_mut
+    #[inline]
+    pub fn metadata(&self) -> &BTreeMap<BString, BString> {
+        &self.metadata
+    }
+
+    /// Returns a mutable reference to the key-value metadata of the `Bvh`. See
+    /// [`Bvh::metadata`].
+    ///
+    /// Keys should not contain `:`, and neither keys nor values should contain line
+    /// breaks, so that they can be written as comments and read back.
+    ///
+    /// [`Bvh::metadata`]: struct.Bvh.html#method.metadata
+    #[inline]
+    pub fn metadata_mut(&mut self) -> &mut BTreeMap<BString, BString> {
+        &mut self.metadata
+    }
+}
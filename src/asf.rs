@@ -0,0 +1,245 @@
+use crate::{joint::JointData, Axis, Bvh, ChannelType};
+use std::io::{self, Write};
+
+impl Bvh {
+    /// Writes the skeleton of the `Bvh` to `writer` in the Acclaim skeleton (`asf`)
+    /// format, such as for tools which consume `asf`/`amc` files. The motion is not
+    /// written.
+    ///
+    /// The conversion is approximate, because `asf` describes each bone by a
+    /// direction and a length, with its own rest orientation, while `bvh` describes
+    /// each joint by an offset from its parent:
+    ///
+    /// * The root joint becomes the `:root` section, with its offset as the
+    ///   position and its channels as the order. Any other root joints are not
+    ///   written.
+    /// * Every other joint becomes a bone with the joint's name, running from its
+    ///   parent to the joint. Its direction is the normalized offset of the joint,
+    ///   and its length is the length of the offset; a zero offset has the direction
+    ///   `0 0 0`. Its degrees of freedom are the joint's position and rotation
+    ///   channels in order, without limits.
+    /// * Every end site becomes a bone without degrees of freedom, named after its
+    ///   joint with an `_End` suffix.
+    /// * Every bone has a zero `axis`, since `bvh` has no rest orientations, and
+    ///   angles are in degrees. Lengths are in the units of the `Bvh`.
+    ///
+    /// Whitespace in joint names is replaced with `_`, since `asf` names cannot
+    /// contain whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 2.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let mut asf = vec![];
+    /// bvh.write_asf(&mut asf)?;
+    /// let asf = String::from_utf8(asf).unwrap();
+    /// assert!(asf.contains("    name Chest\n    direction 0 1 0\n    length 5\n"));
+    /// assert!(asf.contains("    dof rz rx ry\n"));
+    /// assert!(asf.contains("  root Chest\n  Chest Chest_End\n"));
+    /// # Result::<(), std::io::Error>::Ok(())
+    /// ```
+    pub fn write_asf<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        #[inline(never)]
+        fn write_asf_(bvh: &Bvh, writer: &mut dyn Write) -> io::Result<()> {
+            let root = match bvh.joints.iter().position(JointData::is_root) {
+                Some(root) => root,
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "The bvh has no root joint",
+                    ))
+                }
+            };
+            let root_joint = &bvh.joints[root];
+            let is_bone = |index: usize| bvh.joints[index].parent_index().is_some();
+
+            writeln!(writer, ":version 1.10")?;
+            write!(writer, ":name ")?;
+            write_name(writer, root_joint.name())?;
+            writeln!(writer)?;
+            writeln!(writer, ":units")?;
+            writeln!(writer, "  mass 1.0")?;
+            writeln!(writer, "  length 1.0")?;
+            writeln!(writer, "  angle deg")?;
+            writeln!(writer, ":documentation")?;
+            writeln!(writer, "  Skeleton converted from a bvh file")?;
+
+            writeln!(writer, ":root")?;
+            write!(writer, "  order")?;
+            for channel in root_joint.channels() {
+                let ty = channel.channel_type();
+                if let Some(dof) = dof_name(ty) {
+                    write!(writer, " {}", dof.to_ascii_uppercase())?;
+                }
+            }
+            writeln!(writer)?;
+            let axis = root_joint
+                .channels()
+                .iter()
+                .map(|channel| channel.channel_type())
+                .filter(|ty| ty.is_rotation())
+                .map(|ty| axis_name(ty.axis()))
+                .collect::<String>();
+            let axis = if axis.len() == 3 { &axis[..] } else { "XYZ" };
+            writeln!(writer, "  axis {}", axis)?;
+            let [x, y, z] = root_joint.offset();
+            writeln!(writer, "  position {} {} {}", x, y, z)?;
+            writeln!(writer, "  orientation 0 0 0")?;
+
+            writeln!(writer, ":bonedata")?;
+            let mut id = 0;
+            for (index, joint) in bvh.joints.iter().enumerate() {
+                if is_bone(index) {
+                    id += 1;
+                    write_bone(writer, id, joint.name(), b"", joint.offset(), Some(joint))?;
+                }
+                if let Some(end_site) = joint.end_site() {
+                    id += 1;
+                    write_bone(writer, id, joint.name(), b"_End", end_site, None)?;
+                }
+            }
+
+            writeln!(writer, ":hierarchy")?;
+            writeln!(writer, "  begin")?;
+            for (index, joint) in bvh.joints.iter().enumerate() {
+                if index != root && !is_bone(index) {
+                    continue;
+                }
+
+                let children = bvh
+                    .joints
+                    .iter()
+                    .filter(|child| child.parent_index() == Some(index))
+                    .collect::<Vec<_>>();
+                if children.is_empty() && joint.end_site().is_none() {
+                    continue;
+                }
+
+                if index == root {
+                    write!(writer, "  root")?;
+                } else {
+                    write!(writer, "  ")?;
+                    write_name(writer, joint.name())?;
+                }
+                for child in children {
+                    write!(writer, " ")?;
+                    write_name(writer, child.name())?;
+                }
+                if joint.end_site().is_some() {
+                    write!(writer, " ")?;
+                    write_name(writer, joint.name())?;
+                    write!(writer, "_End")?;
+                }
+                writeln!(writer)?;
+            }
+            writeln!(writer, "  end")?;
+            writer.flush()
+        }
+
+        write_asf_(self, writer)
+    }
+}
+
+/// Write a single bone of the `:bonedata` section. The degrees of freedom are
+/// taken from the channels of `joint`, if it is given.
+fn write_bone(
+    writer: &mut dyn Write,
+    id: usize,
+    name: &[u8],
+    suffix: &[u8],
+    offset: &[f32; 3],
+    joint: Option<&JointData>,
+) -> io::Result<()> {
+    let length = offset.iter().map(|v| v * v).sum::<f32>().sqrt();
+    let direction = if length > 0.0 {
+        [offset[0] / length, offset[1] / length, offset[2] / length]
+    } else {
+        [0.0; 3]
+    };
+
+    writeln!(writer, "  begin")?;
+    writeln!(writer, "    id {}", id)?;
+    write!(writer, "    name ")?;
+    write_name(writer, name)?;
+    writer.write_all(suffix)?;
+    writeln!(writer)?;
+    writeln!(
+        writer,
+        "    direction {} {} {}",
+        direction[0], direction[1], direction[2]
+    )?;
+    writeln!(writer, "    length {}", length)?;
+    writeln!(writer, "    axis 0 0 0 XYZ")?;
+
+    let dofs = joint
+        .map(|joint| {
+            joint
+                .channels()
+                .iter()
+                .filter_map(|channel| dof_name(channel.channel_type()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if !dofs.is_empty() {
+        writeln!(writer, "    dof {}", dofs.join(" "))?;
+        for i in 0..dofs.len() {
+            let prefix = if i == 0 { "    limits" } else { "          " };
+            writeln!(writer, "{} (-inf inf)", prefix)?;
+        }
+    }
+    writeln!(writer, "  end")
+}
+
+/// Write `name`, replacing whitespace with `_`.
+fn write_name(writer: &mut dyn Write, name: &[u8]) -> io::Result<()> {
+    let name = name
+        .iter()
+        .map(|&b| if b.is_ascii_whitespace() { b'_' } else { b })
+        .collect::<Vec<_>>();
+    writer.write_all(&name)
+}
+
+/// Returns the `asf` degree of freedom for the channel type, or `None` if it
+/// cannot be represented.
+fn dof_name(ty: ChannelType) -> Option<&'static str> {
+    let name = match ty {
+        ChannelType::PositionX => "tx",
+        ChannelType::PositionY => "ty",
+        ChannelType::PositionZ => "tz",
+        ChannelType::RotationX => "rx",
+        ChannelType::RotationY => "ry",
+        ChannelType::RotationZ => "rz",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[inline]
+fn axis_name(axis: Axis) -> char {
+    match axis {
+        Axis::X => 'X',
+        Axis::Y => 'Y',
+        Axis::Z => 'Z',
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    errors::{LoadError, LoadJointsError},
+    Bvh,
+};
+use rayon::prelude::*;
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Load every file with the `bvh` extension in the directory `dir` in parallel.
+///
+/// The extension is matched ignoring case, and subdirectories are not searched.
+/// Each path is returned with the result of loading it, so that a file which fails
+/// to load does not prevent the others from loading. The results are sorted by
+/// path.
+///
+/// This function is only available with the `rayon` feature.
+///
+/// # Errors
+///
+/// Returns an error if the contents of `dir` cannot be read.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// for (path, result) in bvh_anim::load_dir("./data")? {
+///     match result {
+///         Ok(bvh) => println!("{}: {} frames", path.display(), bvh.frames().len()),
+///         Err(e) => println!("{}: {}", path.display(), e),
+///     }
+/// }
+/// # Result::<(), io::Error>::Ok(())
+/// ```
+pub fn load_dir<P: AsRef<Path>>(dir: P) -> io::Result<Vec<(PathBuf, Result<Bvh, LoadError>)>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_bvh = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("bvh"))
+            .unwrap_or(false);
+        if is_bvh && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths
+        .into_par_iter()
+        .map(|path| {
+            let result = File::open(&path)
+                .map_err(|e| LoadError::from(LoadJointsError::Io(e)))
+                .and_then(Bvh::from_reader);
+            (path, result)
+        })
+        .collect())
+}
@@ -0,0 +1,184 @@
+//! Contains non-fatal problems which may be reported about a `Bvh`.
+
+use std::{fmt, time::Duration};
+
+/// A problem which was found in a `Bvh`, but which did not prevent it from being
+/// used.
+///
+/// When parsing in lenient mode, malformed input which can be recovered from is
+/// reported as a `Diagnostic` instead of an error. See
+/// [`ParseOptions::read_with_diagnostics`][`ParseOptions::read_with_diagnostics`].
+///
+/// [`ParseOptions::read_with_diagnostics`]: ../read/struct.ParseOptions.html#method.read_with_diagnostics
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    /// What kind of problem was found.
+    pub kind: DiagnosticKind,
+    /// The line in the source bvh where the problem was found, if it was found
+    /// while parsing.
+    pub line: Option<usize>,
+}
+
+impl Diagnostic {
+    /// Create a new `Diagnostic` of the given `kind` at `line`.
+    #[inline]
+    pub const fn new(kind: DiagnosticKind, line: Option<usize>) -> Self {
+        Diagnostic { kind, line }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "{}: ", line)?;
+        }
+        fmt::Display::fmt(&self.kind, f)
+    }
+}
+
+/// The kinds of problem which may be reported by a [`Diagnostic`][`Diagnostic`].
+///
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// An `OFFSET` section did not have exactly 3 values. Missing values were set to
+    /// `0.0`, and extra values were ignored.
+    OffsetValueCount {
+        /// The number of values in the `OFFSET` section.
+        count: usize,
+    },
+    /// An `End Site` had a `CHANNELS` section, which was ignored. Its channels do
+    /// not have any motion values.
+    EndSiteChannelsIgnored,
+    /// The number of channels stated in a `CHANNELS` section did not match the
+    /// number of channel names listed after it. The listed channels were used.
+    ChannelCountMismatch {
+        /// The number of channels stated in the `CHANNELS` section.
+        expected: usize,
+        /// The number of channel names which were listed.
+        actual: usize,
+    },
+    /// The `HIERARCHY` keyword was missing, and the hierarchy was assumed to start
+    /// at the `ROOT` joint.
+    MissingHierarchy,
+    /// A row of the `MOTION` section had the wrong number of values, so that the
+    /// frame at `frame_index` started in the middle of a line.
+    MisalignedMotionRow {
+        /// The index of the first frame which was misaligned.
+        frame_index: usize,
+    },
+    /// The number of frames in the `MOTION` section did not match the `Frames`
+    /// line. The frames which were read are kept, and the values of an incomplete
+    /// last frame are ignored.
+    FrameCountMismatch {
+        /// The number of frames given by the `Frames` line.
+        expected: usize,
+        /// The number of complete frames which were read.
+        actual: usize,
+    },
+    /// The offset of a joint, or of its end site, has a value which is infinite or
+    /// `NaN`.
+    NonFiniteOffset {
+        /// The index of the joint.
+        joint_index: usize,
+        /// Whether the offset is the joint's end site rather than the joint itself.
+        end_site: bool,
+    },
+    /// The offset of a joint, or of its end site, is longer than the plausible
+    /// maximum.
+    LargeOffset {
+        /// The index of the joint.
+        joint_index: usize,
+        /// Whether the offset is the joint's end site rather than the joint itself.
+        end_site: bool,
+        /// The length of the offset.
+        length: f32,
+    },
+    /// The end site of a joint has a zero offset, so that the last bone of its
+    /// chain has no length.
+    ZeroLengthEndSite {
+        /// The index of the joint.
+        joint_index: usize,
+    },
+    /// The `Frame Time` line was missing, so the fallback frame time was used.
+    MissingFrameTime {
+        /// The frame time which was used instead.
+        frame_time: Duration,
+    },
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DiagnosticKind::OffsetValueCount { count } => write!(
+                f,
+                "the \"OFFSET\" section has {} values instead of 3",
+                count
+            ),
+            DiagnosticKind::EndSiteChannelsIgnored => {
+                f.write_str("the \"CHANNELS\" section of an \"End Site\" was ignored")
+            }
+            DiagnosticKind::ChannelCountMismatch { expected, actual } => write!(
+                f,
+                "the \"CHANNELS\" section states {} channels, but lists {}",
+                expected, actual
+            ),
+            DiagnosticKind::MisalignedMotionRow { frame_index } => write!(
+                f,
+                "frame {} starts in the middle of a line, so an earlier row had the wrong number of values",
+                frame_index
+            ),
+            DiagnosticKind::FrameCountMismatch { expected, actual } => write!(
+                f,
+                "expected {} frames, but found {} frames",
+                expected, actual
+            ),
+            DiagnosticKind::NonFiniteOffset {
+                joint_index,
+                end_site,
+            } => write!(
+                f,
+                "the {} of joint {} is not finite",
+                offset_name(end_site),
+                joint_index
+            ),
+            DiagnosticKind::LargeOffset {
+                joint_index,
+                end_site,
+                length,
+            } => write!(
+                f,
+                "the {} of joint {} has an implausible length of {}",
+                offset_name(end_site),
+                joint_index,
+                length
+            ),
+            DiagnosticKind::ZeroLengthEndSite { joint_index } => write!(
+                f,
+                "the end site of joint {} has a zero offset",
+                joint_index
+            ),
+            DiagnosticKind::MissingHierarchy => {
+                f.write_str("the \"HIERARCHY\" keyword is missing before \"ROOT\"")
+            }
+            DiagnosticKind::MissingFrameTime { frame_time } => write!(
+                f,
+                "the \"Frame Time\" line is missing, so a frame time of {} seconds was used",
+                frame_time.as_secs_f64()
+            ),
+        }
+    }
+}
+
+/// Returns how to describe the offset of a joint or of its end site.
+#[inline]
+fn offset_name(end_site: bool) -> &'static str {
+    if end_site {
+        "end site offset"
+    } else {
+        "offset"
+    }
+}
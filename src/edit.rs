@@ -0,0 +1,2186 @@
+use crate::{
+    errors::{AddChannelError, ClipMismatchError, JointIndexError, RetainJointsError},
+    joint::{JointData, JointPrivateData, Offset},
+    math::{self, Mat, Quat},
+    Axis, Bvh, Channel, ChannelType,
+};
+use smallvec::SmallVec;
+use std::{collections::HashSet, mem, time::Duration};
+
+/// How frames are added by [`Bvh::resize_frames`][`Bvh::resize_frames`].
+///
+/// [`Bvh::resize_frames`]: struct.Bvh.html#method.resize_frames
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ResizeMode {
+    /// Repeat the last frame.
+    ClampLast,
+    /// Repeat the animation from the first frame.
+    Loop,
+    /// Resample the whole animation to the new number of frames.
+    Interpolate,
+}
+
+impl Bvh {
+    /// The largest difference between the frame times of two clips for which they
+    /// are still considered equal by [`Bvh::append_motion`] and
+    /// [`Bvh::append_blended`].
+    ///
+    /// [`Bvh::append_motion`]: struct.Bvh.html#method.append_motion
+    /// [`Bvh::append_blended`]: struct.Bvh.html#method.append_blended
+    pub const DEFAULT_FRAME_TIME_TOLERANCE: Duration = Duration::from_micros(1);
+
+    /// Apply the affine transform `matrix` to the whole animation, so that the
+    /// clip is rigidly repositioned, rotated and scaled.
+    ///
+    /// `matrix` is a 4x4 column-major matrix, as described in the [`math`] module.
+    ///
+    /// Only the root joint's offset and channels are changed, as every other
+    /// joint is positioned relative to the root:
+    ///
+    /// * The root's offset is transformed as a point.
+    /// * The root's position channels are transformed as a vector. Missing position
+    ///   channels are treated as `0.0`, and are not added to the root.
+    /// * The rotation part of `matrix` is composed into the root's rotation
+    ///   channels. This is only possible if the root has a rotation channel for
+    ///   each of the three axes; otherwise the root's rotation channels are left
+    ///   unchanged.
+    ///
+    /// Since joint offsets cannot be individually scaled, any scale in `matrix` is
+    /// applied to the root's offset and position channels, and the average scale
+    /// along each axis is applied to the offsets and position channels of all other
+    /// joints.
+    ///
+    /// This assumes that the root's position channels are listed before its
+    /// rotation channels, which is the case in almost all `Bvh` files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 1.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 2.0 0.0
+    /// };
+    ///
+    /// bvh.transform([
+    ///     [1.0, 0.0, 0.0, 0.0],
+    ///     [0.0, 1.0, 0.0, 0.0],
+    ///     [0.0, 0.0, 1.0, 0.0],
+    ///     [10.0, 0.0, 0.0, 1.0],
+    /// ]);
+    ///
+    /// assert_eq!(*bvh.root_joint().unwrap().offset(), [11.0, 0.0, 0.0]);
+    /// assert_eq!(bvh.frames().next().unwrap().as_slice(), &[0.0, 2.0, 0.0]);
+    /// ```
+    ///
+    /// [`math`]: math/index.html
+    pub fn transform(&mut self, matrix: Mat) {
+        let mut rotation = matrix;
+        rotation[3] = [0.0, 0.0, 0.0, 1.0];
+        let mut col_lengths = [0.0; 3];
+        for (col, length) in rotation.iter_mut().take(3).zip(col_lengths.iter_mut()) {
+            *length = col.iter().take(3).map(|v| v * v).sum::<f32>().sqrt();
+            if *length > 0.0 {
+                for v in col.iter_mut().take(3) {
+                    *v /= *length;
+                }
+            }
+        }
+        let scale = col_lengths.iter().sum::<f32>() / 3.0;
+
+        let num_channels = self.num_channels;
+        let mut position_indices = [None; 3];
+        let mut rotation_indices = [None; 3];
+        let mut rotation_order = Vec::with_capacity(3);
+        let mut num_rotations = 0;
+
+        for joint in &mut self.joints {
+            if joint.is_root() {
+                let offset = math::transform_point(&matrix, *joint.offset());
+                joint.set_offset(offset, false);
+
+                for channel in joint.channels() {
+                    let ty = channel.channel_type();
+                    let axis = ty.axis() as usize;
+                    if ty.is_position() {
+                        position_indices[axis] = Some(channel.motion_index());
+                    } else if ty.is_rotation() {
+                        num_rotations += 1;
+                        if rotation_indices[axis].is_none() {
+                            rotation_indices[axis] = Some(channel.motion_index());
+                            rotation_order.push(ty.axis());
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let offset = scaled(joint.offset(), scale);
+            joint.set_offset(offset, false);
+            if let Some(end_site) = joint.end_site().map(|e| scaled(e, scale)) {
+                joint.set_offset(end_site, true);
+            }
+
+            for channel in joint.channels() {
+                if channel.channel_type().is_position() {
+                    let index = channel.motion_index();
+                    for frame in self.motion_values.chunks_exact_mut(num_channels) {
+                        frame[index] *= scale;
+                    }
+                }
+            }
+        }
+
+        let rotation_order = match rotation_order[..] {
+            [a, b, c] if num_rotations == 3 => Some([a, b, c]),
+            _ => None,
+        };
+
+        if num_channels == 0 {
+            return;
+        }
+        for frame in self.motion_values.chunks_exact_mut(num_channels) {
+            if position_indices.iter().any(Option::is_some) {
+                let mut position = [0.0; 3];
+                for (p, index) in position.iter_mut().zip(position_indices.iter()) {
+                    *p = index.map(|i| frame[i]).unwrap_or(0.0);
+                }
+                let mut linear = matrix;
+                linear[3] = [0.0, 0.0, 0.0, 1.0];
+                let position = math::transform_point(&linear, position);
+                for (p, index) in position.iter().zip(position_indices.iter()) {
+                    if let Some(i) = *index {
+                        frame[i] = *p;
+                    }
+                }
+            }
+
+            if let Some(order) = rotation_order {
+                let index_of = |axis: Axis| rotation_indices[axis as usize].unwrap();
+                let angles = [
+                    frame[index_of(order[0])],
+                    frame[index_of(order[1])],
+                    frame[index_of(order[2])],
+                ];
+                let rotated = math::mat_mul(&rotation, &math::euler_to_mat(order, angles));
+                let angles = math::mat_to_euler(&rotated, order);
+                for (&axis, &angle) in order.iter().zip(angles.iter()) {
+                    frame[index_of(axis)] = angle;
+                }
+            }
+        }
+    }
+
+    /// Rotate the whole animation by `degrees` about the world `y` axis, so that
+    /// the character faces a different direction.
+    ///
+    /// The root's offset and position channels are rotated about the origin, and the
+    /// rotation is composed into the root's rotation channels in its own rotation
+    /// order. This is equivalent to calling [`Bvh::transform`] with a rotation about
+    /// the `y` axis, so the same requirements on the root's channels apply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     2.0 0.0 0.0 0.0 0.0 0.0
+    /// };
+    ///
+    /// bvh.rotate_y(90.0);
+    /// let frame = bvh.frames().next().unwrap();
+    /// let [x, y, z] = bvh.world_positions(&frame)[0];
+    /// assert!(x.abs() < 1.0e-5 && y.abs() < 1.0e-5 && (z + 2.0).abs() < 1.0e-5);
+    /// ```
+    ///
+    /// [`Bvh::transform`]: struct.Bvh.html#method.transform
+    #[inline]
+    pub fn rotate_y(&mut self, degrees: f32) {
+        self.transform(math::mat_rotation(Axis::Y, degrees));
+    }
+
+    /// Convert the animation between left-handed and right-handed coordinate
+    /// systems by reversing the direction of `axis`, such as to import it into an
+    /// engine with the other handedness.
+    ///
+    /// This reflects the whole animation through the plane perpendicular to `axis`:
+    /// the `axis` component of every offset, end site and position channel is
+    /// negated, and every rotation channel about one of the other two axes is
+    /// negated, while rotation channels about `axis` and scale channels are left
+    /// unchanged. Reflecting a rotation about an axis in this plane reverses its
+    /// direction, while a rotation about `axis` itself is unchanged, so every
+    /// joint's world-space transform `W` becomes `S * W * S`, where `S` negates
+    /// `axis`. The rotation order of each joint is kept, and rotations still follow
+    /// the right-hand rule in the new coordinate system.
+    ///
+    /// Flipping the same axis twice restores the original animation exactly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, Axis};
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 1.0
+    ///         CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 2.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0 3.0 10.0 20.0 30.0
+    /// };
+    ///
+    /// bvh.flip_handedness(Axis::Z);
+    /// assert_eq!(*bvh.root_joint().unwrap().offset(), [0.0, 0.0, -1.0]);
+    /// assert_eq!(
+    ///     bvh.frames().next().unwrap().as_slice(),
+    ///     &[1.0, 2.0, -3.0, 10.0, -20.0, -30.0]
+    /// );
+    /// ```
+    pub fn flip_handedness(&mut self, axis: Axis) {
+        let flipped = |offset: &Offset| {
+            let mut offset = *offset;
+            offset[axis as usize] = negated(offset[axis as usize]);
+            offset
+        };
+
+        let num_channels = self.num_channels;
+        let mut negated_columns = Vec::new();
+        for joint in &mut self.joints {
+            let offset = flipped(joint.offset());
+            joint.set_offset(offset, false);
+            if let Some(end_site) = joint.end_site().map(flipped) {
+                joint.set_offset(end_site, true);
+            }
+
+            for channel in joint.channels() {
+                let ty = channel.channel_type();
+                if (ty.is_position() && ty.axis() == axis)
+                    || (ty.is_rotation() && ty.axis() != axis)
+                {
+                    negated_columns.push(channel.motion_index());
+                }
+            }
+        }
+
+        if num_channels == 0 {
+            return;
+        }
+        for frame in self.motion_values.chunks_exact_mut(num_channels) {
+            for &index in &negated_columns {
+                frame[index] = negated(frame[index]);
+            }
+        }
+    }
+
+    /// Keep only the joints whose indices are in `keep`, and remove all other
+    /// joints from the skeleton.
+    ///
+    /// Each kept joint is reparented to its nearest kept ancestor, and the offsets
+    /// of any removed joints in between are added to its offset. The channels of
+    /// the removed joints are removed from every frame. Since only offsets are
+    /// composed, any motion of the removed joints is lost.
+    ///
+    /// If a kept joint loses all of its children and has no end site, then it is
+    /// given an end site at the position of the first removed end site beneath it.
+    ///
+    /// Indices in `keep` which are out of range are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the root joint is not in `keep`. The `Bvh` is not
+    /// modified in this case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// # use std::collections::HashSet;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Middle
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 1 Zrotation
+    ///             JOINT Tip
+    ///             {
+    ///                 OFFSET 0.0 2.0 0.0
+    ///                 CHANNELS 1 Zrotation
+    ///                 End Site
+    ///                 {
+    ///                     OFFSET 0.0 3.0 0.0
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0 3.0 4.0 5.0
+    /// };
+    ///
+    /// let keep = [0, 2].iter().copied().collect::<HashSet<_>>();
+    /// bvh.retain_joints(&keep).unwrap();
+    ///
+    /// let tip = bvh.joints().find_by_name("Tip").unwrap();
+    /// assert_eq!(tip.parent_index(), Some(0));
+    /// assert_eq!(*tip.offset(), [0.0, 3.0, 0.0]);
+    /// assert_eq!(bvh.frames().next().unwrap().as_slice(), &[1.0, 2.0, 3.0, 5.0]);
+    /// ```
+    pub fn retain_joints(&mut self, keep: &HashSet<usize>) -> Result<(), RetainJointsError> {
+        if let Some(root) =
+            (0..self.joints.len()).find(|&i| self.joints[i].is_root() && !keep.contains(&i))
+        {
+            return Err(RetainJointsError::root_not_kept(root));
+        }
+
+        let kept = (0..self.joints.len())
+            .map(|i| keep.contains(&i))
+            .collect::<Vec<_>>();
+        self.retain_joints_by_mask(&kept);
+        Ok(())
+    }
+
+    /// Keep only the joints for which `kept[index]` is `true`. Every root joint
+    /// must be kept.
+    pub(crate) fn retain_joints_by_mask(&mut self, kept: &[bool]) {
+        let num_joints = self.joints.len();
+        // The offset of each joint relative to its nearest kept ancestor.
+        let mut carried_offsets: Vec<Offset> = Vec::with_capacity(num_joints);
+        // The nearest kept ancestor of each joint, as an index into the old joints.
+        let mut kept_ancestors: Vec<Option<usize>> = Vec::with_capacity(num_joints);
+        let mut end_sites: Vec<Option<Offset>> = vec![None; num_joints];
+
+        for (i, joint) in self.joints.iter().enumerate() {
+            let offset = *joint.offset();
+            let (carried, ancestor) = match joint.parent_index() {
+                Some(parent) if kept[parent] => (offset, Some(parent)),
+                Some(parent) => (add(carried_offsets[parent], offset), kept_ancestors[parent]),
+                None => (offset, None),
+            };
+            carried_offsets.push(carried);
+            kept_ancestors.push(ancestor);
+
+            if !kept[i] {
+                if let (Some(ancestor), Some(end_site)) = (ancestor, joint.end_site()) {
+                    end_sites[ancestor].get_or_insert(add(carried, *end_site));
+                }
+            }
+        }
+
+        let mut has_children = vec![false; num_joints];
+        for (i, ancestor) in kept_ancestors.iter().enumerate() {
+            if let (true, Some(ancestor)) = (kept[i], *ancestor) {
+                has_children[ancestor] = true;
+            }
+        }
+
+        let mut new_indices: Vec<Option<usize>> = vec![None; num_joints];
+        let mut new_depths: Vec<usize> = Vec::with_capacity(num_joints);
+        let mut columns = Vec::with_capacity(self.num_channels);
+        let mut new_joints = Vec::with_capacity(num_joints);
+
+        for (i, joint) in self.joints.iter().enumerate() {
+            if !kept[i] {
+                continue;
+            }
+
+            let new_index = new_joints.len();
+            new_indices[i] = Some(new_index);
+
+            let channels = joint
+                .channels()
+                .iter()
+                .map(|channel| {
+                    columns.push(channel.motion_index());
+                    Channel::new(channel.channel_type(), columns.len() - 1)
+                })
+                .collect::<SmallVec<[Channel; 6]>>();
+
+            let mut new_joint = joint.clone();
+            new_joint.set_offset(carried_offsets[i], false);
+            new_joint.set_channels(channels);
+
+            let depth = match kept_ancestors[i].and_then(|a| new_indices[a]) {
+                Some(new_parent) => {
+                    let depth = new_depths[new_parent] + 1;
+                    if let Some(private) = new_joint.private_data_mut() {
+                        *private = JointPrivateData::new(new_index, new_parent, depth);
+                    }
+                    depth
+                }
+                None => 0,
+            };
+            new_depths.push(depth);
+
+            if new_joint.is_child() && !has_children[i] && joint.end_site().is_none() {
+                if let Some(end_site) = end_sites[i] {
+                    new_joint.set_offset(end_site, true);
+                }
+            }
+
+            new_joints.push(new_joint);
+        }
+
+        let mut motion_values = Vec::with_capacity(self.frames().len() * columns.len());
+        if self.num_channels > 0 {
+            for frame in self.motion_values.chunks_exact(self.num_channels) {
+                motion_values.extend(columns.iter().map(|&column| frame[column]));
+            }
+        }
+
+        self.joints = new_joints;
+        self.num_channels = columns.len();
+        self.motion_values = motion_values;
+    }
+
+    /// Reorder the joints of the `Bvh` into depth-first order, and renumber their
+    /// channels to match, such as after merging or editing skeletons.
+    ///
+    /// Afterwards, every joint is stored directly after its parent or an earlier
+    /// sibling's descendants, children keep their relative order, and the motion
+    /// columns are in the same order as the joints' channels, which is the order in
+    /// which the writer emits them. Parent indices and the motion values are
+    /// remapped so that every frame has the same pose. A `Bvh` which is already in
+    /// canonical order, such as one which was parsed, is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 1 Zrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0
+    /// };
+    ///
+    /// let original = bvh.clone();
+    /// bvh.canonicalize_order();
+    /// assert_eq!(bvh, original);
+    /// ```
+    pub fn canonicalize_order(&mut self) {
+        let num_joints = self.joints.len();
+        let mut children = vec![Vec::new(); num_joints];
+        let mut roots = Vec::new();
+        for (i, joint) in self.joints.iter().enumerate() {
+            match joint.parent_index() {
+                Some(parent) if parent < num_joints && parent != i => children[parent].push(i),
+                _ => roots.push(i),
+            }
+        }
+
+        let mut order = Vec::with_capacity(num_joints);
+        let mut visited = vec![false; num_joints];
+        let mut stack = Vec::new();
+        // Joints which are not reachable from a root, because their parents form a
+        // cycle, are kept after the reachable joints.
+        for start in roots.into_iter().chain(0..num_joints) {
+            stack.push(start);
+            while let Some(i) = stack.pop() {
+                if !mem::replace(&mut visited[i], true) {
+                    order.push(i);
+                    stack.extend(children[i].iter().rev());
+                }
+            }
+        }
+
+        let mut new_indices = vec![0; num_joints];
+        for (new_index, &i) in order.iter().enumerate() {
+            new_indices[i] = new_index;
+        }
+
+        let mut new_depths: Vec<usize> = Vec::with_capacity(num_joints);
+        let mut columns = Vec::with_capacity(self.num_channels);
+        let mut new_joints = Vec::with_capacity(num_joints);
+        for (new_index, &i) in order.iter().enumerate() {
+            let joint = &self.joints[i];
+            let channels = joint
+                .channels()
+                .iter()
+                .map(|channel| {
+                    columns.push(channel.motion_index());
+                    Channel::new(channel.channel_type(), columns.len() - 1)
+                })
+                .collect::<SmallVec<[Channel; 6]>>();
+
+            let mut new_joint = joint.clone();
+            new_joint.set_channels(channels);
+
+            let new_parent = joint
+                .parent_index()
+                .map(|parent| new_indices[parent])
+                .filter(|&parent| parent < new_index);
+            let depth = match new_parent {
+                Some(new_parent) => {
+                    let depth = new_depths[new_parent] + 1;
+                    if let Some(private) = new_joint.private_data_mut() {
+                        *private = JointPrivateData::new(new_index, new_parent, depth);
+                    }
+                    depth
+                }
+                None => 0,
+            };
+            new_depths.push(depth);
+            new_joints.push(new_joint);
+        }
+
+        if self.num_channels > 0 && columns.len() == self.num_channels {
+            let mut motion_values = Vec::with_capacity(self.motion_values.len());
+            for frame in self.motion_values.chunks_exact(self.num_channels) {
+                motion_values.extend(columns.iter().map(|&column| frame[column]));
+            }
+            self.motion_values = motion_values;
+        }
+
+        self.joints = new_joints;
+    }
+
+    /// Returns `true` if two or more joints in the `Bvh` have the same name.
+    ///
+    /// See [`Bvh::deduplicate_names`].
+    ///
+    /// [`Bvh::deduplicate_names`]: struct.Bvh.html#method.deduplicate_names
+    pub fn has_duplicate_names(&self) -> bool {
+        let mut seen = HashSet::with_capacity(self.joints.len());
+        !self.joints.iter().all(|joint| seen.insert(joint.name()))
+    }
+
+    /// Rename joints so that every joint in the `Bvh` has a unique name.
+    ///
+    /// The first joint with a given name keeps it, and each later joint with the
+    /// same name has a numeric suffix appended, such as `LeftHand.001`. Suffixes
+    /// which would clash with the name of another joint are skipped. The skeleton
+    /// and the motion are unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         JOINT Hand
+    ///         {
+    ///             OFFSET 1.0 0.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 1.0 0.0 0.0
+    ///             }
+    ///         }
+    ///         JOINT Hand
+    ///         {
+    ///             OFFSET -1.0 0.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET -1.0 0.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert!(bvh.has_duplicate_names());
+    /// bvh.deduplicate_names();
+    /// assert!(!bvh.has_duplicate_names());
+    ///
+    /// let names = bvh.joints().map(|joint| joint.name().to_vec()).collect::<Vec<_>>();
+    /// assert_eq!(names, [&b"Hips"[..], b"Hand", b"Hand.001"]);
+    /// ```
+    pub fn deduplicate_names(&mut self) {
+        let original_names = self
+            .joints
+            .iter()
+            .map(|joint| joint.name().to_vec())
+            .collect::<HashSet<_>>();
+        let mut used = HashSet::with_capacity(self.joints.len());
+
+        for joint in &mut self.joints {
+            let name = joint.name().to_vec();
+            if used.insert(name.clone()) {
+                continue;
+            }
+
+            let new_name = (1..)
+                .map(|suffix| {
+                    let mut candidate = name.clone();
+                    candidate.extend_from_slice(format!(".{:03}", suffix).as_bytes());
+                    candidate
+                })
+                .find(|candidate| !original_names.contains(candidate) && !used.contains(candidate))
+                .unwrap_or_default();
+
+            used.insert(new_name.clone());
+            joint.set_name(new_name);
+        }
+    }
+
+    /// Rename every joint whose name is the first element of a pair in `map` to
+    /// the second element of that pair, such as to set up a retargeting between
+    /// two naming conventions. Returns the number of joints which were renamed.
+    ///
+    /// All of the renames are applied at once, so that the names of two joints can
+    /// be swapped. Joints whose names are not in `map` are not changed, and pairs
+    /// which do not match any joint are ignored. If a name appears in `map` more
+    /// than once, the first pair is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let renamed = bvh.rename_joints(&[(b"Hips", b"pelvis"), (b"Neck", b"neck")]);
+    /// assert_eq!(renamed, 1);
+    ///
+    /// let names = bvh.joints().map(|joint| joint.name().to_vec()).collect::<Vec<_>>();
+    /// assert_eq!(names, [&b"pelvis"[..], b"Chest"]);
+    /// ```
+    pub fn rename_joints(&mut self, map: &[(&[u8], &[u8])]) -> usize {
+        let mut renamed = 0;
+        for joint in &mut self.joints {
+            let new_name = map
+                .iter()
+                .find(|&&(old_name, _)| joint.name() == old_name)
+                .map(|&(_, new_name)| new_name);
+            if let Some(new_name) = new_name {
+                joint.set_name(new_name);
+                renamed += 1;
+            }
+        }
+        renamed
+    }
+
+    /// Reduce discontinuities in the rotation channels of every joint, such as
+    /// the jumps which occur when an angle wraps around from `180` to `-180`, or
+    /// near gimbal lock.
+    ///
+    /// For joints with a rotation channel for each of the three axes, every frame's
+    /// rotation is converted to a quaternion, which is kept in the same hemisphere
+    /// as the previous frame's quaternion, and then converted back to Euler angles
+    /// in the joint's rotation order. Of the equivalent sets of Euler angles, the
+    /// one closest to the previous frame is chosen. For other joints, each rotation
+    /// channel is unwrapped by multiples of `360` degrees to be closest to the
+    /// previous frame.
+    ///
+    /// The first frame is left unchanged, and the resulting rotation of every
+    /// joint in every frame is the same as before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Zrotation
+    ///     }
+    ///     MOTION
+    ///     Frames: 4
+    ///     Frame Time: 0.033333333
+    ///     170.0
+    ///     179.0
+    ///     -179.0
+    ///     -170.0
+    /// };
+    ///
+    /// bvh.regularize_euler();
+    /// let angles = bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>();
+    /// assert_eq!(angles, [170.0, 179.0, 181.0, 190.0]);
+    /// ```
+    pub fn regularize_euler(&mut self) {
+        let num_channels = self.num_channels;
+        if num_channels == 0 {
+            return;
+        }
+
+        for joint in &self.joints {
+            let (axes, indices): (Vec<Axis>, Vec<usize>) = joint
+                .channels()
+                .iter()
+                .filter(|channel| channel.channel_type().is_rotation())
+                .map(|channel| (channel.channel_type().axis(), channel.motion_index()))
+                .unzip();
+
+            let order = match axes[..] {
+                [a, b, c] if a != b && b != c && a != c => Some([a, b, c]),
+                _ => None,
+            };
+
+            let mut previous: Option<(Quat, [f32; 3])> = None;
+            let mut frames = self.motion_values.chunks_exact_mut(num_channels);
+            let mut previous_values = match frames.next() {
+                Some(frame) => indices.iter().map(|&i| frame[i]).collect::<Vec<_>>(),
+                None => continue,
+            };
+
+            for frame in frames {
+                match order {
+                    Some(order) => {
+                        let (prev_quat, prev_angles) = *previous.get_or_insert_with(|| {
+                            let angles =
+                                [previous_values[0], previous_values[1], previous_values[2]];
+                            (
+                                math::mat_to_quat(&math::euler_to_mat(order, angles)),
+                                angles,
+                            )
+                        });
+
+                        let angles = [frame[indices[0]], frame[indices[1]], frame[indices[2]]];
+                        let mut quat = math::mat_to_quat(&math::euler_to_mat(order, angles));
+                        if math::quat_dot(prev_quat, quat) < 0.0 {
+                            quat = [-quat[0], -quat[1], -quat[2], -quat[3]];
+                        }
+
+                        let [a, b, c] = math::mat_to_euler(&math::quat_to_mat(quat), order);
+                        let angles = [[a, b, c], [a + 180.0, 180.0 - b, c + 180.0]]
+                            .iter()
+                            .map(|candidate| {
+                                let mut unwrapped = [0.0; 3];
+                                for i in 0..3 {
+                                    unwrapped[i] = closest_angle(candidate[i], prev_angles[i]);
+                                }
+                                unwrapped
+                            })
+                            .min_by(|x, y| {
+                                angle_distance(x, &prev_angles)
+                                    .partial_cmp(&angle_distance(y, &prev_angles))
+                                    .unwrap_or(std::cmp::Ordering::Equal)
+                            })
+                            .unwrap_or(angles);
+
+                        for (&index, &angle) in indices.iter().zip(angles.iter()) {
+                            frame[index] = angle;
+                        }
+                        previous = Some((quat, angles));
+                    }
+                    None => {
+                        for (&index, previous_value) in
+                            indices.iter().zip(previous_values.iter_mut())
+                        {
+                            frame[index] = closest_angle(frame[index], *previous_value);
+                            *previous_value = frame[index];
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Snap the values of the rotation channels of every joint back into a canonical
+    /// range in every frame, without changing the orientation that they represent,
+    /// such as after additive edits have pushed them past `360` degrees.
+    ///
+    /// For joints with a rotation channel for each of the three axes, the rotation is
+    /// converted to a quaternion and back, so that the first and last angles are in
+    /// the range `[-180, 180]` and the middle angle is in the range `[-90, 90]`.
+    /// Other rotation channels are wrapped into the range `[-180, 180]`.
+    ///
+    /// This is the opposite of [`Bvh::regularize_euler`], which removes the jumps
+    /// between frames that wrapping the angles creates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Zrotation
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.033333333
+    ///     370.0
+    ///     -190.0
+    ///     90.0
+    /// };
+    ///
+    /// bvh.renormalize_rotations();
+    /// let angles = bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>();
+    /// assert_eq!(angles, [10.0, 170.0, 90.0]);
+    /// ```
+    ///
+    /// [`Bvh::regularize_euler`]: struct.Bvh.html#method.regularize_euler
+    pub fn renormalize_rotations(&mut self) {
+        let num_channels = self.num_channels;
+        if num_channels == 0 {
+            return;
+        }
+
+        for joint in &self.joints {
+            let (axes, indices): (Vec<Axis>, Vec<usize>) = joint
+                .channels()
+                .iter()
+                .filter(|channel| channel.channel_type().is_rotation())
+                .map(|channel| (channel.channel_type().axis(), channel.motion_index()))
+                .unzip();
+
+            let order = match axes[..] {
+                [a, b, c] if a != b && b != c && a != c => Some([a, b, c]),
+                _ => None,
+            };
+
+            for frame in self.motion_values.chunks_exact_mut(num_channels) {
+                match order {
+                    Some(order) => {
+                        let angles = [frame[indices[0]], frame[indices[1]], frame[indices[2]]];
+                        let quat = math::mat_to_quat(&math::euler_to_mat(order, angles));
+                        let angles = math::mat_to_euler(&math::quat_to_mat(quat), order);
+                        for (&index, &angle) in indices.iter().zip(angles.iter()) {
+                            frame[index] = angle;
+                        }
+                    }
+                    None => {
+                        for &index in &indices {
+                            frame[index] = closest_angle(frame[index], 0.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Simplify the motion of every channel, by replacing the frames which can be
+    /// reconstructed from their neighbouring keyframes to within `tolerance` with
+    /// the interpolated values.
+    ///
+    /// Since a `Bvh` always stores a value for every channel in every frame, no
+    /// frames are removed. Instead, each channel is reduced to a set of keyframes,
+    /// and the frames in between two keyframes are snapped to the values
+    /// interpolated between them. This removes small amounts of noise from the
+    /// animation, and makes it easier to compress.
+    ///
+    /// For joints with a rotation channel for each of the three axes, the
+    /// rotation is interpolated in quaternion space, and `tolerance` is the maximum
+    /// angle in degrees between the original and the interpolated rotation. Other
+    /// channels are interpolated linearly, and `tolerance` is the maximum
+    /// difference between the original and the interpolated value. Keyframes are
+    /// chosen greedily, so the first and last frames are always keyframes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 5
+    ///     Frame Time: 0.033333333
+    ///     0.0
+    ///     1.01
+    ///     1.99
+    ///     3.0
+    ///     0.0
+    /// };
+    ///
+    /// bvh.reduce_keyframes(0.05);
+    /// let values = bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>();
+    /// assert_eq!(values, [0.0, 1.0, 2.0, 3.0, 0.0]);
+    /// ```
+    pub fn reduce_keyframes(&mut self, tolerance: f32) {
+        let num_channels = self.num_channels;
+        let num_frames = self.frames().len();
+        if num_channels == 0 || num_frames < 3 {
+            return;
+        }
+
+        let motion = &mut self.motion_values;
+        for joint in &self.joints {
+            let (rotation_axes, rotation_indices): (Vec<Axis>, Vec<usize>) = joint
+                .channels()
+                .iter()
+                .filter(|channel| channel.channel_type().is_rotation())
+                .map(|channel| (channel.channel_type().axis(), channel.motion_index()))
+                .unzip();
+
+            let order = match rotation_axes[..] {
+                [a, b, c] if a != b && b != c && a != c => Some([a, b, c]),
+                _ => None,
+            };
+
+            let scalar_indices = joint
+                .channels()
+                .iter()
+                .filter(|channel| order.is_none() || !channel.channel_type().is_rotation())
+                .map(|channel| channel.motion_index());
+
+            for index in scalar_indices {
+                let value = |frame: usize| motion[frame * num_channels + index];
+                let lerp = |start: usize, end: usize, frame: usize| {
+                    let t = (frame - start) as f32 / (end - start) as f32;
+                    value(start) + (value(end) - value(start)) * t
+                };
+                let keys = reduce_track(num_frames, |start, end, frame| {
+                    (lerp(start, end, frame) - value(frame)).abs() <= tolerance
+                });
+
+                let snapped = snap_between_keys(&keys, lerp);
+                for (frame, new_value) in snapped {
+                    motion[frame * num_channels + index] = new_value;
+                }
+            }
+
+            if let Some(order) = order {
+                let angles_at = |frame: usize| {
+                    let mut angles = [0.0; 3];
+                    for (angle, &index) in angles.iter_mut().zip(rotation_indices.iter()) {
+                        *angle = motion[frame * num_channels + index];
+                    }
+                    angles
+                };
+                let quats = (0..num_frames)
+                    .map(|frame| math::mat_to_quat(&math::euler_to_mat(order, angles_at(frame))))
+                    .collect::<Vec<_>>();
+                let slerp = |start: usize, end: usize, frame: usize| {
+                    let t = (frame - start) as f32 / (end - start) as f32;
+                    math::quat_slerp(quats[start], quats[end], t)
+                };
+                let keys = reduce_track(num_frames, |start, end, frame| {
+                    math::quat_angle_between(slerp(start, end, frame), quats[frame]) <= tolerance
+                });
+
+                let snapped = snap_between_keys(&keys, |start, end, frame| {
+                    let original = angles_at(frame);
+                    let mut angles =
+                        math::mat_to_euler(&math::quat_to_mat(slerp(start, end, frame)), order);
+                    for (angle, &target) in angles.iter_mut().zip(original.iter()) {
+                        *angle = closest_angle(*angle, target);
+                    }
+                    angles
+                });
+                for (frame, angles) in snapped {
+                    for (&index, &angle) in rotation_indices.iter().zip(angles.iter()) {
+                        motion[frame * num_channels + index] = angle;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Append the frames of `other` to the end of the `Bvh`, cross-fading between the
+    /// two clips over `blend_frames` frames so that the seam does not pop.
+    ///
+    /// The last `blend_frames` frames of the `Bvh` are blended with the first
+    /// `blend_frames` frames of `other`, moving gradually from the `Bvh` towards
+    /// `other`, and the remaining frames of `other` are appended after them. The
+    /// result has `blend_frames` fewer frames than the two clips together. For joints
+    /// with a rotation channel for each of the three axes, the rotation is
+    /// interpolated in quaternion space; all other channels are interpolated
+    /// linearly. `blend_frames` is limited to the number of frames in the shorter
+    /// clip, and if it is `0`, then the frames of `other` are appended unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `other` does not have the same joint hierarchy, the same
+    /// channels, and the same frame time as the `Bvh`, within
+    /// [`Bvh::DEFAULT_FRAME_TIME_TOLERANCE`]. In that case, the `Bvh` is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.033333333
+    ///     0.0
+    ///     0.0
+    ///     0.0
+    /// };
+    ///
+    /// let mut other = bvh.clone();
+    /// other.map_frames(|_, frame| frame[0] = 3.0);
+    ///
+    /// bvh.append_blended(&other, 2)?;
+    /// let values = bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>();
+    /// assert_eq!(values, [0.0, 1.0, 2.0, 3.0]);
+    /// # Result::<(), bvh_anim::errors::ClipMismatchError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::DEFAULT_FRAME_TIME_TOLERANCE`]: struct.Bvh.html#associatedconstant.DEFAULT_FRAME_TIME_TOLERANCE
+    pub fn append_blended(
+        &mut self,
+        other: &Bvh,
+        blend_frames: usize,
+    ) -> Result<(), ClipMismatchError> {
+        self.check_appendable(other, Self::DEFAULT_FRAME_TIME_TOLERANCE)?;
+
+        let num_channels = self.num_channels;
+        if num_channels == 0 {
+            return Ok(());
+        }
+
+        let blend_frames = blend_frames
+            .min(self.frames().len())
+            .min(other.frames().len());
+        let seam = self.motion_values.len() - blend_frames * num_channels;
+
+        for blend_index in 0..blend_frames {
+            let t = (blend_index + 1) as f32 / (blend_frames + 1) as f32;
+            let start = seam + blend_index * num_channels;
+            let from = &mut self.motion_values[start..start + num_channels];
+            let to = &other.motion_values[blend_index * num_channels..][..num_channels];
+
+            blend_pose(&self.joints, from, to, t);
+        }
+
+        self.motion_values
+            .extend_from_slice(&other.motion_values[blend_frames * num_channels..]);
+        Ok(())
+    }
+
+    /// Append the frames of `other` to the end of the `Bvh` unchanged, such as to
+    /// stitch together clips of the same skeleton.
+    ///
+    /// The frame times of the clips are considered equal if they differ by at most
+    /// [`Bvh::DEFAULT_FRAME_TIME_TOLERANCE`], so that clips whose frame times were
+    /// rounded differently by different exporters can still be combined. Use
+    /// [`Bvh::append_motion_with_tolerance`] to choose the tolerance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `other` does not have the same joint hierarchy, the same
+    /// channels, and the same frame time as the `Bvh`. In that case, the `Bvh` is not
+    /// changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// # use std::time::Duration;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0
+    /// };
+    ///
+    /// let mut other = bvh.clone();
+    /// other.set_frame_time(*bvh.frame_time() + Duration::from_nanos(100));
+    /// other.map_frames(|_, frame| frame[0] = 1.0);
+    ///
+    /// bvh.append_motion(&other)?;
+    /// let values = bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>();
+    /// assert_eq!(values, [0.0, 1.0]);
+    /// # Result::<(), bvh_anim::errors::ClipMismatchError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::DEFAULT_FRAME_TIME_TOLERANCE`]: struct.Bvh.html#associatedconstant.DEFAULT_FRAME_TIME_TOLERANCE
+    /// [`Bvh::append_motion_with_tolerance`]: struct.Bvh.html#method.append_motion_with_tolerance
+    #[inline]
+    pub fn append_motion(&mut self, other: &Bvh) -> Result<(), ClipMismatchError> {
+        self.append_motion_with_tolerance(other, Self::DEFAULT_FRAME_TIME_TOLERANCE)
+    }
+
+    /// Append the frames of `other` to the end of the `Bvh` unchanged, accepting
+    /// frame times which differ by at most `tolerance`.
+    ///
+    /// The frame time of the `Bvh` is kept. See [`Bvh::append_motion`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `other` does not have the same joint hierarchy and the
+    /// same channels as the `Bvh`, or if the frame times differ by more than
+    /// `tolerance`. In that case, the `Bvh` is not changed.
+    ///
+    /// [`Bvh::append_motion`]: struct.Bvh.html#method.append_motion
+    pub fn append_motion_with_tolerance(
+        &mut self,
+        other: &Bvh,
+        tolerance: Duration,
+    ) -> Result<(), ClipMismatchError> {
+        self.check_appendable(other, tolerance)?;
+        self.motion_values.extend_from_slice(&other.motion_values);
+        Ok(())
+    }
+
+    /// Check that the frames of `other` can be appended to the `Bvh`.
+    fn check_appendable(&self, other: &Bvh, tolerance: Duration) -> Result<(), ClipMismatchError> {
+        if self.joints.len() != other.joints.len() {
+            return Err(ClipMismatchError::JointCount {
+                expected: self.joints.len(),
+                actual: other.joints.len(),
+            });
+        }
+        for (joint_index, (a, b)) in self.joints.iter().zip(other.joints.iter()).enumerate() {
+            if a.parent_index() != b.parent_index() {
+                return Err(ClipMismatchError::Hierarchy { joint_index });
+            }
+            if a.channels() != b.channels() {
+                return Err(ClipMismatchError::Channels { joint_index });
+            }
+        }
+        if self.frame_time.abs_diff(other.frame_time) > tolerance {
+            return Err(ClipMismatchError::FrameTime {
+                expected: self.frame_time,
+                actual: other.frame_time,
+            });
+        }
+        Ok(())
+    }
+
+    /// Change the number of frames in the `Bvh` to exactly `target`, such as to
+    /// batch clips of the same length.
+    ///
+    /// With [`ResizeMode::ClampLast`] and [`ResizeMode::Loop`], extra frames are
+    /// removed from the end, and missing frames are added by repeating the last
+    /// frame, or by repeating the animation from the start, respectively.
+    ///
+    /// With [`ResizeMode::Interpolate`], the animation is resampled to `target`
+    /// frames by linearly interpolating every channel between the nearest original
+    /// frames, and the frame time is changed so that the duration from the first to
+    /// the last frame stays the same. Rotation channels are interpolated as angles, so
+    /// [`Bvh::regularize_euler`] should be used first if they wrap around.
+    ///
+    /// If the `Bvh` has no frames, then it is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, ResizeMode};
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.033333333
+    ///     0.0
+    ///     1.0
+    ///     2.0
+    /// };
+    ///
+    /// let values = |bvh: &bvh_anim::Bvh| bvh.frames().map(|f| f[0]).collect::<Vec<_>>();
+    ///
+    /// let mut looped = bvh.clone();
+    /// looped.resize_frames(5, ResizeMode::Loop);
+    /// assert_eq!(values(&looped), [0.0, 1.0, 2.0, 0.0, 1.0]);
+    ///
+    /// let mut resampled = bvh.clone();
+    /// resampled.resize_frames(5, ResizeMode::Interpolate);
+    /// assert_eq!(values(&resampled), [0.0, 0.5, 1.0, 1.5, 2.0]);
+    ///
+    /// bvh.resize_frames(2, ResizeMode::ClampLast);
+    /// assert_eq!(values(&bvh), [0.0, 1.0]);
+    /// ```
+    ///
+    /// [`ResizeMode::ClampLast`]: enum.ResizeMode.html#variant.ClampLast
+    /// [`ResizeMode::Loop`]: enum.ResizeMode.html#variant.Loop
+    /// [`ResizeMode::Interpolate`]: enum.ResizeMode.html#variant.Interpolate
+    /// [`Bvh::regularize_euler`]: struct.Bvh.html#method.regularize_euler
+    pub fn resize_frames(&mut self, target: usize, mode: ResizeMode) {
+        let num_channels = self.num_channels;
+        let num_frames = self.frames().len();
+        if num_frames == 0 || num_frames == target {
+            return;
+        }
+
+        let frame = |index: usize| &self.motion_values[index * num_channels..][..num_channels];
+        let mut motion_values = Vec::with_capacity(target * num_channels);
+        match mode {
+            ResizeMode::ClampLast | ResizeMode::Loop => {
+                for index in 0..target {
+                    let source = if index < num_frames {
+                        index
+                    } else if mode == ResizeMode::Loop {
+                        index % num_frames
+                    } else {
+                        num_frames - 1
+                    };
+                    motion_values.extend_from_slice(frame(source));
+                }
+            }
+            ResizeMode::Interpolate => {
+                let scale = if target > 1 {
+                    (num_frames - 1) as f64 / (target - 1) as f64
+                } else {
+                    0.0
+                };
+                for index in 0..target {
+                    let position = index as f64 * scale;
+                    let before = (position.floor() as usize).min(num_frames - 1);
+                    let after = (before + 1).min(num_frames - 1);
+                    let t = (position - before as f64) as f32;
+                    motion_values.extend(
+                        frame(before)
+                            .iter()
+                            .zip(frame(after))
+                            .map(|(a, b)| a + (b - a) * t),
+                    );
+                }
+                if target > 1 {
+                    self.frame_time = self.frame_time.mul_f64(scale);
+                }
+            }
+        }
+
+        self.motion_values = motion_values;
+    }
+
+    /// Change the frame time of the `Bvh` to `new_frame_time`, resampling the frames
+    /// so that the animation plays at the same speed, such as to convert a clip to
+    /// a different frame rate.
+    ///
+    /// The duration from the first to the last frame is kept, rounded to a whole
+    /// number of frames at the new frame time, and every channel is linearly
+    /// interpolated between the nearest original frames, as with
+    /// [`ResizeMode::Interpolate`]. In contrast, [`Bvh::set_frame_time`] only
+    /// changes the frame time, so that the same frames play faster or slower.
+    ///
+    /// If the `Bvh` has fewer than two frames, or if `new_frame_time` is zero, then
+    /// only the frame time is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// # use std::time::Duration;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 1
+    ///     0.0
+    ///     1.0
+    ///     2.0
+    /// };
+    ///
+    /// bvh.set_frame_time_keep_duration(Duration::from_millis(500));
+    /// let values = bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>();
+    /// assert_eq!(values, [0.0, 0.5, 1.0, 1.5, 2.0]);
+    /// assert_eq!(*bvh.frame_time(), Duration::from_millis(500));
+    /// ```
+    ///
+    /// [`ResizeMode::Interpolate`]: enum.ResizeMode.html#variant.Interpolate
+    /// [`Bvh::set_frame_time`]: struct.Bvh.html#method.set_frame_time
+    pub fn set_frame_time_keep_duration(&mut self, new_frame_time: Duration) {
+        let num_frames = self.frames().len();
+        if num_frames >= 2 && !new_frame_time.is_zero() {
+            let duration = self.frame_time.as_secs_f64() * (num_frames - 1) as f64;
+            let intervals = (duration / new_frame_time.as_secs_f64()).round();
+            let target = if intervals.is_finite() && intervals < (usize::MAX / 2) as f64 {
+                intervals as usize + 1
+            } else {
+                num_frames
+            };
+            self.resize_frames(target, ResizeMode::Interpolate);
+        }
+        self.frame_time = new_frame_time;
+    }
+
+    /// Remove the frames at the start and the end of the `Bvh` in which nothing
+    /// moves, such as the dead frames before and after a capture.
+    ///
+    /// Leading frames are removed while no channel differs by more than `epsilon`
+    /// from the following frame, so that the first frame kept is the pose just
+    /// before the motion starts. Similarly, trailing frames are removed while no
+    /// channel differs by more than `epsilon` from the preceding frame, so that the
+    /// last frame kept is the pose where the motion ends. If nothing moves at all,
+    /// only the first frame is kept. Annotations are not moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 6
+    ///     Frame Time: 0.033333333
+    ///     0.0
+    ///     0.0
+    ///     0.0
+    ///     1.0
+    ///     2.0
+    ///     2.0
+    /// };
+    ///
+    /// bvh.trim_static(1.0e-4);
+    /// let values = bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>();
+    /// assert_eq!(values, [0.0, 1.0, 2.0]);
+    /// ```
+    pub fn trim_static(&mut self, epsilon: f32) {
+        let num_channels = self.num_channels;
+        let num_frames = self.frames().len();
+        if num_channels == 0 || num_frames < 2 {
+            return;
+        }
+
+        let frame = |index: usize| &self.motion_values[index * num_channels..][..num_channels];
+        let moves = |index: usize| {
+            frame(index)
+                .iter()
+                .zip(frame(index + 1))
+                .any(|(a, b)| (a - b).abs() > epsilon)
+        };
+
+        let (start, end) = match (0..num_frames - 1).find(|&index| moves(index)) {
+            Some(start) => {
+                let last_move = (start..num_frames - 1)
+                    .rev()
+                    .find(|&index| moves(index))
+                    .unwrap_or(start);
+                (start, last_move + 2)
+            }
+            None => (0, 1),
+        };
+
+        self.motion_values.truncate(end * num_channels);
+        self.motion_values.drain(..start * num_channels);
+    }
+
+    /// Remove the channels whose values never vary by more than `epsilon` over the
+    /// whole animation, to reduce the size of the `Bvh`.
+    ///
+    /// A constant position channel is removed by adding its mean value to the
+    /// joint's offset. This is only possible when all of the joint's channels
+    /// before it are also position channels, as a translation after a rotation
+    /// cannot be moved into the offset. A constant rotation channel is removed
+    /// only if its value is within `epsilon` of `0`, as offsets cannot contain
+    /// rotations. Similarly, a constant scale channel is removed only if its value
+    /// is within `epsilon` of `1`. Channels which cannot be removed are kept.
+    ///
+    /// The position channels of the root joint are always kept. If the `Bvh` has no
+    /// frames, then nothing is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 3 Yposition Zrotation Xrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0 2.0 0.0 5.0
+    ///     0.0 0.0 0.0 2.0 0.0 10.0
+    /// };
+    ///
+    /// bvh.prune_constant_channels(1.0e-4);
+    ///
+    /// let tip = bvh.joints().find_by_name("Tip").unwrap();
+    /// assert_eq!(*tip.offset(), [0.0, 3.0, 0.0]);
+    /// assert_eq!(tip.channels().len(), 1);
+    /// assert_eq!(bvh.frames().nth(1).unwrap().as_slice(), &[0.0, 0.0, 0.0, 10.0]);
+    /// ```
+    pub fn prune_constant_channels(&mut self, epsilon: f32) {
+        if self.frames().len() == 0 {
+            return;
+        }
+
+        let mut removed = vec![false; self.num_channels];
+        let stats = self.channel_stats();
+
+        for (index, joint) in self.joints.iter_mut().enumerate() {
+            let mut offset = *joint.offset();
+            let mut only_positions_before = true;
+
+            for channel in joint.channels() {
+                let ty = channel.channel_type();
+                let channel_stats = &stats[&(index, ty)];
+                let is_constant = channel_stats.range() <= epsilon;
+                let is_zero =
+                    channel_stats.min.abs() <= epsilon && channel_stats.max.abs() <= epsilon;
+
+                if ty.is_position() {
+                    if !joint.is_root() && is_constant && (only_positions_before || is_zero) {
+                        if only_positions_before {
+                            offset[ty.axis() as usize] += channel_stats.mean;
+                        }
+                        removed[channel.motion_index()] = true;
+                    }
+                } else if ty.is_scale() {
+                    only_positions_before = false;
+                    if (channel_stats.min - 1.0).abs() <= epsilon
+                        && (channel_stats.max - 1.0).abs() <= epsilon
+                    {
+                        removed[channel.motion_index()] = true;
+                    }
+                } else {
+                    only_positions_before = false;
+                    if is_zero {
+                        removed[channel.motion_index()] = true;
+                    }
+                }
+            }
+
+            joint.set_offset(offset, false);
+        }
+
+        self.remove_channel_columns(&removed);
+    }
+
+    /// Add an end site to every leaf joint which does not have one, so that the last
+    /// bone of every chain can be drawn.
+    ///
+    /// The end site continues in the direction of the joint's own offset from its
+    /// parent, with a length of `default_length`. If the joint's offset is zero, then
+    /// the end site points along the `Y` axis. A root joint cannot have an end site,
+    /// so a root joint without children is left as it is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 0.0 10.0
+    ///             CHANNELS 0
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// bvh.ensure_end_sites(2.0);
+    ///
+    /// let chest = bvh.joints().find_by_name("Chest").unwrap();
+    /// assert_eq!(chest.end_site(), Some(&[0.0, 0.0, 2.0]));
+    /// ```
+    pub fn ensure_end_sites(&mut self, default_length: f32) {
+        for index in self.leaf_joints() {
+            let joint = &mut self.joints[index];
+            if joint.is_root() || joint.end_site().is_some() {
+                continue;
+            }
+
+            let offset = *joint.offset();
+            let length = offset.iter().map(|v| v * v).sum::<f32>().sqrt();
+            let end_site = if length > 0.0 {
+                let scale = default_length / length;
+                [offset[0] * scale, offset[1] * scale, offset[2] * scale]
+            } else {
+                [0.0, default_length, 0.0]
+            };
+            joint.set_offset(end_site, true);
+        }
+    }
+
+    /// Add a channel of type `ty` to the end of the `CHANNELS` of the joint at index
+    /// `joint`, such as to add rotation channels to a root which only has position
+    /// channels. Every frame gets a value of `default_value` for the new channel.
+    ///
+    /// The new column is placed directly after the joint's other columns, or after
+    /// the columns of the joints before it if it has no channels, and the columns
+    /// after it are moved up by one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `joint` is out of range, or if the joint already has a
+    /// channel of type `ty`. In that case, the `Bvh` is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, ChannelType};
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 1 Zrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0
+    /// };
+    ///
+    /// bvh.add_channel(0, ChannelType::RotationY, 0.0)?;
+    /// assert_eq!(bvh.channel_index(0, ChannelType::RotationY), Some(1));
+    /// assert_eq!(bvh.frames().next().unwrap().as_slice(), &[1.0, 0.0, 2.0]);
+    /// assert!(bvh.add_channel(0, ChannelType::RotationY, 0.0).is_err());
+    /// # Result::<(), bvh_anim::errors::AddChannelError>::Ok(())
+    /// ```
+    pub fn add_channel(
+        &mut self,
+        joint: usize,
+        ty: ChannelType,
+        default_value: f32,
+    ) -> Result<(), AddChannelError> {
+        let num_joints = self.joints.len();
+        let data = self.joints.get(joint).ok_or_else(|| {
+            AddChannelError::JointOutOfRange(JointIndexError::out_of_range(joint, num_joints))
+        })?;
+        if data
+            .channels()
+            .iter()
+            .any(|channel| channel.channel_type() == ty)
+        {
+            return Err(AddChannelError::DuplicateChannel {
+                joint_index: joint,
+                channel_type: ty,
+            });
+        }
+
+        let column = self.joints[..=joint]
+            .iter()
+            .rev()
+            .find_map(|joint| {
+                joint
+                    .channels()
+                    .iter()
+                    .map(|channel| channel.motion_index() + 1)
+                    .max()
+            })
+            .unwrap_or(0);
+
+        for (index, data) in self.joints.iter_mut().enumerate() {
+            let mut channels = data
+                .channels()
+                .iter()
+                .map(|channel| {
+                    let motion_index = channel.motion_index();
+                    let motion_index = if motion_index >= column {
+                        motion_index + 1
+                    } else {
+                        motion_index
+                    };
+                    Channel::new(channel.channel_type(), motion_index)
+                })
+                .collect::<SmallVec<[Channel; 6]>>();
+            if index == joint {
+                channels.push(Channel::new(ty, column));
+            }
+            data.set_channels(channels);
+        }
+
+        let num_channels = self.num_channels;
+        let mut motion_values = Vec::with_capacity(self.frames().len() * (num_channels + 1));
+        if num_channels == 0 {
+            motion_values.resize(self.frames().len(), default_value);
+        } else {
+            for frame in self.motion_values.chunks_exact(num_channels) {
+                motion_values.extend_from_slice(&frame[..column]);
+                motion_values.push(default_value);
+                motion_values.extend_from_slice(&frame[column..]);
+            }
+        }
+
+        self.num_channels += 1;
+        self.motion_values = motion_values;
+        Ok(())
+    }
+
+    /// Remove the motion columns for which `removed[motion_index]` is `true`, and the
+    /// channels which refer to them.
+    pub(crate) fn remove_channel_columns(&mut self, removed: &[bool]) {
+        if !removed.iter().any(|&r| r) {
+            return;
+        }
+
+        let mut new_indices = vec![None; self.num_channels];
+        let mut num_kept = 0;
+        for (new_index, &is_removed) in new_indices.iter_mut().zip(removed) {
+            if !is_removed {
+                *new_index = Some(num_kept);
+                num_kept += 1;
+            }
+        }
+
+        for joint in &mut self.joints {
+            let channels = joint
+                .channels()
+                .iter()
+                .filter_map(|channel| {
+                    new_indices[channel.motion_index()]
+                        .map(|index| Channel::new(channel.channel_type(), index))
+                })
+                .collect::<SmallVec<[Channel; 6]>>();
+            joint.set_channels(channels);
+        }
+
+        let mut motion_values = Vec::with_capacity(self.frames().len() * num_kept);
+        for frame in self.motion_values.chunks_exact(self.num_channels) {
+            motion_values.extend(
+                frame
+                    .iter()
+                    .zip(removed)
+                    .filter(|(_, &is_removed)| !is_removed)
+                    .map(|(&value, _)| value),
+            );
+        }
+
+        self.num_channels = num_kept;
+        self.motion_values = motion_values;
+    }
+}
+
+/// Combine the clips `a` and `b` into one `Bvh`, so that both can be watched
+/// together in a viewer, such as to compare the source and target of a retarget.
+///
+/// The skeletons of `a` and `b` are placed side by side under a new root joint
+/// named `SideBySide`, which has no channels, and `b` is moved by `offset` so
+/// that it does not overlap `a`. Each frame has the values of `a` followed by
+/// the values of `b`. Duplicate joint names are made unique as by
+/// [`Bvh::deduplicate_names`], so the joints of `b` usually gain a suffix.
+///
+/// # Errors
+///
+/// Returns an error if the clips have a different number of frames, or if their
+/// frame times differ by more than [`Bvh::DEFAULT_FRAME_TIME_TOLERANCE`].
+///
+/// # Examples
+///
+/// ```
+/// # use bvh_anim::bvh;
+/// let bvh = bvh! {
+///     HIERARCHY
+///     ROOT Hips
+///     {
+///         OFFSET 0.0 0.0 0.0
+///         CHANNELS 3 Xposition Yposition Zposition
+///         JOINT Chest
+///         {
+///             OFFSET 0.0 1.0 0.0
+///             CHANNELS 0
+///             End Site
+///             {
+///                 OFFSET 0.0 1.0 0.0
+///             }
+///         }
+///     }
+///     MOTION
+///     Frames: 1
+///     Frame Time: 0.033333333
+///     0.0 5.0 0.0
+/// };
+///
+/// let both = bvh_anim::side_by_side(&bvh, &bvh, [10.0, 0.0, 0.0])?;
+/// let names = both.joints().map(|joint| joint.name().to_vec()).collect::<Vec<_>>();
+/// assert_eq!(
+///     names,
+///     [&b"SideBySide"[..], b"Hips", b"Chest", b"Hips.001", b"Chest.001"]
+/// );
+///
+/// let positions = both.world_positions(&both.frames().next().unwrap());
+/// assert_eq!(positions[1], [0.0, 5.0, 0.0]);
+/// assert_eq!(positions[3], [10.0, 5.0, 0.0]);
+/// # Result::<(), bvh_anim::errors::ClipMismatchError>::Ok(())
+/// ```
+///
+/// [`Bvh::deduplicate_names`]: struct.Bvh.html#method.deduplicate_names
+/// [`Bvh::DEFAULT_FRAME_TIME_TOLERANCE`]: struct.Bvh.html#associatedconstant.DEFAULT_FRAME_TIME_TOLERANCE
+pub fn side_by_side(a: &Bvh, b: &Bvh, offset: Offset) -> Result<Bvh, ClipMismatchError> {
+    if a.frames().len() != b.frames().len() {
+        return Err(ClipMismatchError::FrameCount {
+            expected: a.frames().len(),
+            actual: b.frames().len(),
+        });
+    }
+    if a.frame_time.abs_diff(b.frame_time) > Bvh::DEFAULT_FRAME_TIME_TOLERANCE {
+        return Err(ClipMismatchError::FrameTime {
+            expected: a.frame_time,
+            actual: b.frame_time,
+        });
+    }
+
+    let mut root = JointData::empty_root();
+    root.set_name(b"SideBySide".to_vec());
+    let mut joints = vec![root];
+
+    for (clip, shift, first_column) in [(a, [0.0; 3], 0), (b, offset, a.num_channels)]
+        .iter()
+        .copied()
+    {
+        let first_index = joints.len();
+        for (index, joint) in clip.joints.iter().enumerate() {
+            let mut new_joint = JointData::empty_child();
+            new_joint.set_name(joint.name().to_vec());
+
+            let mut joint_offset = *joint.offset();
+            if joint.is_root() {
+                for (value, shift) in joint_offset.iter_mut().zip(shift.iter()) {
+                    *value += shift;
+                }
+            }
+            new_joint.set_offset(joint_offset, false);
+            if let Some(&end_site) = joint.end_site() {
+                new_joint.set_offset(end_site, true);
+            }
+
+            new_joint.set_channels(
+                joint
+                    .channels()
+                    .iter()
+                    .map(|channel| {
+                        Channel::new(
+                            channel.channel_type(),
+                            first_column + channel.motion_index(),
+                        )
+                    })
+                    .collect(),
+            );
+
+            let parent = joint
+                .parent_index()
+                .map(|parent| first_index + parent)
+                .unwrap_or(0);
+            if let Some(private) = new_joint.private_data_mut() {
+                *private = JointPrivateData::new(first_index + index, parent, joint.depth() + 1);
+            }
+            joints.push(new_joint);
+        }
+    }
+
+    let mut motion_values = Vec::with_capacity(a.motion_values.len() + b.motion_values.len());
+    for (row_a, row_b) in a.frame_rows().zip(b.frame_rows()) {
+        motion_values.extend_from_slice(row_a);
+        motion_values.extend_from_slice(row_b);
+    }
+
+    let mut bvh = Bvh {
+        joints,
+        motion_values,
+        num_channels: a.num_channels + b.num_channels,
+        frame_time: a.frame_time,
+        ..Bvh::new()
+    };
+    bvh.deduplicate_names();
+    Ok(bvh)
+}
+
+/// Blend the pose `from` towards the pose `to` by `t`, in place.
+///
+/// Rotations of joints with three distinct rotation axes are blended with a
+/// spherical interpolation, and every other channel is blended linearly. The
+/// blended angles are kept as close as possible to the angles of `from`.
+pub(crate) fn blend_pose(joints: &[JointData], from: &mut [f32], to: &[f32], t: f32) {
+    for joint in joints {
+        let rotations = joint
+            .channels()
+            .iter()
+            .filter(|channel| channel.channel_type().is_rotation())
+            .collect::<SmallVec<[&Channel; 3]>>();
+        let order = match rotations[..] {
+            [a, b, c] => {
+                let order = [
+                    a.channel_type().axis(),
+                    b.channel_type().axis(),
+                    c.channel_type().axis(),
+                ];
+                if order[0] != order[1] && order[1] != order[2] && order[0] != order[2] {
+                    Some(order)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        for channel in joint.channels() {
+            if order.is_none() || !channel.channel_type().is_rotation() {
+                let index = channel.motion_index();
+                from[index] += (to[index] - from[index]) * t;
+            }
+        }
+
+        if let Some(order) = order {
+            let angles = |motion: &[f32]| {
+                let mut angles = [0.0; 3];
+                for (angle, channel) in angles.iter_mut().zip(rotations.iter()) {
+                    *angle = motion[channel.motion_index()];
+                }
+                angles
+            };
+            let original = angles(from);
+            let quat_from = math::mat_to_quat(&math::euler_to_mat(order, original));
+            let quat_to = math::mat_to_quat(&math::euler_to_mat(order, angles(to)));
+            let blended = math::quat_slerp(quat_from, quat_to, t);
+            let blended = math::mat_to_euler(&math::quat_to_mat(blended), order);
+            for ((channel, &angle), &target) in
+                rotations.iter().zip(blended.iter()).zip(original.iter())
+            {
+                from[channel.motion_index()] = closest_angle(angle, target);
+            }
+        }
+    }
+}
+
+/// Greedily choose the keyframes of a track with `num_frames` frames, where
+/// `fits(start, end, frame)` returns whether `frame` can be reconstructed by
+/// interpolating between the keyframes `start` and `end`. The first and last frames
+/// are always keyframes.
+fn reduce_track(num_frames: usize, fits: impl Fn(usize, usize, usize) -> bool) -> Vec<usize> {
+    let mut keys = vec![0];
+    let mut start = 0;
+    let mut end = 1;
+    while end + 1 < num_frames {
+        let next = end + 1;
+        if (start + 1..next).all(|frame| fits(start, next, frame)) {
+            end = next;
+        } else {
+            keys.push(end);
+            start = end;
+            end = start + 1;
+        }
+    }
+    keys.push(num_frames - 1);
+    keys
+}
+
+/// Returns the frames in between each pair of consecutive `keys`, along with the
+/// value returned by `interpolate(start, end, frame)` for them.
+fn snap_between_keys<T>(
+    keys: &[usize],
+    interpolate: impl Fn(usize, usize, usize) -> T,
+) -> Vec<(usize, T)> {
+    keys.windows(2)
+        .flat_map(|pair| {
+            let (start, end) = (pair[0], pair[1]);
+            (start + 1..end).map(move |frame| (start, end, frame))
+        })
+        .map(|(start, end, frame)| (frame, interpolate(start, end, frame)))
+        .collect()
+}
+
+#[inline]
+fn add(a: Offset, b: Offset) -> Offset {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+#[inline]
+fn scaled(offset: &Offset, scale: f32) -> Offset {
+    [offset[0] * scale, offset[1] * scale, offset[2] * scale]
+}
+
+/// Returns `-value`, except that `0.0` stays `0.0` rather than becoming `-0.0`,
+/// so that it is not written as `-0`.
+fn negated(value: f32) -> f32 {
+    if value == 0.0 {
+        value
+    } else {
+        -value
+    }
+}
+
+/// Returns `angle` plus the multiple of `360` degrees which is closest to `target`.
+#[inline]
+pub(crate) fn closest_angle(angle: f32, target: f32) -> f32 {
+    angle + ((target - angle) / 360.0).round() * 360.0
+}
+
+#[inline]
+fn angle_distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonicalize_order_restores_shuffled_joints() {
+        let original = bvh! {
+            HIERARCHY
+            ROOT Base
+            {
+                OFFSET 0.0 0.0 0.0
+                CHANNELS 3 Xposition Yposition Zposition
+                JOINT Left
+                {
+                    OFFSET 1.0 0.0 0.0
+                    CHANNELS 1 Zrotation
+                    End Site
+                    {
+                        OFFSET 1.0 0.0 0.0
+                    }
+                }
+                JOINT Upper
+                {
+                    OFFSET 0.0 1.0 0.0
+                    CHANNELS 1 Xrotation
+                    JOINT Lower
+                    {
+                        OFFSET 0.0 1.0 0.0
+                        CHANNELS 1 Zrotation
+                        End Site
+                        {
+                            OFFSET 0.0 1.0 0.0
+                        }
+                    }
+                }
+            }
+            MOTION
+            Frames: 2
+            Frame Time: 0.033333333
+            0.0 1.0 2.0 10.0 20.0 30.0
+            3.0 4.0 5.0 40.0 50.0 60.0
+        };
+
+        // Store `Lower` before its parent `Upper`, with the motion columns in the
+        // same order as the shuffled joints.
+        let permutation = [0, 1, 3, 2];
+        let mut shuffled = original.clone();
+        let mut columns = Vec::new();
+        shuffled.joints = permutation
+            .iter()
+            .enumerate()
+            .map(|(new_index, &old_index)| {
+                let mut joint = original.joints[old_index].clone();
+                let channels = joint
+                    .channels()
+                    .iter()
+                    .map(|channel| {
+                        columns.push(channel.motion_index());
+                        Channel::new(channel.channel_type(), columns.len() - 1)
+                    })
+                    .collect();
+                joint.set_channels(channels);
+                if let Some(private) = joint.private_data_mut() {
+                    private.self_index = new_index;
+                    private.parent_index = permutation
+                        .iter()
+                        .position(|&old| old == private.parent_index)
+                        .unwrap();
+                }
+                joint
+            })
+            .collect();
+        shuffled.motion_values = original
+            .motion_values
+            .chunks_exact(original.num_channels)
+            .flat_map(|frame| columns.iter().map(move |&column| frame[column]))
+            .collect();
+        assert_ne!(shuffled.motion_values, original.motion_values);
+
+        let positions_by_name = |bvh: &Bvh| {
+            let mut positions = bvh
+                .frames()
+                .flat_map(|frame| {
+                    let positions = bvh.world_positions(&frame);
+                    bvh.joints
+                        .iter()
+                        .zip(positions)
+                        .map(|(joint, position)| (joint.name().to_vec(), position))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            positions.sort_by(|a, b| a.0.cmp(&b.0));
+            positions
+        };
+
+        let mut canonical = shuffled.clone();
+        canonical.canonicalize_order();
+        assert_eq!(canonical, original);
+        assert_eq!(positions_by_name(&canonical), positions_by_name(&original));
+
+        let motion_indices = canonical
+            .joints
+            .iter()
+            .flat_map(|joint| joint.channels().iter().map(|c| c.motion_index()))
+            .collect::<Vec<_>>();
+        assert_eq!(motion_indices, (0..6).collect::<Vec<_>>());
+    }
+}
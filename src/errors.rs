@@ -1,8 +1,9 @@
 //! Errors which may occur when manipulating `Bvh` files.
 
-use crate::Axis;
+use crate::{Axis, ChannelType};
+use bstr::BString;
 use lexical::Error as LexicalError;
-use std::{error::Error as StdError, fmt, io};
+use std::{error::Error as StdError, fmt, io, time::Duration};
 
 /// Errors which may arise when loading a `Bvh` file from
 /// a `Reader`.
@@ -34,6 +35,37 @@ impl LoadError {
     pub fn into_kind(self) -> LoadErrorKind {
         self.kind
     }
+
+    /// Returns the broad category of the `LoadError`, which can be matched on
+    /// without inspecting the specific `LoadJointsError` or `LoadMotionError`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::errors::LoadErrorCategory;
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    /// "#;
+    ///
+    /// let err = bvh_anim::from_bytes(&bvh_string[..]).unwrap_err();
+    /// assert_eq!(err.category(), LoadErrorCategory::FrameCountMismatch);
+    /// ```
+    #[inline]
+    pub fn category(&self) -> LoadErrorCategory {
+        match self.kind {
+            LoadErrorKind::Joints(ref e) => e.category(),
+            LoadErrorKind::Motion(ref e) => e.category(),
+        }
+    }
 }
 
 impl<K: Into<LoadErrorKind>> From<K> for LoadError {
@@ -65,8 +97,30 @@ impl StdError for LoadError {
     }
 }
 
+/// The broad category of a `LoadError`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum LoadErrorCategory {
+    /// An I/O error occurred.
+    Io,
+    /// A required section or value, such as the root joint or the `MOTION` section,
+    /// is missing.
+    MissingSection,
+    /// A token was found where it was not expected, or a token could not be
+    /// understood.
+    UnexpectedToken,
+    /// A number could not be parsed.
+    InvalidNumber,
+    /// A motion value was `NaN` or infinite.
+    NonFiniteValue,
+    /// The number of motion values does not match the number of frames and
+    /// channels.
+    FrameCountMismatch,
+}
+
 /// The kind of the `LoadError`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum LoadErrorKind {
     /// An error occurred when loading the joints hierarchy.
     Joints(LoadJointsError),
@@ -91,11 +145,17 @@ impl From<LoadMotionError> for LoadErrorKind {
 /// Represents an error which occurred when loading the `Joints` of the
 /// bvh file.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum LoadJointsError {
     /// An I/O error occurred.
     Io(io::Error),
     /// The skeletal hierarchy is missing the `Root` joint.
     MissingRoot,
+    /// A `ROOT` joint was found before the `HIERARCHY` keyword.
+    MissingHierarchy {
+        /// Line number in the source bvh where the error occurred.
+        line: usize,
+    },
     /// A name could not be found for the `Joint`.
     MissingJointName {
         /// Line number in the source bvh where the error occurred.
@@ -141,6 +201,28 @@ pub enum LoadJointsError {
         /// Line number in the source bvh where the error occurred.
         line: usize,
     },
+    /// An `End Site` had a `CHANNELS` section, which is not allowed.
+    EndSiteChannels {
+        /// Line number in the source bvh where the error occurred.
+        line: usize,
+    },
+    /// An `OFFSET` section had more than 3 values.
+    ExtraOffsetValues {
+        /// The number of values in the `OFFSET` section.
+        count: usize,
+        /// Line number in the source bvh where the error occurred.
+        line: usize,
+    },
+    /// The number of channels stated in a `CHANNELS` section did not match the
+    /// number of channel names listed after it.
+    ChannelCountMismatch {
+        /// The number of channels stated in the `CHANNELS` section.
+        expected: usize,
+        /// The number of channel names which were listed.
+        actual: usize,
+        /// Line number in the source bvh where the error occurred.
+        line: usize,
+    },
 }
 
 impl LoadJointsError {
@@ -150,15 +232,40 @@ impl LoadJointsError {
     pub fn line(&self) -> Option<usize> {
         match *self {
             LoadJointsError::MissingJointName { line }
+            | LoadJointsError::MissingHierarchy { line }
             | LoadJointsError::UnexpectedChannelsSection { line }
             | LoadJointsError::ParseNumChannelsError { line, .. }
             | LoadJointsError::ParseChannelError { line, .. }
             | LoadJointsError::UnexpectedOffsetSection { line }
             | LoadJointsError::ParseOffsetError { line, .. }
-            | LoadJointsError::MissingOffsetAxis { line, .. } => Some(line),
+            | LoadJointsError::MissingOffsetAxis { line, .. }
+            | LoadJointsError::EndSiteChannels { line }
+            | LoadJointsError::ExtraOffsetValues { line, .. }
+            | LoadJointsError::ChannelCountMismatch { line, .. } => Some(line),
             _ => None,
         }
     }
+
+    /// Returns the broad category of the `LoadJointsError`.
+    #[inline]
+    pub fn category(&self) -> LoadErrorCategory {
+        match *self {
+            LoadJointsError::Io(_) => LoadErrorCategory::Io,
+            LoadJointsError::MissingRoot | LoadJointsError::MissingHierarchy { .. } => {
+                LoadErrorCategory::MissingSection
+            }
+            LoadJointsError::ParseNumChannelsError { .. }
+            | LoadJointsError::ParseOffsetError { .. } => LoadErrorCategory::InvalidNumber,
+            LoadJointsError::MissingJointName { .. }
+            | LoadJointsError::UnexpectedChannelsSection { .. }
+            | LoadJointsError::ParseChannelError { .. }
+            | LoadJointsError::UnexpectedOffsetSection { .. }
+            | LoadJointsError::MissingOffsetAxis { .. }
+            | LoadJointsError::EndSiteChannels { .. }
+            | LoadJointsError::ExtraOffsetValues { .. }
+            | LoadJointsError::ChannelCountMismatch { .. } => LoadErrorCategory::UnexpectedToken,
+        }
+    }
 }
 
 impl From<io::Error> for LoadJointsError {
@@ -174,6 +281,11 @@ impl fmt::Display for LoadJointsError {
         match *self {
             LoadJointsError::Io(ref e) => fmt::Display::fmt(&e, f),
             LoadJointsError::MissingRoot => f.write_str("The root heirarchy could not be found"),
+            LoadJointsError::MissingHierarchy { line } => write!(
+                f,
+                "{}: the \"ROOT\" joint is not preceded by \"HIERARCHY\"",
+                line
+            ),
             LoadJointsError::MissingJointName { line } => {
                 write!(f, "{}: the name is missing from the joints section", line)
             }
@@ -199,6 +311,25 @@ impl fmt::Display for LoadJointsError {
             LoadJointsError::MissingOffsetAxis { axis, line } => {
                 write!(f, "{}: the {}-axis offset value is missing", line, axis)
             }
+            LoadJointsError::EndSiteChannels { line } => write!(
+                f,
+                "{}: an \"End Site\" cannot have a \"CHANNELS\" section",
+                line
+            ),
+            LoadJointsError::ExtraOffsetValues { count, line } => write!(
+                f,
+                "{}: the \"OFFSET\" section has {} values instead of 3",
+                line, count
+            ),
+            LoadJointsError::ChannelCountMismatch {
+                expected,
+                actual,
+                line,
+            } => write!(
+                f,
+                "{}: the \"CHANNELS\" section states {} channels, but lists {}",
+                line, expected, actual
+            ),
         }
     }
 }
@@ -228,6 +359,7 @@ impl StdError for LoadJointsError {
 /// Represents an error which occurred when loading the motion of the
 /// bvh file.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum LoadMotionError {
     /// An I/O error occurred.
     Io(io::Error),
@@ -254,7 +386,15 @@ pub enum LoadMotionError {
     ParseMotionSection {
         /// The parse error, or `None` if there was no number to be parsed.
         parse_error: LexicalError,
-        /// The index of the motion value where the error occurred.
+        /// The index of the channel within its frame where the error occurred.
+        channel_index: usize,
+        /// The line where the error occurred.
+        line: usize,
+    },
+    /// A motion value was `NaN` or infinite, and the `ParseOptions` did not allow
+    /// non-finite motion values.
+    NonFiniteMotionValue {
+        /// The index of the channel within its frame where the error occurred.
         channel_index: usize,
         /// The line where the error occurred.
         line: usize,
@@ -271,6 +411,52 @@ pub enum LoadMotionError {
         /// Expected number of clips.
         expected_num_clips: usize,
     },
+    /// A row of the `MOTION` section had the wrong number of values, so that the
+    /// frame at `frame_index` started in the middle of a line.
+    MisalignedMotionRow {
+        /// The index of the first frame which was misaligned.
+        frame_index: usize,
+        /// The line where the error occurred.
+        line: usize,
+    },
+    /// A row of motion values did not have one value for each channel, when the
+    /// motion is stored separately from the hierarchy.
+    MotionRowWidthMismatch {
+        /// The number of channels in the hierarchy.
+        expected_num_channels: usize,
+        /// The number of values in the row.
+        actual_num_values: usize,
+        /// The line where the error occurred.
+        line: usize,
+    },
+    /// The frame number at the start of a frame was not one more than the frame
+    /// number of the previous frame, when parsing with a leading frame column.
+    FrameIndexMismatch {
+        /// The expected frame number, or `None` if any whole number was expected
+        /// for the first frame.
+        expected: Option<usize>,
+        /// The frame number which was found.
+        actual: f32,
+        /// The line where the error occurred.
+        line: usize,
+    },
+    /// The motion had more frames than the `max_frames` of the `ParseOptions`,
+    /// and the `ParseOptions` did not allow the extra frames to be ignored.
+    TooManyFrames {
+        /// The largest number of frames which is allowed.
+        max_frames: usize,
+        /// The line where the first extra frame was found, or the line of the
+        /// `Frames` line if it promised too many frames.
+        line: usize,
+    },
+    /// The frame time multiplied by the `frame_stride` of the `ParseOptions` was
+    /// too large to be stored in a `Duration`.
+    FrameTimeOverflow {
+        /// The frame time which was read from the bvh.
+        frame_time: Duration,
+        /// The stride which the frame time was multiplied by.
+        stride: usize,
+    },
 }
 
 impl LoadMotionError {
@@ -281,10 +467,40 @@ impl LoadMotionError {
             LoadMotionError::MissingMotionSection { line }
             | LoadMotionError::MissingNumFrames { line, .. }
             | LoadMotionError::MissingFrameTime { line, .. }
-            | LoadMotionError::ParseMotionSection { line, .. } => Some(line),
+            | LoadMotionError::ParseMotionSection { line, .. }
+            | LoadMotionError::NonFiniteMotionValue { line, .. }
+            | LoadMotionError::MotionRowWidthMismatch { line, .. }
+            | LoadMotionError::MisalignedMotionRow { line, .. }
+            | LoadMotionError::FrameIndexMismatch { line, .. }
+            | LoadMotionError::TooManyFrames { line, .. } => Some(line),
             _ => None,
         }
     }
+
+    /// Returns the broad category of the `LoadMotionError`.
+    #[inline]
+    pub fn category(&self) -> LoadErrorCategory {
+        match *self {
+            LoadMotionError::Io(_) => LoadErrorCategory::Io,
+            LoadMotionError::MissingMotionSection { .. }
+            | LoadMotionError::MissingNumFrames {
+                parse_error: None, ..
+            }
+            | LoadMotionError::MissingFrameTime {
+                parse_error: None, ..
+            } => LoadErrorCategory::MissingSection,
+            LoadMotionError::MissingNumFrames { .. }
+            | LoadMotionError::MissingFrameTime { .. }
+            | LoadMotionError::ParseMotionSection { .. }
+            | LoadMotionError::FrameTimeOverflow { .. } => LoadErrorCategory::InvalidNumber,
+            LoadMotionError::NonFiniteMotionValue { .. } => LoadErrorCategory::NonFiniteValue,
+            LoadMotionError::MotionCountMismatch { .. }
+            | LoadMotionError::MotionRowWidthMismatch { .. }
+            | LoadMotionError::MisalignedMotionRow { .. }
+            | LoadMotionError::TooManyFrames { .. } => LoadErrorCategory::FrameCountMismatch,
+            LoadMotionError::FrameIndexMismatch { .. } => LoadErrorCategory::UnexpectedToken,
+        }
+    }
 }
 
 impl fmt::Display for LoadMotionError {
@@ -302,7 +518,7 @@ impl fmt::Display for LoadMotionError {
                 ref parse_error,
                 line,
             } => {
-                if let Some(_) = parse_error {
+                if parse_error.is_some() {
                     write!(fmtr, "{}: could not parse the num frames value", line)
                 } else {
                     write!(
@@ -316,7 +532,7 @@ impl fmt::Display for LoadMotionError {
                 ref parse_error,
                 line,
             } => {
-                if let Some(_) = parse_error {
+                if parse_error.is_some() {
                     write!(fmtr, "{}: could not parse the frame time", line)
                 } else {
                     write!(fmtr, "{}: Could not parse the frame time", line)
@@ -325,6 +541,16 @@ impl fmt::Display for LoadMotionError {
             LoadMotionError::ParseMotionSection { line, .. } => {
                 write!(fmtr, "{}: Could not parse the motion value", line)
             }
+            LoadMotionError::NonFiniteMotionValue {
+                channel_index,
+                line,
+            } => {
+                write!(
+                    fmtr,
+                    "{}: The motion value for channel {} is not finite",
+                    line, channel_index
+                )
+            }
             LoadMotionError::MotionCountMismatch {
                 actual_total_motion_values,
                 expected_total_motion_values,
@@ -339,6 +565,48 @@ impl fmt::Display for LoadMotionError {
                     expected_num_frames,
                     expected_num_clips)
             }
+            LoadMotionError::MisalignedMotionRow { frame_index, line } => write!(
+                fmtr,
+                "{}: frame {} starts in the middle of a line, so an earlier row has the wrong number of values",
+                line, frame_index
+            ),
+            LoadMotionError::MotionRowWidthMismatch {
+                expected_num_channels,
+                actual_num_values,
+                line,
+            } => write!(
+                fmtr,
+                "{}: expected {} motion values in the row, found {}",
+                line, expected_num_channels, actual_num_values
+            ),
+            LoadMotionError::FrameIndexMismatch {
+                expected: Some(expected),
+                actual,
+                line,
+            } => write!(
+                fmtr,
+                "{}: expected frame number {}, found {}",
+                line, expected, actual
+            ),
+            LoadMotionError::FrameIndexMismatch {
+                expected: None,
+                actual,
+                line,
+            } => write!(
+                fmtr,
+                "{}: expected a whole frame number, found {}",
+                line, actual
+            ),
+            LoadMotionError::TooManyFrames { max_frames, line } => write!(
+                fmtr,
+                "{}: the motion has more than the maximum of {} frames",
+                line, max_frames
+            ),
+            LoadMotionError::FrameTimeOverflow { frame_time, stride } => write!(
+                fmtr,
+                "The frame time of {:?} multiplied by the stride {} overflows",
+                frame_time, stride
+            ),
         }
     }
 }
@@ -457,6 +725,12 @@ impl FrameInsertError {
             kind: FrameInsertErrorKind::IncorrectFrameLength { expected, actual },
         }
     }
+
+    pub(crate) const fn out_of_range(index: usize, num_frames: usize) -> Self {
+        Self {
+            kind: FrameInsertErrorKind::IndexOutOfRange { index, num_frames },
+        }
+    }
 }
 
 impl fmt::Display for FrameInsertError {
@@ -470,6 +744,11 @@ impl fmt::Display for FrameInsertError {
                     expected,
                 )
             }
+            FrameInsertErrorKind::IndexOutOfRange { index, num_frames } => write!(
+                f,
+                "Attempted to insert a frame at index {} into a bvh containing {} frames",
+                index, num_frames,
+            ),
         }
     }
 }
@@ -479,6 +758,7 @@ impl StdError for FrameInsertError {}
 #[derive(Debug)]
 enum FrameInsertErrorKind {
     IncorrectFrameLength { expected: usize, actual: usize },
+    IndexOutOfRange { index: usize, num_frames: usize },
 }
 
 /// An error which may occur when attempting to remove a frame from a
@@ -505,3 +785,421 @@ impl fmt::Display for FrameRemoveError {
 }
 
 impl StdError for FrameRemoveError {}
+
+/// An error which may occur when attempting to retain a subset of the joints
+/// in a `Bvh`.
+#[derive(Debug)]
+pub struct RetainJointsError {
+    root_index: usize,
+}
+
+impl RetainJointsError {
+    pub(crate) const fn root_not_kept(root_index: usize) -> Self {
+        Self { root_index }
+    }
+
+    /// Returns the index of the root joint which was not kept.
+    #[inline]
+    pub const fn root_index(&self) -> usize {
+        self.root_index
+    }
+}
+
+impl fmt::Display for RetainJointsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Attempted to remove the root joint at index {}, which must always be kept",
+            self.root_index
+        )
+    }
+}
+
+impl StdError for RetainJointsError {}
+
+/// An error which occurs when a joint index does not refer to a joint in a `Bvh`.
+#[derive(Debug)]
+pub struct JointIndexError {
+    index: usize,
+    num_joints: usize,
+}
+
+impl JointIndexError {
+    pub(crate) const fn out_of_range(index: usize, num_joints: usize) -> Self {
+        Self { index, num_joints }
+    }
+
+    /// Returns the joint index which was out of range.
+    #[inline]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the number of joints in the `Bvh`.
+    #[inline]
+    pub const fn num_joints(&self) -> usize {
+        self.num_joints
+    }
+}
+
+impl fmt::Display for JointIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The joint index {} is out of range for a bvh with {} joints",
+            self.index, self.num_joints
+        )
+    }
+}
+
+impl StdError for JointIndexError {}
+
+/// An error which may occur when adding a channel to a joint with
+/// [`Bvh::add_channel`].
+///
+/// [`Bvh::add_channel`]: ../struct.Bvh.html#method.add_channel
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AddChannelError {
+    /// The joint index does not refer to a joint in the `Bvh`.
+    JointOutOfRange(JointIndexError),
+    /// The joint already has a channel of the given type.
+    DuplicateChannel {
+        /// The index of the joint.
+        joint_index: usize,
+        /// The type of the channel.
+        channel_type: ChannelType,
+    },
+}
+
+impl fmt::Display for AddChannelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            AddChannelError::JointOutOfRange(ref e) => fmt::Display::fmt(e, f),
+            AddChannelError::DuplicateChannel {
+                joint_index,
+                channel_type,
+            } => write!(
+                f,
+                "The joint at index {} already has a {} channel",
+                joint_index, channel_type
+            ),
+        }
+    }
+}
+
+impl StdError for AddChannelError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            AddChannelError::JointOutOfRange(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// An error which occurs when a slice of channel values does not have one value
+/// for every channel of a `Bvh`.
+#[derive(Debug)]
+pub struct ChannelCountError {
+    expected: usize,
+    actual: usize,
+}
+
+impl ChannelCountError {
+    pub(crate) const fn new(expected: usize, actual: usize) -> Self {
+        Self { expected, actual }
+    }
+
+    /// Returns the number of channels in the `Bvh`.
+    #[inline]
+    pub const fn expected(&self) -> usize {
+        self.expected
+    }
+
+    /// Returns the number of values which were given.
+    #[inline]
+    pub const fn actual(&self) -> usize {
+        self.actual
+    }
+}
+
+impl fmt::Display for ChannelCountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Expected {} channel values, but found {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl StdError for ChannelCountError {}
+
+/// An error which occurs when joints other than the roots of a `Bvh` have position
+/// channels.
+#[derive(Debug)]
+pub struct NonRootTranslationError {
+    joints: Vec<(usize, BString)>,
+}
+
+impl NonRootTranslationError {
+    pub(crate) const fn new(joints: Vec<(usize, BString)>) -> Self {
+        Self { joints }
+    }
+
+    /// Returns the index and name of each non-root joint with position channels,
+    /// in joint order.
+    #[inline]
+    pub fn joints(&self) -> &[(usize, BString)] {
+        &self.joints[..]
+    }
+}
+
+impl fmt::Display for NonRootTranslationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Only root joints may have position channels, but these joints do:")?;
+        for (index, name) in &self.joints {
+            write!(f, " {} (index {})", name, index)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for NonRootTranslationError {}
+
+/// An error which may occur when building a `Bvh` from a quaternion tensor.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum QuaternionTensorError {
+    /// The shape of the tensor does not match the skeleton of the template, or the
+    /// template has no joints.
+    ShapeMismatch {
+        /// The shape of the tensor.
+        shape: [usize; 3],
+        /// The number of joints in the template.
+        num_joints: usize,
+    },
+    /// The number of quaternion values does not match the shape of the tensor.
+    QuaternionsLength {
+        /// The number of values required by the shape.
+        expected: usize,
+        /// The number of values given.
+        actual: usize,
+    },
+    /// The number of root translation values does not match the number of frames.
+    RootTranslationLength {
+        /// The number of values required by the number of frames.
+        expected: usize,
+        /// The number of values given.
+        actual: usize,
+    },
+}
+
+impl fmt::Display for QuaternionTensorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            QuaternionTensorError::ShapeMismatch { shape, num_joints } => write!(
+                f,
+                "The tensor shape {:?} does not match a skeleton with {} joints",
+                shape, num_joints
+            ),
+            QuaternionTensorError::QuaternionsLength { expected, actual } => write!(
+                f,
+                "Expected {} quaternion values, but found {}",
+                expected, actual
+            ),
+            QuaternionTensorError::RootTranslationLength { expected, actual } => write!(
+                f,
+                "Expected {} root translation values, but found {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl StdError for QuaternionTensorError {}
+
+/// An error which may occur when applying a normalized frame or a `QuatPose` to a
+/// `Bvh`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum NormalizedFrameError {
+    /// There is no frame at the given index.
+    FrameOutOfRange {
+        /// The index of the frame.
+        index: usize,
+        /// The number of frames in the `Bvh`.
+        num_frames: usize,
+    },
+    /// The number of values does not match the number of joints.
+    Length {
+        /// The number of values required by the skeleton.
+        expected: usize,
+        /// The number of values given.
+        actual: usize,
+    },
+    /// The pose has a different number of joints than the `Bvh`.
+    JointCount {
+        /// The number of joints in the `Bvh`.
+        expected: usize,
+        /// The number of joints in the pose.
+        actual: usize,
+    },
+}
+
+impl fmt::Display for NormalizedFrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            NormalizedFrameError::FrameOutOfRange { index, num_frames } => write!(
+                f,
+                "Attempted to apply a frame at index {} to a bvh containing {} frames",
+                index, num_frames
+            ),
+            NormalizedFrameError::Length { expected, actual } => write!(
+                f,
+                "Expected {} normalized frame values, but found {}",
+                expected, actual
+            ),
+            NormalizedFrameError::JointCount { expected, actual } => write!(
+                f,
+                "Expected a pose with {} joints, but found {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl StdError for NormalizedFrameError {}
+
+/// An error which occurs when two `Bvh` clips which are compared or combined do not
+/// have the same skeleton, the same number of frames, or the same frame time.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ClipMismatchError {
+    /// The clips have a different number of joints.
+    JointCount {
+        /// The number of joints in the first clip.
+        expected: usize,
+        /// The number of joints in the second clip.
+        actual: usize,
+    },
+    /// The joint at `joint_index` has a different parent in each clip.
+    Hierarchy {
+        /// The index of the first joint whose parent differs.
+        joint_index: usize,
+    },
+    /// The joint at `joint_index` has different channels in each clip.
+    Channels {
+        /// The index of the first joint whose channels differ.
+        joint_index: usize,
+    },
+    /// The clips have a different number of frames.
+    FrameCount {
+        /// The number of frames in the first clip.
+        expected: usize,
+        /// The number of frames in the second clip.
+        actual: usize,
+    },
+    /// The clips have a different frame time.
+    FrameTime {
+        /// The frame time of the first clip.
+        expected: Duration,
+        /// The frame time of the second clip.
+        actual: Duration,
+    },
+}
+
+impl fmt::Display for ClipMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ClipMismatchError::JointCount { expected, actual } => write!(
+                f,
+                "The clips have different numbers of joints: {} and {}",
+                expected, actual
+            ),
+            ClipMismatchError::Hierarchy { joint_index } => write!(
+                f,
+                "The joint at index {} has a different parent in each clip",
+                joint_index
+            ),
+            ClipMismatchError::Channels { joint_index } => write!(
+                f,
+                "The joint at index {} has different channels in each clip",
+                joint_index
+            ),
+            ClipMismatchError::FrameCount { expected, actual } => write!(
+                f,
+                "The clips have different numbers of frames: {} and {}",
+                expected, actual
+            ),
+            ClipMismatchError::FrameTime { expected, actual } => write!(
+                f,
+                "The clips have different frame times: {:?} and {:?}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl StdError for ClipMismatchError {}
+
+/// An error which may occur when decoding a binary frame with
+/// [`Bvh::decode_binary_frame`].
+///
+/// [`Bvh::decode_binary_frame`]: ../struct.Bvh.html#method.decode_binary_frame
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BinaryFrameError {
+    /// There is no frame at the given index.
+    FrameOutOfRange {
+        /// The index of the frame.
+        index: usize,
+        /// The number of frames in the `Bvh`.
+        num_frames: usize,
+    },
+    /// A joint index does not refer to a joint in the `Bvh`.
+    JointOutOfRange(JointIndexError),
+    /// The first byte does not name a known quaternion encoding.
+    UnknownEncoding {
+        /// The first byte of the buffer.
+        tag: u8,
+    },
+    /// The length of the buffer does not match the encoding and the number of
+    /// joints.
+    Length {
+        /// The number of bytes required.
+        expected: usize,
+        /// The number of bytes given.
+        actual: usize,
+    },
+}
+
+impl fmt::Display for BinaryFrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            BinaryFrameError::FrameOutOfRange { index, num_frames } => write!(
+                f,
+                "Attempted to decode a frame at index {} into a bvh containing {} frames",
+                index, num_frames
+            ),
+            BinaryFrameError::JointOutOfRange(ref e) => fmt::Display::fmt(e, f),
+            BinaryFrameError::UnknownEncoding { tag } => {
+                write!(f, "Unknown binary frame encoding {}", tag)
+            }
+            BinaryFrameError::Length { expected, actual } => write!(
+                f,
+                "Expected a binary frame of {} bytes, but found {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl StdError for BinaryFrameError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            BinaryFrameError::JointOutOfRange(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
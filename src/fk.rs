@@ -0,0 +1,747 @@
+use crate::{
+    edit,
+    errors::ChannelCountError,
+    frames::Frame,
+    joint::{JointData, Offset},
+    math::{self, Mat, Quat},
+    tensor, Bvh,
+};
+
+impl Bvh {
+    /// Compute the transform of `joint` relative to its parent, using the
+    /// channel values in `motion`.
+    ///
+    /// The local transform is the translation by the joint's offset, followed by
+    /// each channel's transform in the order in which they are listed. A scale
+    /// channel scales along its axis, and so also scales the offsets of the joint's
+    /// children.
+    pub(crate) fn joint_local_transform(joint: &JointData, motion: &[f32]) -> Mat {
+        let mut transform = math::mat_translation(*joint.offset());
+        for channel in joint.channels() {
+            let value = motion[channel.motion_index()];
+            let ty = channel.channel_type();
+            let channel_transform = if ty.is_position() {
+                let mut v = [0.0; 3];
+                v[ty.axis() as usize] = value;
+                math::mat_translation(v)
+            } else if ty.is_scale() {
+                let mut v = [1.0; 3];
+                v[ty.axis() as usize] = value;
+                math::mat_scale(v)
+            } else {
+                math::mat_rotation(ty.axis(), value)
+            };
+            transform = math::mat_mul(&transform, &channel_transform);
+        }
+        transform
+    }
+
+    /// Returns the transform of every joint in the `Bvh` relative to its parent at
+    /// the given `frame`. The returned transforms are in the same order as
+    /// [`Bvh::joints`].
+    ///
+    /// The local transform of a joint is the translation by its offset, followed by
+    /// the transform of each of its channels in the order in which they are listed.
+    /// [`Bvh::world_transforms`] composes these transforms from the root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` has fewer values than the number of channels in the `Bvh`.
+    ///
+    /// [`Bvh::joints`]: struct.Bvh.html#method.joints
+    /// [`Bvh::world_transforms`]: struct.Bvh.html#method.world_transforms
+    pub fn local_transforms(&self, frame: &Frame<'_>) -> Vec<Mat> {
+        let motion = frame.as_slice();
+        self.joints
+            .iter()
+            .map(|joint| Bvh::joint_local_transform(joint, motion))
+            .collect()
+    }
+
+    /// Returns the world-space transform of every joint in the `Bvh` at the given
+    /// `frame`. The returned transforms are in the same order as [`Bvh::joints`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` has fewer values than the number of channels in the `Bvh`.
+    ///
+    /// [`Bvh::joints`]: struct.Bvh.html#method.joints
+    pub fn world_transforms(&self, frame: &Frame<'_>) -> Vec<Mat> {
+        let mut transforms = Vec::with_capacity(self.joints.len());
+        self.world_transforms_into(frame, &mut transforms);
+        transforms
+    }
+
+    /// Computes the world-space transform of every joint in the `Bvh` at the given
+    /// `frame`, like [`Bvh::world_transforms`], but writes them into `out` instead of
+    /// allocating a new `Vec`.
+    ///
+    /// Any previous contents of `out` are replaced. Reusing the same `out` for every
+    /// frame avoids allocating once it is large enough to hold every joint.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` has fewer values than the number of channels in the `Bvh`.
+    ///
+    /// [`Bvh::world_transforms`]: struct.Bvh.html#method.world_transforms
+    pub fn world_transforms_into(&self, frame: &Frame<'_>, out: &mut Vec<Mat>) {
+        let motion = frame.as_slice();
+        out.clear();
+        for joint in &self.joints {
+            let local = Bvh::joint_local_transform(joint, motion);
+            let transform = match joint.parent_index() {
+                Some(parent) => math::mat_mul(&out[parent], &local),
+                None => local,
+            };
+            out.push(transform);
+        }
+    }
+
+    /// Returns the world-space transform of every joint for the channel `values`
+    /// of a pose which is not stored in the `Bvh`, such as a candidate pose of an
+    /// IK solver. This is the same as [`Bvh::world_transforms`], except that
+    /// `values` does not need to be a frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `values` does not have one value for every channel of
+    /// the `Bvh`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, math};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let transforms = bvh.world_transforms_for_values(&[2.0])?;
+    /// assert_eq!(math::mat_get_translation(&transforms[1]), [2.0, 10.0, 0.0]);
+    /// assert!(bvh.world_transforms_for_values(&[]).is_err());
+    /// # Result::<(), bvh_anim::errors::ChannelCountError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::world_transforms`]: struct.Bvh.html#method.world_transforms
+    pub fn world_transforms_for_values(
+        &self,
+        values: &[f32],
+    ) -> Result<Vec<Mat>, ChannelCountError> {
+        if values.len() != self.num_channels {
+            return Err(ChannelCountError::new(self.num_channels, values.len()));
+        }
+
+        let mut transforms: Vec<Mat> = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            let local = Bvh::joint_local_transform(joint, values);
+            let transform = match joint.parent_index() {
+                Some(parent) => math::mat_mul(&transforms[parent], &local),
+                None => local,
+            };
+            transforms.push(transform);
+        }
+        Ok(transforms)
+    }
+
+    /// Returns the world-space transform of every joint in the rest pose, where every
+    /// position and rotation channel is `0` and every scale channel is `1`.
+    pub(crate) fn rest_world_transforms(&self) -> Vec<Mat> {
+        let mut motion = vec![0.0; self.num_channels];
+        for channel in self.joints.iter().flat_map(|joint| joint.channels()) {
+            if channel.channel_type().is_scale() {
+                motion[channel.motion_index()] = 1.0;
+            }
+        }
+
+        let mut transforms: Vec<Mat> = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            let local = Bvh::joint_local_transform(joint, &motion);
+            let transform = match joint.parent_index() {
+                Some(parent) => math::mat_mul(&transforms[parent], &local),
+                None => local,
+            };
+            transforms.push(transform);
+        }
+        transforms
+    }
+
+    /// Returns the world-space start and end points of every bone of the `Bvh` at the
+    /// given `frame`, such as to draw the bones as lines or capsules.
+    ///
+    /// There is a bone from every joint to each of its children, and from every joint
+    /// with an end site to its end site. The bones are in joint order: the bone from
+    /// a joint's parent to the joint comes first, followed by the bone to its end
+    /// site, if it has one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 0.0 0.0
+    /// };
+    ///
+    /// let frame = bvh.frames().next().unwrap();
+    /// assert_eq!(
+    ///     bvh.bone_segments(&frame),
+    ///     [
+    ///         ([1.0, 0.0, 0.0], [1.0, 10.0, 0.0]),
+    ///         ([1.0, 10.0, 0.0], [1.0, 15.0, 0.0]),
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` has fewer values than the number of channels in the `Bvh`.
+    pub fn bone_segments(&self, frame: &Frame<'_>) -> Vec<(Offset, Offset)> {
+        let transforms = self.world_transforms(frame);
+        let mut segments = Vec::with_capacity(self.joints.len() * 2);
+        for (joint, transform) in self.joints.iter().zip(&transforms) {
+            let position = math::mat_get_translation(transform);
+            if let Some(parent) = joint.parent_index() {
+                segments.push((math::mat_get_translation(&transforms[parent]), position));
+            }
+            if let Some(end_site) = joint.end_site() {
+                segments.push((position, math::transform_point(transform, *end_site)));
+            }
+        }
+        segments
+    }
+
+    /// Checks that forward kinematics in the rest pose places every joint at the sum
+    /// of its own offset and the offsets of its ancestors, as a self-check of the
+    /// transform composition used by [`Bvh::world_transforms`].
+    ///
+    /// In the rest pose every position and rotation channel is `0` and every scale
+    /// channel is `1`, so each joint's transform relative to its parent is just the
+    /// translation by its offset. Positions are compared with a relative tolerance
+    /// of `1.0e-4`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 1.0 0.0 0.0
+    ///         CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.verify_fk_consistency(), Ok(()));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a message describing the first joint whose position does not match.
+    ///
+    /// [`Bvh::world_transforms`]: struct.Bvh.html#method.world_transforms
+    pub fn verify_fk_consistency(&self) -> Result<(), String> {
+        check_rest_positions(&self.joints, &self.rest_world_transforms())
+    }
+
+    /// Returns the world-space orientation of every joint in the `Bvh` at the given
+    /// `frame`, without computing their positions, such as to orient bone meshes for
+    /// instancing. The returned quaternions are in the same order as
+    /// [`Bvh::joints`], and are stored as described in the [`math`] module.
+    ///
+    /// Each orientation is the rotation part of the corresponding transform returned
+    /// by [`Bvh::world_transforms`]. Scale channels are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` has fewer values than the number of channels in the `Bvh`.
+    ///
+    /// [`Bvh::joints`]: struct.Bvh.html#method.joints
+    /// [`math`]: math/index.html
+    /// [`Bvh::world_transforms`]: struct.Bvh.html#method.world_transforms
+    pub fn world_orientations(&self, frame: &Frame<'_>) -> Vec<Quat> {
+        let motion = frame.as_slice();
+        let mut orientations: Vec<Quat> = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            let local = tensor::joint_rotation(joint, motion);
+            let orientation = match joint.parent_index() {
+                Some(parent) => math::quat_normalize(math::quat_mul(orientations[parent], local)),
+                None => local,
+            };
+            orientations.push(orientation);
+        }
+        orientations
+    }
+
+    /// Returns the rotation of the joint at index `joint` relative to its parent at
+    /// the given `frame` as a unit rotation axis and an angle in radians about it.
+    ///
+    /// The rotation is the composition of the joint's rotation channels in the order
+    /// in which they are listed. The angle is in the range `[0, π]`. If the joint is
+    /// not rotated, then the angle is `0`, and the axis is the `X` axis.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0 0.0 0.0 90.0
+    /// };
+    ///
+    /// let frame = bvh.frames().next().unwrap();
+    /// let (axis, angle) = bvh.joint_axis_angle(1, &frame);
+    /// assert!((axis[1] - 1.0).abs() < 1.0e-5);
+    /// assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 1.0e-5);
+    ///
+    /// assert_eq!(bvh.joint_axis_angle(0, &frame), ([1.0, 0.0, 0.0], 0.0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `joint` is out of range, or if `frame` has fewer values than the
+    /// number of channels in the `Bvh`.
+    pub fn joint_axis_angle(&self, joint: usize, frame: &Frame<'_>) -> (Offset, f32) {
+        let rotation = tensor::joint_rotation(&self.joints[joint], frame.as_slice());
+        let (axis, degrees) = math::quat_to_axis_angle(rotation);
+        (axis, degrees.to_radians())
+    }
+
+    /// Returns the world-space position of every joint in the `Bvh` at the given
+    /// `frame`. The returned positions are in the same order as [`Bvh::joints`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 1 Zrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0 3.0 0.0
+    /// };
+    ///
+    /// let frame = bvh.frames().next().unwrap();
+    /// let positions = bvh.world_positions(&frame);
+    /// assert_eq!(positions, [[1.0, 2.0, 3.0], [1.0, 12.0, 3.0]]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` has fewer values than the number of channels in the `Bvh`.
+    ///
+    /// [`Bvh::joints`]: struct.Bvh.html#method.joints
+    pub fn world_positions(&self, frame: &Frame<'_>) -> Vec<Offset> {
+        self.world_transforms(frame)
+            .iter()
+            .map(math::mat_get_translation)
+            .collect()
+    }
+
+    /// Returns the world-space position of every joint at the time `t` in seconds,
+    /// interpolating between the two nearest frames, such as to play back a clip at
+    /// an arbitrary wall-clock time.
+    ///
+    /// The rotations of joints with three distinct rotation axes are interpolated
+    /// with a spherical interpolation, and every other channel is interpolated
+    /// linearly, before the positions are computed as with
+    /// [`Bvh::world_positions`]. `t` is clamped to the duration of the
+    /// animation, and `NaN` is treated as `0.0`.
+    ///
+    /// Returns an empty `Vec` if the `Bvh` has no frames.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     2.0
+    /// };
+    ///
+    /// let positions = bvh.world_positions_at_time(0.25);
+    /// assert_eq!(positions, [[1.0, 0.0, 0.0], [1.0, 10.0, 0.0]]);
+    /// ```
+    ///
+    /// [`Bvh::world_positions`]: struct.Bvh.html#method.world_positions
+    pub fn world_positions_at_time(&self, t: f64) -> Vec<Offset> {
+        let last = match self.num_frames().checked_sub(1) {
+            Some(last) => last,
+            None => return Vec::new(),
+        };
+
+        let frame_time = self.frame_time.as_secs_f64();
+        let position = if frame_time > 0.0 && t > 0.0 {
+            (t / frame_time).min(last as f64)
+        } else {
+            0.0
+        };
+        let before = (position.floor() as usize).min(last);
+        let after = (before + 1).min(last);
+        let t = (position - before as f64) as f32;
+
+        let num_channels = self.num_channels;
+        let mut motion = self.motion_values[before * num_channels..][..num_channels].to_vec();
+        if t > 0.0 {
+            let to = &self.motion_values[after * num_channels..][..num_channels];
+            edit::blend_pose(&self.joints, &mut motion, to, t);
+        }
+
+        let mut positions = Vec::with_capacity(self.joints.len());
+        let mut transforms: Vec<Mat> = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            let local = Bvh::joint_local_transform(joint, &motion);
+            let transform = match joint.parent_index() {
+                Some(parent) => math::mat_mul(&transforms[parent], &local),
+                None => local,
+            };
+            positions.push(math::mat_get_translation(&transform));
+            transforms.push(transform);
+        }
+        positions
+    }
+
+    /// Returns an iterator over the world-space position of every joint at every
+    /// frame, as `(time, joint, position)` records, such as to build a long format
+    /// table for plotting.
+    ///
+    /// `time` is the time of the frame in seconds, and `joint` is the index of the
+    /// joint. The records are ordered by frame, and then by joint. The positions of
+    /// each frame are computed as the iterator reaches it, with
+    /// [`Bvh::world_positions`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     1.0
+    /// };
+    ///
+    /// let records = bvh.position_records().collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     records,
+    ///     [
+    ///         (0.0, 0, [0.0, 0.0, 0.0]),
+    ///         (0.0, 1, [0.0, 10.0, 0.0]),
+    ///         (0.5, 0, [1.0, 0.0, 0.0]),
+    ///         (0.5, 1, [1.0, 10.0, 0.0]),
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// [`Bvh::world_positions`]: struct.Bvh.html#method.world_positions
+    pub fn position_records(&self) -> impl Iterator<Item = (f64, usize, Offset)> + '_ {
+        let frame_time = self.frame_time.as_secs_f64();
+        self.frames().enumerate().flat_map(move |(index, frame)| {
+            let time = index as f64 * frame_time;
+            self.world_positions(&frame)
+                .into_iter()
+                .enumerate()
+                .map(move |(joint, position)| (time, joint, position))
+        })
+    }
+
+    /// Returns the world-space position of the joint at index `joint` in every
+    /// frame, such as to draw its motion trail.
+    ///
+    /// Only the transforms of the joint and its ancestors are computed, so this is
+    /// cheaper than calling [`Bvh::world_positions`] for every frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     1.0
+    /// };
+    ///
+    /// assert_eq!(bvh.joint_trajectory(1), [[0.0, 10.0, 0.0], [1.0, 10.0, 0.0]]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `joint` is out of range.
+    ///
+    /// [`Bvh::world_positions`]: struct.Bvh.html#method.world_positions
+    pub fn joint_trajectory(&self, joint: usize) -> Vec<Offset> {
+        let mut chain = vec![joint];
+        while let Some(parent) = self.joints[*chain.last().unwrap()].parent_index() {
+            chain.push(parent);
+        }
+
+        self.frames()
+            .map(|frame| {
+                let motion = frame.as_slice();
+                let transform = chain.iter().rev().fold(math::IDENTITY, |parent, &index| {
+                    let local = Bvh::joint_local_transform(&self.joints[index], motion);
+                    math::mat_mul(&parent, &local)
+                });
+                math::mat_get_translation(&transform)
+            })
+            .collect()
+    }
+
+    /// Returns the transform of the joint at index `b` relative to the joint at
+    /// index `a` at the given `frame`, such as to attach a prop to a hand or to
+    /// measure the pose of one joint from another.
+    ///
+    /// The returned matrix is `world(a)⁻¹ * world(b)`, where `world` is the
+    /// world-space transform returned by [`Bvh::world_transforms`], so it maps
+    /// points in the space of `b` into the space of `a`. The relative transform of
+    /// a joint to itself is the identity.
+    ///
+    /// Returns `None` if the world-space transform of `a` is not invertible, which
+    /// only happens if a scale channel along its chain is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, math};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 10.0 0.0
+    ///             CHANNELS 1 Zrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0 3.0 0.0
+    /// };
+    ///
+    /// let frame = bvh.frames().next().unwrap();
+    /// let relative = bvh.relative_transform(0, 1, &frame).unwrap();
+    /// assert_eq!(math::mat_get_translation(&relative), [0.0, 10.0, 0.0]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is out of range, or if `frame` has fewer values than
+    /// the number of channels in the `Bvh`.
+    ///
+    /// [`Bvh::world_transforms`]: struct.Bvh.html#method.world_transforms
+    pub fn relative_transform(&self, a: usize, b: usize, frame: &Frame<'_>) -> Option<Mat> {
+        let transforms = self.world_transforms(frame);
+        let inverse = math::mat_affine_inverse(&transforms[a])?;
+        Some(math::mat_mul(&inverse, &transforms[b]))
+    }
+}
+
+/// Check that the rest pose `transforms` of `joints` place every joint at the sum of
+/// the offsets along its chain.
+fn check_rest_positions(joints: &[JointData], transforms: &[Mat]) -> Result<(), String> {
+    let mut expected: Vec<Offset> = Vec::with_capacity(joints.len());
+    for (index, (joint, transform)) in joints.iter().zip(transforms).enumerate() {
+        let offset = *joint.offset();
+        let position = match joint.parent_index() {
+            Some(parent) => {
+                let p = expected[parent];
+                [p[0] + offset[0], p[1] + offset[1], p[2] + offset[2]]
+            }
+            None => offset,
+        };
+
+        let actual = math::mat_get_translation(transform);
+        let matches = actual
+            .iter()
+            .zip(&position)
+            .all(|(a, e)| (a - e).abs() <= 1.0e-4 * e.abs().max(1.0));
+        if !matches {
+            return Err(format!(
+                "joint {} ({}) is at {:?} in the rest pose, but its offsets sum to {:?}",
+                index,
+                String::from_utf8_lossy(joint.name()),
+                actual,
+                position
+            ));
+        }
+        expected.push(position);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupted_rest_transform_is_detected() {
+        let bvh = bvh! {
+            HIERARCHY
+            ROOT Base
+            {
+                OFFSET 1.0 0.0 0.0
+                CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+                JOINT Middle
+                {
+                    OFFSET 0.0 10.0 0.0
+                    CHANNELS 3 Zrotation Xrotation Yrotation
+                    JOINT Tip
+                    {
+                        OFFSET 0.0 10.0 0.0
+                        CHANNELS 3 Zrotation Xrotation Yrotation
+                        End Site
+                        {
+                            OFFSET 0.0 5.0 0.0
+                        }
+                    }
+                }
+            }
+            MOTION
+            Frames: 0
+            Frame Time: 0.033333333
+        };
+
+        let mut transforms = bvh.rest_world_transforms();
+        assert_eq!(check_rest_positions(&bvh.joints, &transforms), Ok(()));
+
+        // Composing a joint's transform with itself instead of its parent's.
+        transforms[2] = math::mat_mul(&transforms[2], &transforms[2]);
+        let err = check_rest_positions(&bvh.joints, &transforms).unwrap_err();
+        assert!(err.starts_with("joint 2 (Tip)"), "{}", err);
+    }
+}
@@ -144,7 +144,7 @@ impl<'a> AsRef<[f32]> for Frame<'a> {
 impl<'a> Borrow<[f32]> for Frame<'a> {
     #[inline]
     fn borrow(&self) -> &[f32] {
-        &*self.0
+        self.0
     }
 }
 
@@ -197,7 +197,7 @@ impl<'a> FrameMut<'a> {
     /// Attempts to return a mutable reference to the motion value at `channel`.
     /// Otherwise, returns `None`.
     #[inline]
-    pub fn get_mut<I: FrameIndex>(&'a mut self, index: I) -> Option<&mut Output<I>> {
+    pub fn get_mut<I: FrameIndex>(&'a mut self, index: I) -> Option<&'a mut Output<I>> {
         self.0.get_mut(index.to_slice_index())
     }
 
@@ -1,10 +1,12 @@
 #![allow(dead_code, missing_docs)]
 
-use crate::Channel;
+use crate::{Channel, ChannelType};
 use bstr::{BStr, BString, ByteSlice};
 use smallvec::SmallVec;
 use std::{
+    borrow::Cow,
     cmp::{Ordering, PartialEq, PartialOrd},
+    collections::HashMap,
     ffi::{CStr, CString},
     fmt, mem,
     ops::{Deref, DerefMut},
@@ -15,6 +17,28 @@ use std::{
 /// end site.
 pub type Offset = [f32; 3];
 
+/// A lookup table from `Joint` names to their indices, created with
+/// [`Bvh::build_name_index`].
+///
+/// [`Bvh::build_name_index`]: ../struct.Bvh.html#method.build_name_index
+pub type NameIndex = HashMap<BString, usize>;
+
+/// The kind of a [`Joint`][`Joint`] in the skeleton, returned by
+/// [`Joint::kind`][`Joint::kind`].
+///
+/// [`Joint`]: struct.Joint.html
+/// [`Joint::kind`]: struct.Joint.html#method.kind
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum JointKind {
+    /// The root of the skeletal hierarchy.
+    Root,
+    /// A child joint which does not end in an end site.
+    Child,
+    /// A child joint which ends in an end site, at the end of a chain.
+    EndSite,
+}
+
 /// Internal representation of a joint.
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum JointData {
@@ -34,7 +58,7 @@ pub(crate) enum JointData {
         /// Positional offset of this `Joint` relative to the parent.
         offset: Offset,
         /// The channels applicable to this `Joint`.
-        channels: SmallVec<[Channel; 3]>,
+        channels: SmallVec<[Channel; 6]>,
         /// End site offset.
         end_site_offset: Option<Offset>,
         /// Private data.
@@ -45,18 +69,12 @@ pub(crate) enum JointData {
 impl JointData {
     #[inline]
     pub(crate) const fn is_root(&self) -> bool {
-        match *self {
-            JointData::Root { .. } => true,
-            _ => false,
-        }
+        matches!(*self, JointData::Root { .. })
     }
 
     #[inline]
     pub(crate) const fn is_child(&self) -> bool {
-        match *self {
-            JointData::Child { .. } => true,
-            _ => false,
-        }
+        matches!(*self, JointData::Child { .. })
     }
 
     #[inline]
@@ -143,7 +161,7 @@ impl JointData {
     pub(crate) fn empty_root() -> Self {
         JointData::Root {
             name: Default::default(),
-            offset: [0.0, 0.0, 0.0].into(),
+            offset: [0.0, 0.0, 0.0],
             channels: Default::default(),
         }
     }
@@ -152,7 +170,7 @@ impl JointData {
     pub(crate) fn empty_child() -> Self {
         JointData::Child {
             name: Default::default(),
-            offset: [0.0, 0.0, 0.0].into(),
+            offset: [0.0, 0.0, 0.0],
             channels: Default::default(),
             end_site_offset: Default::default(),
             private: JointPrivateData::empty(),
@@ -170,7 +188,13 @@ impl JointData {
     pub(crate) fn set_offset(&mut self, new_offset: impl Into<Offset>, is_site: bool) {
         let new_offset = new_offset.into();
         match *self {
-            JointData::Root { ref mut offset, .. } => *offset = new_offset,
+            // A root joint cannot carry an end site, so its end site offset is dropped
+            // rather than replacing the offset of the root itself.
+            JointData::Root { ref mut offset, .. } => {
+                if !is_site {
+                    *offset = new_offset;
+                }
+            }
             JointData::Child {
                 ref mut offset,
                 ref mut end_site_offset,
@@ -193,7 +217,7 @@ impl JointData {
             } => *channels = new_channels,
             JointData::Child {
                 ref mut channels, ..
-            } => *channels = new_channels.iter().map(|c| *c).collect(),
+            } => *channels = new_channels,
         }
     }
 
@@ -291,7 +315,7 @@ impl From<Vec<u8>> for JointName {
 impl From<&'_ [u8]> for JointName {
     #[inline]
     fn from(s: &'_ [u8]) -> Self {
-        JointName(s.into_iter().copied().collect::<JointNameInner>())
+        JointName(s.iter().copied().collect::<JointNameInner>())
     }
 }
 
@@ -421,24 +445,11 @@ impl<'a> Joints<'a> {
     /// need to iterate through to the end sites of all children, you will
     /// need to continually call `iter_children` on each `Joint` in the iterator.
     pub(crate) fn iter_children(joint: &Joint<'a>) -> Self {
-        let first_child = joint
-            .joints
-            .iter()
-            .find(|jd| {
-                if let Some(p) = jd.private_data() {
-                    p.parent_index == joint.index
-                } else {
-                    false
-                }
-            })
-            .map(JointData::index)
-            .unwrap();
-
         Joints {
             joints: joint.joints,
             // clips: joint.clips,
-            current_joint: joint.data().index(),
-            from_child: Some(first_child),
+            current_joint: joint.index + 1,
+            from_child: Some(joint.index),
         }
     }
 
@@ -462,6 +473,16 @@ impl<'a> Joints<'a> {
 impl<'a> Iterator for Joints<'a> {
     type Item = Joint<'a>;
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(parent) = self.from_child {
+            // Children are always stored after their parent.
+            let offset = self
+                .joints
+                .get(self.current_joint..)?
+                .iter()
+                .position(|jd| jd.private_data().is_some_and(|p| p.parent_index == parent))?;
+            self.current_joint += offset;
+        }
+
         if self.current_joint >= self.joints.len() {
             return None;
         }
@@ -470,12 +491,7 @@ impl<'a> Iterator for Joints<'a> {
             index: self.current_joint,
             joints: self.joints,
         });
-
-        if self.from_child.is_none() {
-            self.current_joint += 1;
-        } else {
-            unimplemented!()
-        }
+        self.current_joint += 1;
 
         joint
     }
@@ -610,6 +626,61 @@ impl Joint<'_> {
         self.data().is_child()
     }
 
+    /// Returns the kind of the `Joint`, such as to branch on whether it is the root
+    /// or at the end of a chain.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, JointKind};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             JOINT Head
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///                 CHANNELS 0
+    ///                 End Site
+    ///                 {
+    ///                     OFFSET 0.0 5.0 0.0
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.0333333
+    /// };
+    ///
+    /// let names = bvh
+    ///     .joints()
+    ///     .map(|joint| match joint.kind() {
+    ///         JointKind::Root => "root",
+    ///         JointKind::Child => "child",
+    ///         JointKind::EndSite => "end site",
+    ///         _ => unreachable!(),
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(names, ["root", "child", "end site"]);
+    /// ```
+    #[inline]
+    pub const fn kind(&self) -> JointKind {
+        if self.is_root() {
+            JointKind::Root
+        } else if self.has_end_site() {
+            JointKind::EndSite
+        } else {
+            JointKind::Child
+        }
+    }
+
     /// Returns the name of the `JointData`.
     ///
     /// # Examples
@@ -641,6 +712,44 @@ impl Joint<'_> {
         self.data().name()
     }
 
+    /// Returns the name of the `JointData` as a string, replacing any bytes which are
+    /// not valid UTF-8 with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// The name is only copied if it contains invalid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.0333333
+    /// };
+    ///
+    /// let root = bvh.root_joint().unwrap();
+    /// assert_eq!(root.name_lossy(), "Hips");
+    /// ```
+    #[inline]
+    pub fn name_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.name())
+    }
+
     /// Returns the offset of the `JointData` if it exists, or `None`.
     ///
     /// # Examples
@@ -765,7 +874,110 @@ impl Joint<'_> {
         self.data().channels()
     }
 
+    /// Returns `true` if the `Joint` has a channel of type `channel_type`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, ChannelType};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let hips = bvh.root_joint().unwrap();
+    /// assert!(hips.has_position() && !hips.has_rotation());
+    /// assert!(hips.has_channel(ChannelType::PositionY));
+    ///
+    /// let chest = bvh.joints().find_by_name("Chest").unwrap();
+    /// assert!(chest.has_rotation() && !chest.has_position());
+    /// assert!(!chest.has_channel(ChannelType::PositionY));
+    /// ```
+    #[inline]
+    pub fn has_channel(&self, channel_type: ChannelType) -> bool {
+        self.channels()
+            .iter()
+            .any(|channel| channel.channel_type() == channel_type)
+    }
+
+    /// Returns `true` if the `Joint` has at least one position channel.
+    ///
+    /// See [`has_channel`](#method.has_channel) for an example.
+    #[inline]
+    pub fn has_position(&self) -> bool {
+        self.channels()
+            .iter()
+            .any(|channel| channel.channel_type().is_position())
+    }
+
+    /// Returns `true` if the `Joint` has at least one rotation channel.
+    ///
+    /// See [`has_channel`](#method.has_channel) for an example.
+    #[inline]
+    pub fn has_rotation(&self) -> bool {
+        self.channels()
+            .iter()
+            .any(|channel| channel.channel_type().is_rotation())
+    }
+
     /// Returns the index of this joint in the `Bvh`'s array of joints.
+    ///
+    /// The index can be used to find the joint again with [`Bvh::joint`], or to
+    /// relate it to the motion values of its channels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0 10.0 0.0 0.0
+    ///     0.0 0.0 0.0 20.0 0.0 0.0
+    /// };
+    ///
+    /// let index = bvh.joints().find_by_name("Chest").unwrap().index();
+    /// assert_eq!(index, 1);
+    ///
+    /// let chest = bvh.joint(index).unwrap();
+    /// let column = chest.channels()[0].motion_index();
+    /// let values = bvh.frames().map(|frame| frame[column]).collect::<Vec<_>>();
+    /// assert_eq!(values, [10.0, 20.0]);
+    /// ```
+    ///
+    /// [`Bvh::joint`]: ../struct.Bvh.html#method.joint
     #[inline]
     pub const fn index(&self) -> usize {
         self.index
@@ -790,7 +1002,14 @@ impl Joint<'_> {
     /// Returns an iterator over the children of `self`.
     #[inline]
     pub fn children(&self) -> Joints<'_> {
-        Joints::iter_children(&self)
+        Joints::iter_children(self)
+    }
+
+    /// Returns the depth of the `Joint` in the hierarchy, which is `0` for the root
+    /// joint and `1` for a joint attached to the root.
+    #[inline]
+    pub const fn depth(&self) -> usize {
+        self.data().depth()
     }
 
     /// Access a read-only view of the internal data of the `Joint`.
@@ -844,6 +1063,15 @@ impl<'a> JointMut<'a> {
         &self.joints[self_index]
     }
     */
+    /// Returns the index of this joint in the `Bvh`'s array of joints. See
+    /// [`Joint::index`].
+    ///
+    /// [`Joint::index`]: struct.Joint.html#method.index
+    #[inline]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
     /// Mutable access to the internal data of the `JointMut`.
     #[inline]
     pub(crate) fn data_mut(&mut self) -> &mut JointData {
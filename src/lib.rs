@@ -118,12 +118,13 @@
 //!
 //! There are a few ways to create a [`Bvh`][`Bvh`] struct:
 //!
-//! * You can use the [`from_reader`][`from_reader`] function, which will parse a `BufRead`
+//! * You can use the [`from_reader`][`from_reader`] function, which will parse any `Read`
 //!   as a bvh file. The [`from_bytes`][`from_bytes`] function is a convenient wrapper function
 //!   to parse an in-memory slice of bytes as a `bvh` file. Note that the file does not need to
 //!   be strictly UTF-8, although it should be an ascii-compatible encoding. These functions are
 //!   also available as associated methods on the `Bvh` type directly as [`Bvh::from_reader`]
-//!   [`Bvh::from_reader`] and [`Bvh::from_bytes`][`Bvh::from_bytes`]
+//!   [`Bvh::from_reader`] and [`Bvh::from_bytes`][`Bvh::from_bytes`]. Various aspects of the
+//!   parsing can be customised using the [`ParseOptions`][`ParseOptions`] type.
 //!
 //! * You can use the [`bvh!`][`bvh!`] macro to construct a [`Bvh`][`Bvh`] instance in your source files
 //!   using the same syntax as you would use for a standard bvh file.
@@ -148,6 +149,10 @@
 //!   belonging to an associated [`Joint`][`Joint`] of the [`Bvh`][`Bvh`], although you can convert
 //!   it into an [`&[`][`slice`][`f32`][`f32`][`]`][`slice`] using the [`Frame::as_slice`][`Frame::as_slice`] method.
 //!
+//! * The [`Bvh::world_transforms`][`Bvh::world_transforms`] and [`Bvh::world_positions`]
+//!   [`Bvh::world_positions`] methods evaluate the skeleton at a [`Frame`][`Frame`] using
+//!   forward kinematics. See the [`math`][`math`] module for the conventions used.
+//!
 //! * You can serialise the [`Bvh`][`Bvh`] into a [`Write`][`Write`] type using the [`Bvh::write_to`]
 //!   [`Bvh::write_to`] method. There is also the [`Bvh::to_bstring`][`Bvh::to_bstring`] method, which
 //!   converts the [`Bvh`][`Bvh`] into a [`BString`][`BString`]. Various aspects of the formatting
@@ -185,10 +190,14 @@
 //! [`f32`]: https://doc.rust-lang.org/stable/std/primitive.f32.html
 //! [`Channel`]: struct.Channel.html
 //! [`Frame::as_slice`]: struct.Frame.html#method.as_slice
+//! [`Bvh::world_transforms`]: struct.Bvh.html#method.world_transforms
+//! [`Bvh::world_positions`]: struct.Bvh.html#method.world_positions
+//! [`math`]: math/index.html
 //! [`Write`]: https://doc.rust-lang.org/stable/std/io/trait.Write.html
 //! [`Bvh::write_to`]: struct.Bvh.html#method.write_to
 //! [`Bvh::to_bstring`]: struct.Bvh.html#method.to_bstring
 //! [`BString`]: https://docs.rs/bstr/0.1.2/bstr/struct.BString.html
+//! [`ParseOptions`]: read/struct.ParseOptions.html
 //! [`WriteOptions`]: write/struct.WriteOptions.html
 //! [Github]: https://github.com/burtonageo/bvh_anim/tree/master/examples
 //! [bvh_html]: https://research.cs.wisc.edu/graphics/Courses/cs-838-1999/Jeff/BVH.html
@@ -197,28 +206,45 @@
 #[macro_use]
 mod macros;
 
+pub mod diagnostic;
 pub mod errors;
 
+pub mod read;
+
 pub mod write;
 
+mod analysis;
+mod annotation;
+mod asf;
+#[cfg(feature = "rayon")]
+mod batch;
+mod edit;
+mod fk;
 mod frame_cursor;
 mod frame_iter;
 pub mod joint;
+pub mod math;
+#[cfg(feature = "nalgebra")]
+mod na;
+#[cfg(feature = "ndarray")]
+mod nd;
 mod parse;
+mod preserve;
+mod tensor;
 
 use crate::{
-    errors::{LoadError, ParseChannelError},
+    errors::{FrameInsertError, FrameRemoveError, LoadError, ParseChannelError},
     frames::{FrameCursor, Frames, FramesMut},
-    joint::{JointData, Offset},
-};
-use bstr::{
-    io::{BufReadExt, ByteLines},
-    BStr, ByteSlice,
+    joint::{JointData, NameIndex, Offset},
+    preserve::Preserved,
+    read::ParseOptions,
 };
+use bstr::{io::BufReadExt, BStr, BString, ByteSlice};
 use std::{
+    collections::BTreeMap,
     convert::TryFrom,
     fmt,
-    io::{self, Cursor, Write},
+    io::{self, BufRead, Cursor, Read, Write},
     iter::Enumerate,
     mem,
     num::NonZeroUsize,
@@ -234,21 +260,29 @@ pub mod frames {
     pub use crate::frame_iter::{Frame, FrameIndex, FrameMut, Frames, FramesMut};
 }
 
-pub use joint::{Joint, JointMut, Joints, JointsMut};
+pub use analysis::{ChannelStats, PoseMetric, RigMismatch};
+#[cfg(feature = "rayon")]
+pub use batch::load_dir;
+pub use edit::{side_by_side, ResizeMode};
+pub use joint::{Joint, JointKind, JointMut, Joints, JointsMut};
 #[doc(hidden)]
 pub use macros::BvhLiteralBuilder;
+pub use tensor::{QuatEncoding, QuatPose};
 
-struct CachedEnumerate<I> {
+struct CachedEnumerate<I: Iterator> {
     iter: Enumerate<I>,
     last_enumerator: Option<usize>,
+    /// An item which was put back to be returned again by the next call to `next`.
+    put_back: Option<(usize, I::Item)>,
 }
 
-impl<I> CachedEnumerate<I> {
+impl<I: Iterator> CachedEnumerate<I> {
     #[inline]
     fn new(iter: Enumerate<I>) -> Self {
         CachedEnumerate {
             iter,
             last_enumerator: None,
+            put_back: None,
         }
     }
 
@@ -256,12 +290,22 @@ impl<I> CachedEnumerate<I> {
     fn last_enumerator(&self) -> Option<usize> {
         self.last_enumerator
     }
+
+    /// Put back `item`, which was returned by `next`, so that it is returned again
+    /// by the next call to `next`.
+    #[inline]
+    fn put_back(&mut self, item: (usize, I::Item)) {
+        self.put_back = Some(item);
+    }
 }
 
 impl<I: Iterator> Iterator for CachedEnumerate<I> {
     type Item = <Enumerate<I> as Iterator>::Item;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(item) = self.put_back.take() {
+            return Some(item);
+        }
         let (curr, item) = self.iter.next()?;
         self.last_enumerator = Some(curr);
         Some((curr, item))
@@ -269,11 +313,81 @@ impl<I: Iterator> Iterator for CachedEnumerate<I> {
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.iter.size_hint()
+        let (lower, upper) = self.iter.size_hint();
+        let put_back = usize::from(self.put_back.is_some());
+        (
+            lower.saturating_add(put_back),
+            upper.and_then(|upper| upper.checked_add(put_back)),
+        )
+    }
+}
+
+/// An iterator over the lines of a reader, like `BufReadExt::byte_lines`, except
+/// that `\r`, `\n` and `\r\n` are all treated as line terminators. The terminator
+/// is not included in the line.
+struct ByteLines<'a> {
+    reader: &'a mut dyn BufReadExt,
+    /// Whether the last line ended with a `\r` at the end of the buffer, so that a
+    /// `\n` at the start of the next buffer is part of the same terminator.
+    skip_newline: bool,
+}
+
+impl<'a> ByteLines<'a> {
+    #[inline]
+    fn new(reader: &'a mut dyn BufReadExt) -> Self {
+        ByteLines {
+            reader,
+            skip_newline: false,
+        }
+    }
+}
+
+impl Iterator for ByteLines<'_> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = Vec::new();
+        let mut read_any = false;
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            };
+            if buf.is_empty() {
+                return if read_any { Some(Ok(line)) } else { None };
+            }
+            if mem::take(&mut self.skip_newline) && buf[0] == b'\n' {
+                self.reader.consume(1);
+                continue;
+            }
+
+            read_any = true;
+            match buf.iter().position(|&b| b == b'\n' || b == b'\r') {
+                Some(end) => {
+                    line.extend_from_slice(&buf[..end]);
+                    let mut consumed = end + 1;
+                    if buf[end] == b'\r' {
+                        match buf.get(consumed) {
+                            Some(b'\n') => consumed += 1,
+                            Some(_) => {}
+                            None => self.skip_newline = true,
+                        }
+                    }
+                    self.reader.consume(consumed);
+                    return Some(Ok(line));
+                }
+                None => {
+                    line.extend_from_slice(buf);
+                    let consumed = buf.len();
+                    self.reader.consume(consumed);
+                }
+            }
+        }
     }
 }
 
-type EnumeratedLines<'a> = CachedEnumerate<ByteLines<&'a mut dyn BufReadExt>>;
+type EnumeratedLines<'a> = CachedEnumerate<ByteLines<'a>>;
 
 impl EnumeratedLines<'_> {
     pub(crate) fn next_non_empty_line(&mut self) -> Option<<Self as Iterator>::Item> {
@@ -297,14 +411,19 @@ impl EnumeratedLines<'_> {
     }
 }
 
-/// Loads the `Bvh` from the `reader`.
+/// Loads the `Bvh` from the `reader`, which may be any type implementing `Read`.
 #[inline]
-pub fn from_reader<R: BufReadExt>(data: R) -> Result<Bvh, LoadError> {
+pub fn from_reader<R: Read>(data: R) -> Result<Bvh, LoadError> {
     Bvh::from_reader(data)
 }
 
 /// Parse a sequence of bytes as if it were an in-memory `Bvh` file.
 ///
+/// Parsing does no I/O, so to load a `Bvh` from an asynchronous reader without
+/// blocking the runtime, read the whole stream into a buffer first (for example
+/// with `tokio::io::AsyncReadExt::read_to_end`), and then pass the buffer to this
+/// function.
+///
 /// # Examples
 ///
 /// ```
@@ -371,6 +490,12 @@ pub fn from_str(string: &str) -> Result<Bvh, LoadError> {
 ///
 /// See the [module documentation](index.html#using-this-library)
 /// for more information.
+///
+/// Comparing two `Bvh`s with `==` is exact, so `Bvh`s which differ only by
+/// floating point noise compare unequal. Use [`Bvh::approx_eq`][`Bvh::approx_eq`]
+/// for a tolerant comparison.
+///
+/// [`Bvh::approx_eq`]: struct.Bvh.html#method.approx_eq
 #[derive(Clone, Debug, PartialEq)]
 pub struct Bvh {
     /// The list of joints. If the root joint exists, it is always at
@@ -382,6 +507,15 @@ pub struct Bvh {
     num_channels: usize,
     /// The total time it takes to play one frame.
     frame_time: Duration,
+    /// Whether the `Frame Time` line was missing when parsing, so that the frame
+    /// time is the fallback of the `ParseOptions`.
+    frame_time_inferred: bool,
+    /// Labels attached to individual frames, keyed by frame index.
+    annotations: BTreeMap<usize, Vec<BString>>,
+    /// Key-value metadata from the `# key: value` comments before the hierarchy.
+    metadata: BTreeMap<BString, BString>,
+    /// The original source of the `Bvh`, if it was parsed with `Bvh::parse_preserving`.
+    preserved: Preserved,
 }
 
 impl Bvh {
@@ -393,6 +527,10 @@ impl Bvh {
             motion_values: Vec::new(),
             num_channels: 0,
             frame_time: Duration::from_secs(0),
+            frame_time_inferred: false,
+            annotations: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            preserved: Preserved::none(),
         }
     }
 
@@ -430,20 +568,229 @@ impl Bvh {
 
     /// Loads the `Bvh` from the `reader`.
     #[inline]
-    pub fn from_reader<R: BufReadExt>(mut reader: R) -> Result<Self, LoadError> {
-        #[inline(never)]
-        fn from_reader_(reader: &mut dyn BufReadExt) -> Result<Bvh, LoadError> {
-            let mut lines = CachedEnumerate::new(reader.byte_lines().enumerate());
+    ///
+    /// The `reader` may be any type implementing `Read`, such as a `File`, a
+    /// `TcpStream`, or a byte slice. It is buffered internally, so it does not need
+    /// to be wrapped in a `BufReader`.
+    ///
+    /// # Notes
+    ///
+    /// To customise how the `Bvh` is parsed, see the [`ParseOptions`][`ParseOptions`]
+    /// type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// # use std::fs::File;
+    /// let file = File::open("./data/test_simple.bvh")?;
+    /// let from_file = Bvh::from_reader(file)?;
+    ///
+    /// let bytes = std::fs::read("./data/test_simple.bvh")?;
+    /// let from_slice = Bvh::from_reader(&bytes[..])?;
+    ///
+    /// assert_eq!(from_file, from_slice);
+    /// # Result::<(), Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    ///
+    /// [`ParseOptions`]: read/struct.ParseOptions.html
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, LoadError> {
+        ParseOptions::new().read(reader)
+    }
+
+    /// Loads a `Bvh` which is embedded in a larger stream, such as a custom container
+    /// format, starting from the current position of `reader`.
+    ///
+    /// Parsing stops after the last frame given by the `Frames` line, so that
+    /// `reader` is left positioned at the start of the line after it, and the caller
+    /// can continue reading the rest of the stream. Blank lines before the
+    /// `HIERARCHY` line are skipped.
+    ///
+    /// To customise how the `Bvh` is parsed, see
+    /// [`ParseOptions::read_from`][`ParseOptions::read_from`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// # use std::io::{BufRead, Cursor};
+    /// let container = b"HIERARCHY
+    /// ROOT Hips
+    /// {
+    ///     OFFSET 0.0 0.0 0.0
+    ///     CHANNELS 3 Xposition Yposition Zposition
+    ///     JOINT Chest
+    ///     {
+    ///         OFFSET 0.0 5.0 0.0
+    ///         CHANNELS 0
+    ///         End Site
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///         }
+    ///     }
+    /// }
+    /// MOTION
+    /// Frames: 1
+    /// Frame Time: 0.033333333
+    /// 0.0 1.0 2.0
+    /// END_OF_BVH
+    /// ";
+    ///
+    /// let mut reader = Cursor::new(&container[..]);
+    /// let bvh = Bvh::parse_from(&mut reader)?;
+    /// assert_eq!(bvh.num_frames(), 1);
+    ///
+    /// let mut rest = String::new();
+    /// reader.read_line(&mut rest)?;
+    /// assert_eq!(rest, "END_OF_BVH\n");
+    /// # Result::<(), Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    ///
+    /// [`ParseOptions::read_from`]: read/struct.ParseOptions.html#method.read_from
+    #[inline]
+    pub fn parse_from<R: BufRead>(reader: &mut R) -> Result<Self, LoadError> {
+        ParseOptions::new().read_from(reader)
+    }
 
+    /// Loads a `Bvh` whose skeleton and motion are stored separately, from the
+    /// `hierarchy` and `motion` readers.
+    ///
+    /// `hierarchy` must contain a `HIERARCHY` section; anything after the root joint,
+    /// such as a `MOTION` section, is ignored. `motion` must contain the values of
+    /// one frame on each line, which may be preceded by any of the `MOTION`,
+    /// `Frames` and `Frame Time` lines. If the `Frames` line is present, then the
+    /// number of rows must match it, and if the `Frame Time` line is missing, then
+    /// the frame time is `0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either part cannot be parsed, or if a row of `motion`
+    /// does not have one value for each channel in `hierarchy`. The line numbers of
+    /// errors refer to the reader in which the error occurred.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// # use std::time::Duration;
+    /// let hierarchy = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    /// "#;
+    /// let motion = b"Frame Time: 0.5\n0.0 1.0 2.0\n3.0 4.0 5.0\n";
+    ///
+    /// let bvh = Bvh::from_parts(&hierarchy[..], &motion[..])?;
+    /// assert_eq!(bvh.num_frames(), 2);
+    /// assert_eq!(*bvh.frame_time(), Duration::from_millis(500));
+    ///
+    /// assert!(Bvh::from_parts(&hierarchy[..], &b"0.0 1.0\n"[..]).is_err());
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    pub fn from_parts<R1: BufRead, R2: BufRead>(
+        mut hierarchy: R1,
+        mut motion: R2,
+    ) -> Result<Self, LoadError> {
+        #[inline(never)]
+        fn from_parts_(
+            hierarchy: &mut dyn BufReadExt,
+            motion: &mut dyn BufReadExt,
+        ) -> Result<Bvh, LoadError> {
+            let options = ParseOptions::new();
             let mut bvh = Bvh::default();
 
-            bvh.read_joints(&mut lines)?;
-            bvh.read_motion(&mut lines)?;
+            let mut lines = CachedEnumerate::new(ByteLines::new(hierarchy).enumerate());
+            bvh.read_joints(&mut lines, &options, &mut Vec::new())?;
+
+            let mut lines = CachedEnumerate::new(ByteLines::new(motion).enumerate());
+            bvh.read_motion_rows(&mut lines, &options)?;
 
             Ok(bvh)
         }
 
-        from_reader_(reader.by_ref())
+        from_parts_(&mut hierarchy, &mut motion)
+    }
+
+    /// Replaces the frames of the `Bvh` with motion read from `reader`, keeping the
+    /// existing skeleton, such as to reload a clip whose motion was edited
+    /// externally.
+    ///
+    /// `reader` is read in the same format as the `motion` part of
+    /// [`Bvh::from_parts`]: the values of one frame on each line, which may be
+    /// preceded by any of the `MOTION`, `Frames` and `Frame Time` lines. If the
+    /// `Frame Time` line is missing, then the frame time is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the motion cannot be parsed, or if a row does not have
+    /// one value for each channel of the `Bvh`, in which case the `Bvh` is left
+    /// unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    /// };
+    ///
+    /// bvh.reload_motion(&mut &b"Frames: 2\n1.0 2.0 3.0\n4.0 5.0 6.0\n"[..])?;
+    /// assert_eq!(bvh.num_frames(), 2);
+    ///
+    /// assert!(bvh.reload_motion(&mut &b"1.0 2.0\n"[..]).is_err());
+    /// assert_eq!(bvh.num_frames(), 2);
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::from_parts`]: struct.Bvh.html#method.from_parts
+    pub fn reload_motion<R: BufRead>(&mut self, reader: &mut R) -> Result<(), LoadError> {
+        #[inline(never)]
+        fn reload_motion_(bvh: &mut Bvh, motion: &mut dyn BufReadExt) -> Result<(), LoadError> {
+            let mut reloaded = Bvh {
+                num_channels: bvh.num_channels,
+                frame_time: bvh.frame_time,
+                ..Bvh::new()
+            };
+
+            let mut lines = CachedEnumerate::new(ByteLines::new(motion).enumerate());
+            reloaded.read_motion_rows(&mut lines, &ParseOptions::new())?;
+
+            bvh.motion_values = reloaded.motion_values;
+            bvh.frame_time = reloaded.frame_time;
+            Ok(())
+        }
+
+        reload_motion_(self, reader)
     }
 
     /// Writes the `Bvh` using the `bvh` file format to the `writer`, with
@@ -490,6 +837,104 @@ impl Bvh {
         write::WriteOptions::default().write_to_string(self)
     }
 
+    /// Writes a single frame of the `Bvh` as a complete `bvh` file to the `writer`,
+    /// with the given formatting options. The written file contains the whole
+    /// hierarchy, and a motion section with only the frame at index `frame`.
+    ///
+    /// This is useful for exporting a static pose.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with the kind [`io::ErrorKind::InvalidInput`] if `frame`
+    /// is out of range, in which case nothing is written.
+    ///
+    /// [`io::ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_frame<W: Write>(
+        &self,
+        frame: usize,
+        writer: &mut W,
+        options: &write::WriteOptions,
+    ) -> io::Result<()> {
+        let motion_values = self.frames().nth(frame).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("The frame at index {} does not exist", frame),
+            )
+        })?;
+
+        let pose = Bvh {
+            joints: self.joints.clone(),
+            motion_values: motion_values.as_slice().to_vec(),
+            num_channels: self.num_channels,
+            frame_time: self.frame_time,
+            metadata: self.metadata.clone(),
+            ..Bvh::new()
+        };
+        options.write(&pose, writer)
+    }
+
+    /// Returns `true` if `self` and `other` have the same skeleton and motion, where
+    /// the offsets, the motion values and the frame time in seconds may differ by
+    /// at most `epsilon`.
+    ///
+    /// The names, channels and hierarchy of the joints must be identical. Frame
+    /// annotations are not compared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// # use std::time::Duration;
+    /// let a = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 1.0 2.0
+    /// };
+    ///
+    /// let mut b = a.clone();
+    /// b.set_frame_time(Duration::from_nanos(33_333_334));
+    ///
+    /// assert_ne!(a, b);
+    /// assert!(a.approx_eq(&b, 1.0e-6));
+    /// ```
+    pub fn approx_eq(&self, other: &Bvh, epsilon: f32) -> bool {
+        let close = |a: f32, b: f32| (a - b).abs() <= epsilon;
+        let close_offsets = |a: &Offset, b: &Offset| a.iter().zip(b).all(|(&a, &b)| close(a, b));
+
+        let joints_match = self.joints.len() == other.joints.len()
+            && self.joints.iter().zip(&other.joints).all(|(a, b)| {
+                a.name() == b.name()
+                    && a.channels() == b.channels()
+                    && a.parent_index() == b.parent_index()
+                    && close_offsets(a.offset(), b.offset())
+                    && match (a.end_site(), b.end_site()) {
+                        (Some(a), Some(b)) => close_offsets(a, b),
+                        (None, None) => true,
+                        _ => false,
+                    }
+            });
+
+        let frame_time_delta =
+            (self.frame_time.as_secs_f64() - other.frame_time.as_secs_f64()).abs();
+
+        joints_match
+            && frame_time_delta <= f64::from(epsilon)
+            && self.num_channels == other.num_channels
+            && self.motion_values.len() == other.motion_values.len()
+            && self
+                .motion_values
+                .iter()
+                .zip(&other.motion_values)
+                .all(|(&a, &b)| close(a, b))
+    }
+
     /// Returns the root joint if it exists, or `None` if the skeleton is empty.
     ///
     /// # Examples
@@ -531,117 +976,1016 @@ impl Bvh {
         }
     }
 
-    /// Returns an iterator over all the `Joint`s in the `Bvh`.
-    #[inline]
-    pub fn joints(&self) -> Joints<'_> {
-        Joints::iter_root(&self.joints[..])
-    }
-
-    /// Returns a mutable iterator over all the joints in the `Bvh`.
-    #[inline]
-    pub fn joints_mut(&mut self) -> JointsMut<'_> {
-        JointsMut::iter_root(&mut self.joints[..])
-    }
-
-    /// Returns a `Frames` iterator over the frames of the bvh.
+    /// Returns the `Joint` at `index` in the `Bvh`'s array of joints, or `None` if
+    /// `index` is out of range.
     ///
-    /// # Example
+    /// The returned `Joint` gives access to everything about the joint, such as its
+    /// name, offset, channels, parent, children, depth and kind.
+    ///
+    /// # Examples
     ///
     /// ```
-    /// # use bvh_anim::Bvh;
-    /// # fn use_bvh(bvh: Bvh) {
-    /// let bvh = // ...
-    /// # bvh;
-    /// for frame in bvh.frames() {
-    ///     // use frame...
-    ///     # let _ = frame;
-    /// }
-    /// # } // fn use_bvh()
-    /// ```
-    #[inline]
-    pub fn frames(&self) -> Frames<'_> {
-        Frames {
-            chunks: NonZeroUsize::new(self.num_channels).map(|_| {
-                self.motion_values
-                    .as_slice()
-                    .chunks_exact(self.num_channels)
-            }),
-        }
-    }
-
-    /// Returns a mutable iterator over the frames of the bvh.
+    /// # use bvh_anim::{bvh, JointKind};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 2.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
     ///
-    /// # Example
+    /// let chest = bvh.joint(1).unwrap();
+    /// assert_eq!(chest.name(), b"Chest");
+    /// assert_eq!(*chest.offset(), [0.0, 5.0, 0.0]);
+    /// assert_eq!(chest.channels().len(), 3);
+    /// assert_eq!(chest.parent().unwrap().name(), b"Hips");
+    /// assert_eq!(chest.children().count(), 0);
+    /// assert_eq!(chest.depth(), 1);
+    /// assert_eq!(chest.kind(), JointKind::EndSite);
     ///
-    /// ```
-    /// # use bvh_anim::Bvh;
-    /// # fn use_bvh(bvh: Bvh) {
-    /// let mut bvh = // ...
-    /// # bvh;
-    /// for frame in bvh.frames_mut() {
-    ///     // use frame...
-    ///     # let _ = frame;
-    /// }
-    /// # } // fn use_bvh()
+    /// assert!(bvh.joint(2).is_none());
     /// ```
     #[inline]
-    pub fn frames_mut(&mut self) -> FramesMut<'_> {
-        FramesMut {
-            chunks: NonZeroUsize::new(self.num_channels).map(move |_| {
-                self.motion_values
-                    .as_mut_slice()
-                    .chunks_exact_mut(self.num_channels)
-            }),
+    pub fn joint(&self, index: usize) -> Option<Joint<'_>> {
+        if index < self.joints.len() {
+            Some(Joint {
+                index,
+                joints: &self.joints[..],
+            })
+        } else {
+            None
         }
     }
 
-    /// Removes all frame data from the `Bvh`, returning the previous frames.
+    /// Returns an iterator over the indices of the root joints of the `Bvh`, in
+    /// joint order.
+    ///
+    /// A `Bvh` parsed from a file has a single root, but code which iterates over the
+    /// roots does not need to assume this.
     ///
     /// # Example
     ///
     /// ```
-    /// # use bvh_anim::bvh;
-    /// let mut bvh = bvh! {
+    /// # use bvh_anim::{Bvh, bvh};
+    /// assert_eq!(Bvh::new().roots().count(), 0);
+    ///
+    /// let bvh = bvh! {
     ///     HIERARCHY
-    ///     // hierarchy omitted...
-    ///     # ROOT Hips
-    ///     # {
-    ///     #     OFFSET 0.0 0.0 0.0
-    ///     #     CHANNELS 3 Xposition Yposition Zposition
-    ///     #     End Site
-    ///     #     {
-    ///     #         OFFSET 0.0 0.0 0.0
-    ///     #     }
-    ///     # }
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
     ///     MOTION
-    ///     Frames: 1
+    ///     Frames: 0
     ///     Frame Time: 0.033333333
-    ///     0.0 0.0 0.0
     /// };
     ///
-    /// let frames = bvh.extract_frames();
-    /// assert_eq!(bvh.num_frames(), 0);
-    /// assert_eq!(bvh.frames().len(), 0);
-    /// assert_eq!(frames, &[0.0, 0.0, 0.0]);
+    /// assert_eq!(bvh.roots().collect::<Vec<_>>(), [0]);
     /// ```
     #[inline]
-    pub fn extract_frames(&mut self) -> Vec<f32> {
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.joints
+            .iter()
+            .enumerate()
+            .filter(|(_, joint)| joint.is_root())
+            .map(|(index, _)| index)
+    }
+
+    /// Returns the number of root joints in the `Bvh`. See [`Bvh::roots`].
+    ///
+    /// [`Bvh::roots`]: struct.Bvh.html#method.roots
+    #[inline]
+    pub fn num_roots(&self) -> usize {
+        self.roots().count()
+    }
+
+    /// Returns an iterator over all the `Joint`s in the `Bvh`.
+    #[inline]
+    pub fn joints(&self) -> Joints<'_> {
+        Joints::iter_root(&self.joints[..])
+    }
+
+    /// Returns a mutable iterator over all the joints in the `Bvh`.
+    #[inline]
+    pub fn joints_mut(&mut self) -> JointsMut<'_> {
+        JointsMut::iter_root(&mut self.joints[..])
+    }
+
+    /// Returns the indices of the joints for which `pred` returns `true`, in joint
+    /// order, such as to select the joints of a limb by name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// use bstr::ByteSlice;
+    ///
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hand
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT IndexFinger1
+    ///         {
+    ///             OFFSET 0.0 2.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             JOINT IndexFinger2
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///                 CHANNELS 3 Zrotation Xrotation Yrotation
+    ///                 End Site
+    ///                 {
+    ///                     OFFSET 0.0 1.0 0.0
+    ///                 }
+    ///             }
+    ///         }
+    ///         JOINT Thumb1
+    ///         {
+    ///             OFFSET 1.0 1.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 1.0 0.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let fingers = bvh.joints_matching(|joint| joint.name().contains_str("Finger"));
+    /// assert_eq!(fingers, [1, 2]);
+    ///
+    /// let leaves = bvh.joints_matching(|joint| joint.children().next().is_none());
+    /// assert_eq!(leaves, [2, 3]);
+    /// ```
+    pub fn joints_matching<F>(&self, pred: F) -> Vec<usize>
+    where
+        F: Fn(Joint<'_>) -> bool,
+    {
+        (0..self.joints.len())
+            .filter(|&index| {
+                pred(Joint {
+                    index,
+                    joints: &self.joints[..],
+                })
+            })
+            .collect()
+    }
+
+    /// Build a lookup table from the name of each `Joint` to its index. If several
+    /// joints share a name, then the first one is used.
+    ///
+    /// The table is not updated when the `Bvh` is modified, so it should be rebuilt
+    /// after the joints are changed. See [`Bvh::find_joint_indexed`][`Bvh::find_joint_indexed`]
+    /// for performing lookups.
+    ///
+    /// [`Bvh::find_joint_indexed`]: struct.Bvh.html#method.find_joint_indexed
+    pub fn build_name_index(&self) -> NameIndex {
+        let mut index = NameIndex::with_capacity(self.joints.len());
+        for (i, joint) in self.joints.iter().enumerate() {
+            index.entry(BString::from(joint.name())).or_insert(i);
+        }
+        index
+    }
+
+    /// Finds the `Joint` named `joint_name` using a `NameIndex` previously built with
+    /// [`Bvh::build_name_index`][`Bvh::build_name_index`], or `None` if it doesn't exist.
+    ///
+    /// If the `index` is out of date, and the joint it refers to no longer has the name
+    /// `joint_name`, then `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Zrotation Xrotation Yrotation
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 0.0 15.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 0.0 30.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let index = bvh.build_name_index();
+    /// let tip = bvh.find_joint_indexed(&index, "Tip").unwrap();
+    /// assert_eq!(tip.index(), 1);
+    /// assert!(bvh.find_joint_indexed(&index, "Missing").is_none());
+    /// ```
+    ///
+    /// [`Bvh::build_name_index`]: struct.Bvh.html#method.build_name_index
+    pub fn find_joint_indexed<B>(&self, index: &NameIndex, joint_name: &B) -> Option<Joint<'_>>
+    where
+        B: ?Sized + AsRef<[u8]>,
+    {
+        let joint_name = joint_name.as_ref();
+        index
+            .get(joint_name.as_bstr())
+            .copied()
+            .filter(|&i| self.joints.get(i).map(JointData::name) == Some(joint_name))
+            .map(|i| Joint {
+                index: i,
+                joints: &self.joints[..],
+            })
+    }
+
+    /// Returns the index of the parent of the joint at `index`, or `None` if it is
+    /// the root joint, or if `index` is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Middle
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             JOINT Tip
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///                 CHANNELS 0
+    ///                 End Site
+    ///                 {
+    ///                     OFFSET 0.0 1.0 0.0
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.parent_of(2), Some(1));
+    /// assert_eq!(bvh.parent_of(1), Some(0));
+    /// assert_eq!(bvh.parent_of(0), None);
+    /// ```
+    #[inline]
+    pub fn parent_of(&self, index: usize) -> Option<usize> {
+        self.joints.get(index).and_then(JointData::parent_index)
+    }
+
+    /// Returns the indices of the leaf joints of the `Bvh`, which are the joints
+    /// without any child joints, such as fingertips, toes and the head. These are
+    /// usually the joints with an end site, and are often used as end effectors.
+    ///
+    /// The indices are in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             JOINT Head
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///                 CHANNELS 0
+    ///                 End Site
+    ///                 {
+    ///                     OFFSET 0.0 1.0 0.0
+    ///                 }
+    ///             }
+    ///         }
+    ///         JOINT Leg
+    ///         {
+    ///             OFFSET 0.0 -5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 -5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.leaf_joints(), [2, 3]);
+    /// ```
+    pub fn leaf_joints(&self) -> Vec<usize> {
+        let mut has_children = vec![false; self.joints.len()];
+        for parent in self.joints.iter().filter_map(JointData::parent_index) {
+            has_children[parent] = true;
+        }
+        has_children
+            .iter()
+            .enumerate()
+            .filter(|&(_, &has_children)| !has_children)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Returns the offset of every joint in joint order as one contiguous `Vec`,
+    /// such as to upload the rest pose of the skeleton to a GPU buffer.
+    ///
+    /// If `include_end_sites` is `true`, then the offset of each end site follows
+    /// the offset of its joint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.offsets(false), [[0.0, 0.0, 0.0], [0.0, 5.0, 0.0]]);
+    /// assert_eq!(
+    ///     bvh.offsets(true),
+    ///     [[0.0, 0.0, 0.0], [0.0, 5.0, 0.0], [0.0, 1.0, 0.0]]
+    /// );
+    /// ```
+    pub fn offsets(&self, include_end_sites: bool) -> Vec<Offset> {
+        let mut offsets = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            offsets.push(*joint.offset());
+            if include_end_sites {
+                offsets.extend(joint.end_site().copied());
+            }
+        }
+        offsets
+    }
+
+    /// Returns the path of the joint at `index`, which is the name of each of its
+    /// ancestors starting at the root, followed by its own name, joined by `/`, such
+    /// as `Hips/Chest/LeftCollar`.
+    ///
+    /// See [`Bvh::find_by_path`][`Bvh::find_by_path`] to find the joint from its path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    ///
+    /// [`Bvh::find_by_path`]: struct.Bvh.html#method.find_by_path
+    pub fn joint_path(&self, index: usize) -> BString {
+        let mut names = vec![self.joints[index].name()];
+        let mut current = self.joints[index].parent_index();
+        while let Some(parent) = current {
+            names.push(self.joints[parent].name());
+            current = self.joints[parent].parent_index();
+        }
+
+        let mut path = BString::from(Vec::new());
+        for (i, name) in names.iter().rev().enumerate() {
+            if i > 0 {
+                path.push(b'/');
+            }
+            path.extend_from_slice(name);
+        }
+        path
+    }
+
+    /// Returns the index of the joint with the `path` created by
+    /// [`Bvh::joint_path`][`Bvh::joint_path`], or `None` if no joint has that path.
+    ///
+    /// Joint paths may be ambiguous, as sibling joints may share a name, or a joint
+    /// name may contain a `/`. At each level of the path, the first matching child
+    /// is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Zrotation Xrotation Yrotation
+    ///         JOINT Spine
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             JOINT LeftArm
+    ///             {
+    ///                 OFFSET 5.0 0.0 0.0
+    ///                 CHANNELS 3 Zrotation Xrotation Yrotation
+    ///                 End Site
+    ///                 {
+    ///                     OFFSET 5.0 0.0 0.0
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.joint_path(2), "Hips/Spine/LeftArm");
+    /// assert_eq!(bvh.find_by_path(b"Hips/Spine/LeftArm"), Some(2));
+    /// assert_eq!(bvh.find_by_path(b"Hips/LeftArm"), None);
+    /// ```
+    ///
+    /// [`Bvh::joint_path`]: struct.Bvh.html#method.joint_path
+    pub fn find_by_path(&self, path: &[u8]) -> Option<usize> {
+        let mut components = path.split_str("/");
+        let root_name = components.next()?;
+        let mut current = self
+            .joints
+            .iter()
+            .position(|joint| joint.is_root() && joint.name() == root_name)?;
+
+        for name in components {
+            current = self
+                .joints
+                .iter()
+                .enumerate()
+                .skip(current + 1)
+                .find(|(_, joint)| joint.parent_index() == Some(current) && joint.name() == name)
+                .map(|(i, _)| i)?;
+        }
+
+        Some(current)
+    }
+
+    /// Returns a `Frames` iterator over the frames of the bvh.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// # fn use_bvh(bvh: Bvh) {
+    /// let bvh = // ...
+    /// # bvh;
+    /// for frame in bvh.frames() {
+    ///     // use frame...
+    ///     # let _ = frame;
+    /// }
+    /// # } // fn use_bvh()
+    /// ```
+    #[inline]
+    pub fn frames(&self) -> Frames<'_> {
+        Frames {
+            chunks: NonZeroUsize::new(self.num_channels).map(|_| {
+                self.motion_values
+                    .as_slice()
+                    .chunks_exact(self.num_channels)
+            }),
+        }
+    }
+
+    /// Returns an iterator over the motion values of each frame of the bvh, as
+    /// slices of the underlying motion data. Each slice has one value for each
+    /// channel.
+    ///
+    /// This is a simpler alternative to [`Bvh::frames`] when the channel values are
+    /// only needed as plain slices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// # fn use_bvh(bvh: Bvh) {
+    /// let bvh = // ...
+    /// # bvh;
+    /// for row in bvh.frame_rows() {
+    ///     assert_eq!(row.len(), bvh.num_channels());
+    /// }
+    /// # } // fn use_bvh()
+    /// ```
+    ///
+    /// [`Bvh::frames`]: struct.Bvh.html#method.frames
+    #[inline]
+    pub fn frame_rows(&self) -> impl Iterator<Item = &[f32]> + '_ {
+        NonZeroUsize::new(self.num_channels)
+            .into_iter()
+            .flat_map(move |num_channels| self.motion_values.chunks_exact(num_channels.get()))
+    }
+
+    /// Returns a mutable iterator over the motion values of each frame of the bvh, as
+    /// slices of the underlying motion data, such as for in-place bulk edits. Each
+    /// slice has one value for each channel.
+    ///
+    /// This is the mutable equivalent of [`Bvh::frame_rows`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     1.0
+    ///     -5.0
+    /// };
+    ///
+    /// for row in bvh.frame_rows_mut() {
+    ///     for value in row {
+    ///         *value = value.max(0.0);
+    ///     }
+    /// }
+    /// assert_eq!(bvh.frame_rows().collect::<Vec<_>>(), [[1.0], [0.0]]);
+    /// ```
+    ///
+    /// [`Bvh::frame_rows`]: struct.Bvh.html#method.frame_rows
+    #[inline]
+    pub fn frame_rows_mut(&mut self) -> impl Iterator<Item = &mut [f32]> + '_ {
+        let motion_values = &mut self.motion_values;
+        NonZeroUsize::new(self.num_channels)
+            .map(move |num_channels| motion_values.chunks_exact_mut(num_channels.get()))
+            .into_iter()
+            .flatten()
+    }
+
+    /// Returns the motion values of the frame at `index` as a slice of the
+    /// underlying motion data, with one value for each channel, or `None` if there
+    /// is no frame at `index`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     1.0
+    ///     -5.0
+    /// };
+    ///
+    /// assert_eq!(bvh.frame_values(1), Some(&[-5.0][..]));
+    /// assert_eq!(bvh.frame_values(2), None);
+    /// ```
+    #[inline]
+    pub fn frame_values(&self, index: usize) -> Option<&[f32]> {
+        if index >= self.num_frames() {
+            return None;
+        }
+        let start = index * self.num_channels;
+        self.motion_values.get(start..start + self.num_channels)
+    }
+
+    /// Returns the motion values of the frame at `index` as a mutable slice of the
+    /// underlying motion data, or `None` if there is no frame at `index`.
+    ///
+    /// This is the mutable equivalent of [`Bvh::frame_values`].
+    ///
+    /// [`Bvh::frame_values`]: struct.Bvh.html#method.frame_values
+    #[inline]
+    pub fn frame_values_mut(&mut self, index: usize) -> Option<&mut [f32]> {
+        if index >= self.num_frames() {
+            return None;
+        }
+        let start = index * self.num_channels;
+        self.motion_values.get_mut(start..start + self.num_channels)
+    }
+
+    /// Returns a mutable iterator over the frames of the bvh.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// # fn use_bvh(bvh: Bvh) {
+    /// let mut bvh = // ...
+    /// # bvh;
+    /// for frame in bvh.frames_mut() {
+    ///     // use frame...
+    ///     # let _ = frame;
+    /// }
+    /// # } // fn use_bvh()
+    /// ```
+    #[inline]
+    pub fn frames_mut(&mut self) -> FramesMut<'_> {
+        FramesMut {
+            chunks: NonZeroUsize::new(self.num_channels).map(move |_| {
+                self.motion_values
+                    .as_mut_slice()
+                    .chunks_exact_mut(self.num_channels)
+            }),
+        }
+    }
+
+    /// Call `f` with the index and the motion values of every frame of the `Bvh`, so
+    /// that the values can be changed in place.
+    ///
+    /// The motion values of a frame are in the same order as the channels of the
+    /// `Bvh`, so a value can be looked up using [`Channel::motion_index`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    ///     0.0 0.0 0.0
+    /// };
+    ///
+    /// bvh.map_frames(|index, frame| frame[1] = index as f32);
+    /// assert_eq!(bvh.frames().nth(1).unwrap().as_slice(), &[0.0, 1.0, 0.0]);
+    /// ```
+    ///
+    /// [`Channel::motion_index`]: struct.Channel.html#method.motion_index
+    pub fn map_frames<F: FnMut(usize, &mut [f32])>(&mut self, mut f: F) {
+        if self.num_channels == 0 {
+            return;
+        }
+        for (index, frame) in self
+            .motion_values
+            .chunks_exact_mut(self.num_channels)
+            .enumerate()
+        {
+            f(index, frame);
+        }
+    }
+
+    /// Removes all frame data from the `Bvh`, returning the previous frames.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     // hierarchy omitted...
+    ///     # ROOT Hips
+    ///     # {
+    ///     #     OFFSET 0.0 0.0 0.0
+    ///     #     CHANNELS 3 Xposition Yposition Zposition
+    ///     #     End Site
+    ///     #     {
+    ///     #         OFFSET 0.0 0.0 0.0
+    ///     #     }
+    ///     # }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    /// };
+    ///
+    /// let frames = bvh.extract_frames();
+    /// assert_eq!(bvh.num_frames(), 0);
+    /// assert_eq!(bvh.frames().len(), 0);
+    /// assert_eq!(frames, &[0.0, 0.0, 0.0]);
+    /// ```
+    #[inline]
+    pub fn extract_frames(&mut self) -> Vec<f32> {
         mem::take(&mut self.motion_values)
     }
 
-    /// Get the number of frames in the `Bvh`.
+    /// Get the number of frames in the `Bvh`. This is the same as
+    /// `frames().len()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT LeftLeg
+    ///         {
+    ///             OFFSET 1.0 -1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 -1.0 0.0
+    ///             }
+    ///         }
+    ///         JOINT RightLeg
+    ///         {
+    ///             OFFSET -1.0 -1.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 -1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    ///     0.0 1.0 0.0
+    /// };
+    ///
+    /// assert_eq!(bvh.num_frames(), 2);
+    /// assert_eq!(bvh.num_channels(), 3);
+    /// assert_eq!(bvh.joint_count(), 3);
+    /// assert_eq!(bvh.end_site_count(), 2);
+    /// assert!(!bvh.is_empty());
+    /// ```
     #[inline]
-    #[deprecated(note = "Please use `frames().len()` instead,")]
     pub fn num_frames(&self) -> usize {
         self.frames().len()
     }
 
-    /// Get the number of channels in the `Bvh`.
+    /// Returns `true` if the `Bvh` has no frames, even if it has a skeleton.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.num_frames() == 0
+    }
+
+    /// Get the number of joints in the `Bvh`. End sites are not joints, and are not
+    /// counted; see [`Bvh::end_site_count`].
+    ///
+    /// [`Bvh::end_site_count`]: struct.Bvh.html#method.end_site_count
+    #[inline]
+    pub fn joint_count(&self) -> usize {
+        self.joints.len()
+    }
+
+    /// Get the number of end sites in the `Bvh`.
+    #[inline]
+    pub fn end_site_count(&self) -> usize {
+        self.joints
+            .iter()
+            .filter(|joint| joint.end_site().is_some())
+            .count()
+    }
+
+    /// Returns an iterator over every channel in the `Bvh`, in the order of the
+    /// columns of the motion values, such as to build column headers.
+    ///
+    /// Each item is the index of the joint which the channel belongs to, the index
+    /// of the channel's column in each frame, and the channel itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, ChannelType};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 1 Zrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let columns = bvh
+    ///     .channels_iter()
+    ///     .map(|(joint, column, channel)| (joint, column, channel.channel_type()))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     columns,
+    ///     [(0, 0, ChannelType::PositionX), (1, 1, ChannelType::RotationZ)]
+    /// );
+    /// ```
+    pub fn channels_iter(&self) -> impl Iterator<Item = (usize, usize, Channel)> {
+        let mut channels = self
+            .joints
+            .iter()
+            .enumerate()
+            .flat_map(|(joint_index, joint)| {
+                joint
+                    .channels()
+                    .iter()
+                    .map(move |&channel| (joint_index, channel.motion_index(), channel))
+            })
+            .collect::<Vec<_>>();
+        channels.sort_by_key(|&(_, column, _)| column);
+        channels.into_iter()
+    }
+
+    /// Returns the column in each frame of the channel of type `ty` of the joint at
+    /// index `joint`, or `None` if the joint does not exist or does not have such a
+    /// channel. If the joint has more than one channel of type `ty`, then the column
+    /// of the first is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, ChannelType};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 1 Zrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// assert_eq!(bvh.channel_index(1, ChannelType::RotationZ), Some(1));
+    /// assert_eq!(bvh.channel_index(1, ChannelType::RotationX), None);
+    /// assert_eq!(bvh.channel_index(2, ChannelType::RotationZ), None);
+    /// ```
+    pub fn channel_index(&self, joint: usize, ty: ChannelType) -> Option<usize> {
+        self.joints
+            .get(joint)?
+            .channels()
+            .iter()
+            .find(|channel| channel.channel_type() == ty)
+            .map(Channel::motion_index)
+    }
+
+    /// Returns an iterator over every channel of the `Bvh` paired with its value in
+    /// the frame at index `frame`, in the order of the motion columns, such as to
+    /// display a frame in a UI.
+    ///
+    /// If there is no frame at index `frame`, then the iterator is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, ChannelType};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Base
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Tip
+    ///         {
+    ///             OFFSET 0.0 1.0 0.0
+    ///             CHANNELS 1 Zrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 1.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     2.0 45.0
+    /// };
+    ///
+    /// let values = bvh
+    ///     .frame_channels(0)
+    ///     .map(|(channel, value)| (channel.channel_type(), value))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     values,
+    ///     [(ChannelType::PositionX, 2.0), (ChannelType::RotationZ, 45.0)]
+    /// );
+    /// assert_eq!(bvh.frame_channels(1).count(), 0);
+    /// ```
+    pub fn frame_channels(&self, frame: usize) -> impl Iterator<Item = (Channel, f32)> + '_ {
+        let values = self.frame_values(frame).unwrap_or(&[]);
+        self.channels_iter()
+            .filter_map(move |(_, column, channel)| {
+                values.get(column).map(|&value| (channel, value))
+            })
+    }
+
+    /// Get the number of channels in the `Bvh`, which is the number of values in
+    /// each frame.
+    ///
+    /// The count is stored when the `Bvh` is loaded and kept up to date by every
+    /// edit, so this does not need to sum the channels of each joint.
     #[inline]
     pub const fn num_channels(&self) -> usize {
         self.num_channels
     }
 
+    /// Get the total number of channels of every joint in the `Bvh`. This is the
+    /// same as [`Bvh::num_channels`].
+    ///
+    /// [`Bvh::num_channels`]: struct.Bvh.html#method.num_channels
+    #[inline]
+    pub const fn total_channels(&self) -> usize {
+        self.num_channels
+    }
+
     /// Get the duration each frame should play for in the `Bvh`.
     ///
     /// # Example
@@ -669,6 +2013,10 @@ impl Bvh {
 
     /// Set the duration each frame should play for in the `Bvh` to `new_frame_time`.
     ///
+    /// The frames are not changed, so the animation plays faster or slower. Use
+    /// [`Bvh::set_frame_time_keep_duration`] to resample the frames so that the
+    /// animation keeps its speed.
+    ///
     /// # Example
     ///
     /// ```
@@ -685,9 +2033,316 @@ impl Bvh {
     /// assert_eq!(*bvh.frame_time(), new_frame_time);
     /// # } // fn main()
     /// ```
+    ///
+    /// [`Bvh::set_frame_time_keep_duration`]: struct.Bvh.html#method.set_frame_time_keep_duration
     #[inline]
     pub fn set_frame_time(&mut self, new_frame_time: Duration) {
         self.frame_time = new_frame_time;
+        self.frame_time_inferred = false;
+    }
+
+    /// Returns `true` if the `Frame Time` line was missing when the `Bvh` was
+    /// parsed in lenient mode, so that its frame time is the
+    /// [`ParseOptions::fallback_frame_time`] rather than a value read from the
+    /// file. Setting the frame time with [`Bvh::set_frame_time`] clears this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::read::ParseOptions;
+    /// # use std::time::Duration;
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     0.0
+    /// "#;
+    ///
+    /// let mut bvh = ParseOptions::new()
+    ///     .with_lenient(true)
+    ///     .read_bytes(&bvh_string[..])?;
+    /// assert!(bvh.frame_time_was_inferred());
+    /// assert_eq!(*bvh.frame_time(), ParseOptions::new().fallback_frame_time);
+    ///
+    /// bvh.set_frame_time(Duration::from_millis(10));
+    /// assert!(!bvh.frame_time_was_inferred());
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    ///
+    /// [`ParseOptions::fallback_frame_time`]: read/struct.ParseOptions.html#structfield.fallback_frame_time
+    /// [`Bvh::set_frame_time`]: struct.Bvh.html#method.set_frame_time
+    #[inline]
+    pub const fn frame_time_was_inferred(&self) -> bool {
+        self.frame_time_inferred
+    }
+
+    /// Returns the `Bvh` with its frame time set to `frame_time`, for chaining with
+    /// other `with_*` methods. See [`Bvh::set_frame_time`].
+    ///
+    /// [`Bvh::set_frame_time`]: struct.Bvh.html#method.set_frame_time
+    #[inline]
+    pub fn with_frame_time(mut self, frame_time: Duration) -> Self {
+        self.set_frame_time(frame_time);
+        self
+    }
+
+    /// Returns the `Bvh` with all of its frames replaced by `frames`, which must
+    /// each have one value for every channel of the `Bvh`.
+    ///
+    /// If the `Bvh` has no channels, then it cannot store any frames, so the frames
+    /// are dropped and the result has no frames, whatever the length of `frames`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any frame does not have one value for every channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// # use std::time::Duration;
+    /// let skeleton = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let bvh = skeleton
+    ///     .clone()
+    ///     .with_frame_time(Duration::from_millis(10))
+    ///     .with_frames(vec![vec![0.0, 1.0, 2.0], vec![3.0, 4.0, 5.0]])?;
+    /// assert_eq!(bvh.num_frames(), 2);
+    /// assert_eq!(*bvh.frame_time(), Duration::from_millis(10));
+    ///
+    /// assert!(skeleton.with_frames(vec![vec![0.0]]).is_err());
+    /// # Result::<(), bvh_anim::errors::FrameInsertError>::Ok(())
+    /// ```
+    pub fn with_frames(mut self, frames: Vec<Vec<f32>>) -> Result<Self, FrameInsertError> {
+        if let Some(frame) = frames.iter().find(|frame| frame.len() != self.num_channels) {
+            return Err(FrameInsertError::incorrect_len(
+                self.num_channels,
+                frame.len(),
+            ));
+        }
+
+        self.motion_values = frames.concat();
+        Ok(self)
+    }
+
+    /// Returns the index of the frame nearest to the time `t` in seconds from the
+    /// start of the `Bvh`, where frame `i` starts at `i` times the frame time.
+    ///
+    /// Times before the first frame or after the last frame are clamped to them. If
+    /// the `Bvh` has no frames, if `t` is `NaN`, or if the frame time is zero, then
+    /// `0` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     1.0
+    ///     2.0
+    /// };
+    ///
+    /// assert_eq!(bvh.frame_index_at_time(0.2), 0);
+    /// assert_eq!(bvh.frame_index_at_time(0.3), 1);
+    /// assert_eq!(bvh.frame_index_at_time(-1.0), 0);
+    /// assert_eq!(bvh.frame_index_at_time(10.0), 2);
+    /// ```
+    pub fn frame_index_at_time(&self, t: f64) -> usize {
+        let last = match self.num_frames().checked_sub(1) {
+            Some(last) => last,
+            None => return 0,
+        };
+        let frame_time = self.frame_time.as_secs_f64();
+        if frame_time <= 0.0 {
+            return 0;
+        }
+
+        let index = (t / frame_time).round();
+        if index >= last as f64 {
+            last
+        } else if index > 0.0 {
+            index as usize
+        } else {
+            0
+        }
+    }
+
+    /// Returns the values of the pose at the normalized `phase` of the animation,
+    /// such as to drive a looping clip from a blend tree.
+    ///
+    /// A `phase` of `0.0` is the first frame and a `phase` of `1.0` is the last
+    /// frame; phases in between are mapped linearly onto the frames, and every
+    /// channel is interpolated linearly between the two nearest frames. Rotation
+    /// channels are interpolated as angles, so [`Bvh::regularize_euler`] should be
+    /// used first if they wrap around. `phase` is clamped to `0.0..=1.0`, and `NaN`
+    /// is treated as `0.0`.
+    ///
+    /// Returns `None` if the `Bvh` has no frames.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     1.0
+    ///     4.0
+    /// };
+    ///
+    /// assert_eq!(bvh.sample_at_phase(0.25), Some(vec![0.5]));
+    /// assert_eq!(bvh.sample_at_phase(0.75), Some(vec![2.5]));
+    /// assert_eq!(bvh.sample_at_phase(2.0), Some(vec![4.0]));
+    /// ```
+    ///
+    /// [`Bvh::regularize_euler`]: struct.Bvh.html#method.regularize_euler
+    pub fn sample_at_phase(&self, phase: f32) -> Option<Vec<f32>> {
+        let last = self.num_frames().checked_sub(1)?;
+        let phase = if phase.is_nan() {
+            0.0
+        } else {
+            phase.clamp(0.0, 1.0)
+        };
+
+        let position = f64::from(phase) * last as f64;
+        let before = (position.floor() as usize).min(last);
+        let after = (before + 1).min(last);
+        let t = (position - before as f64) as f32;
+
+        let before = self.frame_values(before)?;
+        let after = self.frame_values(after)?;
+        Some(
+            before
+                .iter()
+                .zip(after)
+                .map(|(a, b)| a + (b - a) * t)
+                .collect(),
+        )
+    }
+
+    /// Returns the values of the pose at `t` between the frames at indices `a` and
+    /// `b`, interpolating every channel linearly, such as to inspect the Euler
+    /// curves of a clip while debugging.
+    ///
+    /// Rotation channels are interpolated along the shortest path between their
+    /// two angles, so that interpolating from `350.0` to `10.0` degrees passes
+    /// through `0.0` rather than `180.0`. The angles are still interpolated
+    /// separately, so unlike a spherical interpolation of the whole rotation, the
+    /// path between two rotations need not be the shortest. A `t` of `0.0` returns
+    /// the values of frame `a`, and a `t` of `1.0` returns the values of frame `b`,
+    /// except that rotation angles may differ from it by a multiple of `360.0`
+    /// degrees.
+    ///
+    /// Returns `None` if either `a` or `b` is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 2 Xposition Yrotation
+    ///         End Site
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.5
+    ///     0.0 350.0
+    ///     2.0 10.0
+    /// };
+    ///
+    /// assert_eq!(bvh.interpolate_euler(0, 1, 0.5), Some(vec![1.0, 360.0]));
+    /// assert_eq!(bvh.interpolate_euler(0, 2, 0.5), None);
+    /// ```
+    pub fn interpolate_euler(&self, a: usize, b: usize, t: f32) -> Option<Vec<f32>> {
+        let mut values = self.frame_values(a)?.to_vec();
+        let to = self.frame_values(b)?;
+
+        for channel in self.joints.iter().flat_map(|joint| joint.channels()) {
+            let index = channel.motion_index();
+            let from = values[index];
+            let to = if channel.channel_type().is_rotation() {
+                edit::closest_angle(to[index], from)
+            } else {
+                to[index]
+            };
+            values[index] = from + (to - from) * t;
+        }
+        Some(values)
     }
 
     /// Create a new `FrameCursor` for inserting and removing frames.
@@ -696,11 +2351,163 @@ impl Bvh {
         From::from(self)
     }
 
+    /// Remove the frame at `index` from the `Bvh`. The following frames are moved
+    /// down by one, but annotations are not moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `index` is out of range, in which case the `Bvh` is not
+    /// changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.033333333
+    ///     0.0
+    ///     1.0
+    ///     2.0
+    /// };
+    ///
+    /// bvh.remove_frame(1)?;
+    /// assert_eq!(bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>(), [0.0, 2.0]);
+    /// assert!(bvh.remove_frame(2).is_err());
+    /// # Result::<(), bvh_anim::errors::FrameRemoveError>::Ok(())
+    /// ```
+    pub fn remove_frame(&mut self, index: usize) -> Result<(), FrameRemoveError> {
+        if index >= self.num_frames() {
+            return Err(FrameRemoveError::new(index));
+        }
+
+        let start = index * self.num_channels;
+        self.motion_values.drain(start..start + self.num_channels);
+        Ok(())
+    }
+
+    /// Insert a frame with the given `values` at `index` in the `Bvh`, so that it
+    /// becomes the frame at `index`. An `index` equal to the number of frames
+    /// appends the frame. The following frames are moved up by one, but annotations
+    /// are not moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `index` is greater than the number of frames, or if
+    /// `values` does not have one value for each channel. In that case, the `Bvh`
+    /// is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0
+    ///     2.0
+    /// };
+    ///
+    /// bvh.insert_frame(1, &[1.0])?;
+    /// assert_eq!(bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>(), [0.0, 1.0, 2.0]);
+    /// assert!(bvh.insert_frame(4, &[3.0]).is_err());
+    /// assert!(bvh.insert_frame(3, &[3.0, 4.0]).is_err());
+    /// # Result::<(), bvh_anim::errors::FrameInsertError>::Ok(())
+    /// ```
+    pub fn insert_frame(&mut self, index: usize, values: &[f32]) -> Result<(), FrameInsertError> {
+        let num_frames = self.num_frames();
+        if index > num_frames {
+            return Err(FrameInsertError::out_of_range(index, num_frames));
+        }
+        if values.len() != self.num_channels {
+            return Err(FrameInsertError::incorrect_len(
+                self.num_channels,
+                values.len(),
+            ));
+        }
+
+        let start = index * self.num_channels;
+        self.motion_values
+            .splice(start..start, values.iter().copied());
+        Ok(())
+    }
+
     /// Add a new frame to the `frames` of the `Bvh`.
     #[inline]
     pub fn push_frame(&mut self, frame: &[f32]) {
         self.motion_values.extend(frame);
     }
+
+    /// Remove every frame from the `Bvh`, keeping its joints and frame time, such
+    /// as to reuse its skeleton for new motion. Annotations are not removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0
+    ///     1.0
+    /// };
+    ///
+    /// bvh.clear_motion();
+    /// assert_eq!(bvh.num_frames(), 0);
+    /// assert_eq!(bvh.joints().count(), 2);
+    /// ```
+    #[inline]
+    pub fn clear_motion(&mut self) {
+        self.motion_values.clear();
+    }
 }
 
 impl Default for Bvh {
@@ -718,6 +2525,45 @@ impl FromStr for Bvh {
     }
 }
 
+/// Parses a `str` as a `Bvh`, like [`Bvh::from_str`][`Bvh::from_str`].
+///
+/// # Examples
+///
+/// ```
+/// # use bvh_anim::Bvh;
+/// use std::convert::TryFrom;
+///
+/// let bvh_string = "
+///     HIERARCHY
+///     ROOT Hips
+///     {
+///         OFFSET 0.0 0.0 0.0
+///         CHANNELS 3 Xposition Yposition Zposition
+///         JOINT Chest
+///         {
+///             OFFSET 0.0 5.0 0.0
+///             CHANNELS 0
+///             End Site
+///             {
+///                 OFFSET 0.0 5.0 0.0
+///             }
+///         }
+///     }
+///     MOTION
+///     Frames: 1
+///     Frame Time: 0.033333333
+///     0.0 1.0 2.0
+/// ";
+///
+/// let from_str = Bvh::try_from(bvh_string)?;
+/// let from_bytes = Bvh::try_from(bvh_string.as_bytes())?;
+/// assert_eq!(from_str, from_bytes);
+///
+/// assert!(Bvh::try_from("HIERARCHY").is_err());
+/// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+/// ```
+///
+/// [`Bvh::from_str`]: struct.Bvh.html#method.from_str
 impl TryFrom<&'_ str> for Bvh {
     type Error = LoadError;
     #[inline]
@@ -734,6 +2580,10 @@ impl TryFrom<&'_ BStr> for Bvh {
     }
 }
 
+/// Parses a sequence of bytes as a `Bvh`, like [`Bvh::from_bytes`][`Bvh::from_bytes`].
+/// See the `TryFrom<&str>` implementation for an example.
+///
+/// [`Bvh::from_bytes`]: struct.Bvh.html#method.from_bytes
 impl TryFrom<&'_ [u8]> for Bvh {
     type Error = LoadError;
     #[inline]
@@ -789,9 +2639,44 @@ pub enum ChannelType {
     RotationY,
     /// Can be rotated along the `z` axis.
     RotationZ,
+    /// Can be scaled along the `x` axis.
+    ///
+    /// Scale channels are not part of the standard bvh format, and are only parsed
+    /// when enabled with [`ParseOptions::allow_scale_channels`].
+    ///
+    /// [`ParseOptions::allow_scale_channels`]: read/struct.ParseOptions.html#method.allow_scale_channels
+    ScaleX,
+    /// Can be scaled along the `y` axis.
+    ///
+    /// Scale channels are not part of the standard bvh format, and are only parsed
+    /// when enabled with [`ParseOptions::allow_scale_channels`].
+    ///
+    /// [`ParseOptions::allow_scale_channels`]: read/struct.ParseOptions.html#method.allow_scale_channels
+    ScaleY,
+    /// Can be scaled along the `z` axis.
+    ///
+    /// Scale channels are not part of the standard bvh format, and are only parsed
+    /// when enabled with [`ParseOptions::allow_scale_channels`].
+    ///
+    /// [`ParseOptions::allow_scale_channels`]: read/struct.ParseOptions.html#method.allow_scale_channels
+    ScaleZ,
 }
 
 impl ChannelType {
+    /// Every `ChannelType`, in the order positions first, then rotations, then
+    /// scales, each along `x`, `y` and `z`.
+    pub const ALL: [ChannelType; 9] = [
+        ChannelType::PositionX,
+        ChannelType::PositionY,
+        ChannelType::PositionZ,
+        ChannelType::RotationX,
+        ChannelType::RotationY,
+        ChannelType::RotationZ,
+        ChannelType::ScaleX,
+        ChannelType::ScaleY,
+        ChannelType::ScaleZ,
+    ];
+
     /// Attempt to parse a bvh channel byte string into a `ChannelType`.
     /// Returns `Err` if the string cannot be parsed.
     ///
@@ -804,7 +2689,7 @@ impl ChannelType {
     ///     ChannelType::RotationX);
     ///
     /// let err = ChannelType::from_bytes("Hello").unwrap_err();
-    /// assert_eq!(err.into_inner(), "Hello");
+    /// assert_eq!(err.into_inner(), b"Hello");
     /// ```
     #[inline]
     pub fn from_bytes<B>(s: &B) -> Result<Self, ParseChannelError>
@@ -812,17 +2697,11 @@ impl ChannelType {
         B: AsRef<[u8]> + ?Sized,
     {
         let s = s.as_ref();
-        match s {
-            b"Xrotation" => Ok(ChannelType::RotationX),
-            b"Yrotation" => Ok(ChannelType::RotationY),
-            b"Zrotation" => Ok(ChannelType::RotationZ),
-
-            b"Xposition" => Ok(ChannelType::PositionX),
-            b"Yposition" => Ok(ChannelType::PositionY),
-            b"Zposition" => Ok(ChannelType::PositionZ),
-
-            _ => Err(ParseChannelError::from(s)),
-        }
+        ChannelType::ALL
+            .iter()
+            .copied()
+            .find(|ty| ty.as_str().as_bytes() == s)
+            .ok_or_else(|| ParseChannelError::from(s))
     }
 
     /// Returns `true` if this channel corresponds to a rotational
@@ -837,10 +2716,10 @@ impl ChannelType {
     /// ```
     #[inline]
     pub const fn is_rotation(&self) -> bool {
-        match *self {
-            ChannelType::RotationX | ChannelType::RotationY | ChannelType::RotationZ => true,
-            _ => false,
-        }
+        matches!(
+            *self,
+            ChannelType::RotationX | ChannelType::RotationY | ChannelType::RotationZ
+        )
     }
 
     /// Returns `true` if this channel corresponds to a positional
@@ -855,7 +2734,29 @@ impl ChannelType {
     /// ```
     #[inline]
     pub const fn is_position(&self) -> bool {
-        !self.is_rotation()
+        matches!(
+            *self,
+            ChannelType::PositionX | ChannelType::PositionY | ChannelType::PositionZ
+        )
+    }
+
+    /// Returns `true` if this channel corresponds to a scaling
+    /// transform, otherwise `false`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bvh_anim::ChannelType;
+    /// let channel_type = ChannelType::ScaleY;
+    /// assert!(channel_type.is_scale());
+    /// assert!(!channel_type.is_position());
+    /// ```
+    #[inline]
+    pub const fn is_scale(&self) -> bool {
+        matches!(
+            *self,
+            ChannelType::ScaleX | ChannelType::ScaleY | ChannelType::ScaleZ
+        )
     }
 
     /// Get the `Axis` about which this `Channel` transforms.
@@ -870,13 +2771,18 @@ impl ChannelType {
     #[inline]
     pub const fn axis(&self) -> Axis {
         match *self {
-            ChannelType::RotationX | ChannelType::PositionX => Axis::X,
-            ChannelType::RotationY | ChannelType::PositionY => Axis::Y,
-            ChannelType::RotationZ | ChannelType::PositionZ => Axis::Z,
+            ChannelType::RotationX | ChannelType::PositionX | ChannelType::ScaleX => Axis::X,
+            ChannelType::RotationY | ChannelType::PositionY | ChannelType::ScaleY => Axis::Y,
+            ChannelType::RotationZ | ChannelType::PositionZ | ChannelType::ScaleZ => Axis::Z,
         }
     }
 
-    /// Returns the string representation of the `ChannelType`.
+    /// Returns the string representation of the `ChannelType`, exactly as it
+    /// appears in a `CHANNELS` section of a bvh file.
+    ///
+    /// This is the inverse of [`ChannelType::from_bytes`].
+    ///
+    /// [`ChannelType::from_bytes`]: enum.ChannelType.html#method.from_bytes
     #[inline]
     pub const fn as_str(&self) -> &'static str {
         match *self {
@@ -887,6 +2793,10 @@ impl ChannelType {
             ChannelType::PositionX => "Xposition",
             ChannelType::PositionY => "Yposition",
             ChannelType::PositionZ => "Zposition",
+
+            ChannelType::ScaleX => "Xscale",
+            ChannelType::ScaleY => "Yscale",
+            ChannelType::ScaleZ => "Zscale",
         }
     }
 }
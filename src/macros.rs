@@ -350,13 +350,12 @@ macro_rules! bvh {
     ) => {
         {
             use std::time::Duration;
-            use $crate::parse_joints_internal;
 
             let mut builder = $crate::BvhLiteralBuilder::default();
             builder.push_root(stringify!($root_name));
 
             builder.current_depth += 1;
-            parse_joints_internal!(builder ($($joints)*));
+            $crate::parse_joints_internal!(builder ($($joints)*));
             builder.current_depth -= 1;
 
             builder.set_num_channels();
@@ -380,9 +379,7 @@ macro_rules! bvh {
         )+
     ) => {
         {
-            use $crate::bvh;
-
-            let mut new_bvh = bvh! {
+            let mut new_bvh = $crate::bvh! {
                 HIERARCHY
                 ROOT $root_name
                 {
@@ -461,25 +458,27 @@ impl BvhLiteralBuilder {
 
     pub fn push_channel(&mut self, channel: ChannelType) {
         let channel = Channel::new(channel, self.current_channel_index);
-        self.last_joint().map(|joint| match *joint {
-            JointData::Root {
-                ref mut channels, ..
-            } => {
-                channels.push(channel);
-            }
-            JointData::Child {
-                ref mut channels, ..
-            } => {
-                channels.push(channel);
+        if let Some(joint) = self.last_joint() {
+            match *joint {
+                JointData::Root {
+                    ref mut channels, ..
+                } => {
+                    channels.push(channel);
+                }
+                JointData::Child {
+                    ref mut channels, ..
+                } => {
+                    channels.push(channel);
+                }
             }
-        });
+        }
         self.current_channel_index += 1;
     }
 
     pub fn push_joint_offset(&mut self, offset: Offset, is_end_site: bool) {
-        self.last_joint().map(|joint| {
+        if let Some(joint) = self.last_joint() {
             joint.set_offset(offset, is_end_site);
-        });
+        }
     }
 
     #[inline]
@@ -533,7 +532,7 @@ mod tests {
                 channels: &[ChannelType],
                 end_site: O,
             ) {
-                assert_eq!(joint.name(), expected_name);
+                assert_eq!(joint.name(), expected_name.as_bytes());
                 assert_eq!(*joint.offset(), expected_offset);
                 for (chan, expected_chan) in joint
                     .channels()
@@ -543,7 +542,7 @@ mod tests {
                 {
                     assert_eq!(chan, *expected_chan);
                 }
-                let end_site = end_site.into().map(Into::into);
+                let end_site = end_site.into();
                 assert_eq!(joint.end_site(), end_site.as_ref());
             }
 
@@ -0,0 +1,441 @@
+//! Small linear algebra helpers used to evaluate the transforms of a `Bvh` skeleton.
+//!
+//! This crate deliberately does not depend on a linear algebra library, so transforms
+//! are represented using plain arrays:
+//!
+//! * A [`Mat`][`Mat`] is a 4x4 affine matrix stored in column-major order, so that
+//!   `m[column][row]` addresses a single element, and the translation part of the
+//!   matrix is stored in `m[3]`. Matrices operate on column vectors.
+//!
+//! * A [`Quat`][`Quat`] is a rotation quaternion stored as `[x, y, z, w]`.
+//!
+//! Rotation values in a `Bvh` are in degrees, and rotations follow the right-hand rule.
+//!
+//! [`Mat`]: type.Mat.html
+//! [`Quat`]: type.Quat.html
+
+use crate::{joint::Offset, Axis};
+
+/// A 4x4 column-major affine transformation matrix.
+pub type Mat = [[f32; 4]; 4];
+
+/// A unit quaternion representing a rotation, stored as `[x, y, z, w]`.
+pub type Quat = [f32; 4];
+
+/// The identity matrix.
+pub const IDENTITY: Mat = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Multiply the matrix `a` by the matrix `b`, returning `a * b`.
+#[inline]
+pub fn mat_mul(a: &Mat, b: &Mat) -> Mat {
+    let mut out = [[0.0; 4]; 4];
+    for (col, out_col) in out.iter_mut().enumerate() {
+        for (row, out_elem) in out_col.iter_mut().enumerate() {
+            *out_elem = (0..4).map(|k| a[k][row] * b[col][k]).sum();
+        }
+    }
+    out
+}
+
+/// Create a matrix which translates by `v`.
+#[inline]
+pub fn mat_translation(v: Offset) -> Mat {
+    let mut out = IDENTITY;
+    out[3] = [v[0], v[1], v[2], 1.0];
+    out
+}
+
+/// Create a matrix which scales by `v[i]` along each axis `i`.
+#[inline]
+pub fn mat_scale(v: Offset) -> Mat {
+    let mut out = IDENTITY;
+    for (i, &scale) in v.iter().enumerate() {
+        out[i][i] = scale;
+    }
+    out
+}
+
+/// Create a matrix which rotates by `degrees` about `axis`.
+#[inline]
+pub fn mat_rotation(axis: Axis, degrees: f32) -> Mat {
+    let (s, c) = degrees.to_radians().sin_cos();
+    match axis {
+        Axis::X => [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, c, s, 0.0],
+            [0.0, -s, c, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+        Axis::Y => [
+            [c, 0.0, -s, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [s, 0.0, c, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+        Axis::Z => [
+            [c, s, 0.0, 0.0],
+            [-s, c, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    }
+}
+
+/// Returns the translation part of the matrix `m`.
+#[inline]
+pub fn mat_get_translation(m: &Mat) -> Offset {
+    [m[3][0], m[3][1], m[3][2]]
+}
+
+/// Transform the point `p` by the matrix `m`.
+#[inline]
+pub fn transform_point(m: &Mat, p: Offset) -> Offset {
+    let mut out = [0.0; 3];
+    for (row, out_elem) in out.iter_mut().enumerate() {
+        *out_elem = m[0][row] * p[0] + m[1][row] * p[1] + m[2][row] * p[2] + m[3][row];
+    }
+    out
+}
+
+/// Returns the inverse of the affine matrix `m`, or `None` if it is not
+/// invertible, such as when it scales by `0` along an axis.
+pub fn mat_affine_inverse(m: &Mat) -> Option<Mat> {
+    let cross = |u: [f32; 4], v: [f32; 4]| {
+        [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ]
+    };
+    // The rows of the inverse of the linear part are the cross products of its
+    // columns, divided by its determinant.
+    let rows = [cross(m[1], m[2]), cross(m[2], m[0]), cross(m[0], m[1])];
+    let det = (0..3).map(|k| m[0][k] * rows[0][k]).sum::<f32>();
+    if det == 0.0 || !det.is_finite() {
+        return None;
+    }
+
+    let mut out = IDENTITY;
+    for (col, out_col) in out.iter_mut().take(3).enumerate() {
+        for (out_elem, row) in out_col.iter_mut().zip(rows.iter()) {
+            *out_elem = row[col] / det;
+        }
+    }
+    let translation = mat_get_translation(m);
+    let inverse_translation = transform_point(&out, translation);
+    out[3] = [
+        -inverse_translation[0],
+        -inverse_translation[1],
+        -inverse_translation[2],
+        1.0,
+    ];
+    Some(out)
+}
+
+/// Create a rotation matrix from Euler angles in degrees, where `angles[i]` is the
+/// rotation about `order[i]`, and the rotations are applied in the same order as
+/// `CHANNELS` are applied in a `Bvh` file.
+#[inline]
+pub fn euler_to_mat(order: [Axis; 3], angles: [f32; 3]) -> Mat {
+    order
+        .iter()
+        .zip(angles.iter())
+        .fold(IDENTITY, |m, (&axis, &angle)| {
+            mat_mul(&m, &mat_rotation(axis, angle))
+        })
+}
+
+/// Decompose the rotation part of `m` into Euler angles in degrees, such that
+/// `euler_to_mat(order, mat_to_euler(m, order))` is equal to the rotation part of `m`.
+///
+/// `order` must contain each axis exactly once. The rotation part of `m` is assumed
+/// to be orthonormal.
+///
+/// The middle angle is always in the range `[-90, 90]`. When the middle angle is at
+/// either end of this range, the decomposition is not unique, and the last angle is
+/// set to `0`.
+pub fn mat_to_euler(m: &Mat, order: [Axis; 3]) -> [f32; 3] {
+    let [i, j, k] = [order[0] as usize, order[1] as usize, order[2] as usize];
+    debug_assert!(
+        i != j && j != k && i != k,
+        "rotation order must be distinct"
+    );
+
+    // Even permutations of `XYZ` have a positive parity.
+    let parity = if (j + 3 - i) % 3 == 1 { 1.0 } else { -1.0 };
+    // Access the matrix by `r(row, col)`.
+    let r = |row: usize, col: usize| m[col][row];
+
+    let sin_b = (parity * r(i, k)).clamp(-1.0, 1.0);
+    let b = sin_b.asin();
+    let (a, c) = if sin_b.abs() < 1.0 - 1.0e-6 {
+        let a = (-parity * r(j, k)).atan2(r(k, k));
+        let c = (-parity * r(i, j)).atan2(r(i, i));
+        (a, c)
+    } else {
+        let a = (parity * r(k, j)).atan2(r(j, j));
+        (a, 0.0)
+    };
+
+    [a.to_degrees(), b.to_degrees(), c.to_degrees()]
+}
+
+/// Convert the rotation part of `m` into a unit quaternion. The rotation part of
+/// `m` is assumed to be orthonormal.
+pub fn mat_to_quat(m: &Mat) -> Quat {
+    let r = |row: usize, col: usize| m[col][row];
+    let trace = r(0, 0) + r(1, 1) + r(2, 2);
+    let q = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [
+            (r(2, 1) - r(1, 2)) / s,
+            (r(0, 2) - r(2, 0)) / s,
+            (r(1, 0) - r(0, 1)) / s,
+            0.25 * s,
+        ]
+    } else if r(0, 0) > r(1, 1) && r(0, 0) > r(2, 2) {
+        let s = (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).sqrt() * 2.0;
+        [
+            0.25 * s,
+            (r(0, 1) + r(1, 0)) / s,
+            (r(0, 2) + r(2, 0)) / s,
+            (r(2, 1) - r(1, 2)) / s,
+        ]
+    } else if r(1, 1) > r(2, 2) {
+        let s = (1.0 + r(1, 1) - r(0, 0) - r(2, 2)).sqrt() * 2.0;
+        [
+            (r(0, 1) + r(1, 0)) / s,
+            0.25 * s,
+            (r(1, 2) + r(2, 1)) / s,
+            (r(0, 2) - r(2, 0)) / s,
+        ]
+    } else {
+        let s = (1.0 + r(2, 2) - r(0, 0) - r(1, 1)).sqrt() * 2.0;
+        [
+            (r(0, 2) + r(2, 0)) / s,
+            (r(1, 2) + r(2, 1)) / s,
+            0.25 * s,
+            (r(1, 0) - r(0, 1)) / s,
+        ]
+    };
+    quat_normalize(q)
+}
+
+/// Convert the unit quaternion `q` into a rotation matrix.
+pub fn quat_to_mat(q: Quat) -> Mat {
+    let [x, y, z, w] = q;
+    [
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y + z * w),
+            2.0 * (x * z - y * w),
+            0.0,
+        ],
+        [
+            2.0 * (x * y - z * w),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z + x * w),
+            0.0,
+        ],
+        [
+            2.0 * (x * z + y * w),
+            2.0 * (y * z - x * w),
+            1.0 - 2.0 * (x * x + y * y),
+            0.0,
+        ],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+/// Multiply the quaternion `a` by the quaternion `b`, returning `a * b`, which is
+/// the rotation `b` followed by the rotation `a`, like [`mat_mul`][`mat_mul`].
+///
+/// [`mat_mul`]: fn.mat_mul.html
+#[inline]
+pub fn quat_mul(a: Quat, b: Quat) -> Quat {
+    [
+        a[3] * b[0] + a[0] * b[3] + a[1] * b[2] - a[2] * b[1],
+        a[3] * b[1] - a[0] * b[2] + a[1] * b[3] + a[2] * b[0],
+        a[3] * b[2] + a[0] * b[1] - a[1] * b[0] + a[2] * b[3],
+        a[3] * b[3] - a[0] * b[0] - a[1] * b[1] - a[2] * b[2],
+    ]
+}
+
+/// Returns the dot product of the quaternions `a` and `b`.
+#[inline]
+pub fn quat_dot(a: Quat, b: Quat) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]
+}
+
+/// Returns `q` scaled to unit length, or the identity rotation if `q` has zero
+/// length.
+#[inline]
+pub fn quat_normalize(q: Quat) -> Quat {
+    let length = quat_dot(q, q).sqrt();
+    if length > 0.0 {
+        [q[0] / length, q[1] / length, q[2] / length, q[3] / length]
+    } else {
+        [0.0, 0.0, 0.0, 1.0]
+    }
+}
+
+/// Spherically interpolate between the unit quaternions `a` and `b`, where `t = 0`
+/// returns `a` and `t = 1` returns `b`. The shortest path between the two
+/// rotations is always taken.
+pub fn quat_slerp(a: Quat, b: Quat, t: f32) -> Quat {
+    let mut dot = quat_dot(a, b);
+    let b = if dot < 0.0 {
+        dot = -dot;
+        [-b[0], -b[1], -b[2], -b[3]]
+    } else {
+        b
+    };
+
+    let (wa, wb) = if dot > 1.0 - 1.0e-6 {
+        (1.0 - t, t)
+    } else {
+        let theta = dot.acos();
+        let sin_theta = theta.sin();
+        (
+            ((1.0 - t) * theta).sin() / sin_theta,
+            (t * theta).sin() / sin_theta,
+        )
+    };
+    quat_normalize([
+        wa * a[0] + wb * b[0],
+        wa * a[1] + wb * b[1],
+        wa * a[2] + wb * b[2],
+        wa * a[3] + wb * b[3],
+    ])
+}
+
+/// Returns the angle in degrees of the rotation between the unit quaternions `a`
+/// and `b`.
+#[inline]
+pub fn quat_angle_between(a: Quat, b: Quat) -> f32 {
+    (2.0 * quat_dot(a, b).abs().min(1.0).acos()).to_degrees()
+}
+
+/// Convert the unit quaternion `q` into a unit rotation axis and an angle in
+/// degrees about it. The angle is in the range `[0, 180]`.
+///
+/// If `q` is the identity rotation, then the axis is arbitrary, and is returned as
+/// the `X` axis.
+pub fn quat_to_axis_angle(q: Quat) -> (Offset, f32) {
+    let q = quat_normalize(q);
+    let q = if q[3] < 0.0 {
+        [-q[0], -q[1], -q[2], -q[3]]
+    } else {
+        q
+    };
+
+    let sin_half = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2]).sqrt();
+    if sin_half < 1.0e-6 {
+        return ([1.0, 0.0, 0.0], 0.0);
+    }
+
+    let axis = [q[0] / sin_half, q[1] / sin_half, q[2] / sin_half];
+    let angle = 2.0 * sin_half.atan2(q[3]);
+    (axis, angle.to_degrees())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDERS: [[Axis; 3]; 6] = [
+        [Axis::X, Axis::Y, Axis::Z],
+        [Axis::X, Axis::Z, Axis::Y],
+        [Axis::Y, Axis::X, Axis::Z],
+        [Axis::Y, Axis::Z, Axis::X],
+        [Axis::Z, Axis::X, Axis::Y],
+        [Axis::Z, Axis::Y, Axis::X],
+    ];
+
+    fn assert_mat_eq(a: &Mat, b: &Mat) {
+        for (col_a, col_b) in a.iter().zip(b.iter()) {
+            for (x, y) in col_a.iter().zip(col_b.iter()) {
+                assert!((x - y).abs() < 1.0e-4, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn affine_inverse() {
+        let m = mat_mul(
+            &mat_mul(
+                &mat_translation([1.0, -2.0, 3.0]),
+                &euler_to_mat(ORDERS[3], [10.0, 20.0, 30.0]),
+            ),
+            &mat_scale([2.0, 0.5, 1.5]),
+        );
+        let inverse = mat_affine_inverse(&m).unwrap();
+        assert_mat_eq(&mat_mul(&m, &inverse), &IDENTITY);
+        assert_mat_eq(&mat_mul(&inverse, &m), &IDENTITY);
+
+        assert!(mat_affine_inverse(&mat_scale([1.0, 0.0, 1.0])).is_none());
+    }
+
+    #[test]
+    fn euler_round_trip() {
+        let angles = [
+            [10.0, 20.0, 30.0],
+            [-120.0, 45.0, 170.0],
+            [0.0, 90.0, 0.0],
+            [35.0, -90.0, 0.0],
+        ];
+        for &order in &ORDERS {
+            for &angle in &angles {
+                let m = euler_to_mat(order, angle);
+                let decomposed = mat_to_euler(&m, order);
+                assert_mat_eq(&m, &euler_to_mat(order, decomposed));
+            }
+        }
+    }
+
+    #[test]
+    fn rotation_follows_right_hand_rule() {
+        let p = transform_point(&mat_rotation(Axis::Z, 90.0), [1.0, 0.0, 0.0]);
+        assert!((p[0] - 0.0).abs() < 1.0e-6 && (p[1] - 1.0).abs() < 1.0e-6);
+
+        let p = transform_point(&mat_rotation(Axis::Y, 90.0), [0.0, 0.0, 1.0]);
+        assert!((p[0] - 1.0).abs() < 1.0e-6 && (p[2] - 0.0).abs() < 1.0e-6);
+
+        let p = transform_point(&mat_rotation(Axis::X, 90.0), [0.0, 1.0, 0.0]);
+        assert!((p[1] - 0.0).abs() < 1.0e-6 && (p[2] - 1.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn quat_round_trip() {
+        for &order in &ORDERS {
+            for &angle in &[[10.0, 20.0, 30.0], [-170.0, 80.0, 179.0], [180.0, 0.0, 0.0]] {
+                let m = euler_to_mat(order, angle);
+                let q = mat_to_quat(&m);
+                assert!((quat_dot(q, q) - 1.0).abs() < 1.0e-5);
+                assert_mat_eq(&m, &quat_to_mat(q));
+            }
+        }
+    }
+
+    #[test]
+    fn quat_slerp_halfway() {
+        let a = mat_to_quat(&mat_rotation(Axis::Z, 10.0));
+        let b = mat_to_quat(&mat_rotation(Axis::Z, 90.0));
+        let halfway = quat_slerp(a, b, 0.5);
+        assert_mat_eq(&quat_to_mat(halfway), &mat_rotation(Axis::Z, 50.0));
+        assert!((quat_angle_between(a, halfway) - 40.0).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn quat_mul_matches_mat_mul() {
+        let a = mat_rotation(Axis::X, 30.0);
+        let b = mat_rotation(Axis::Y, -75.0);
+        let product = quat_mul(mat_to_quat(&a), mat_to_quat(&b));
+        assert_mat_eq(&quat_to_mat(product), &mat_mul(&a, &b));
+    }
+}
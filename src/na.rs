@@ -0,0 +1,91 @@
+use crate::{frames::Frame, joint::JointData, tensor, Bvh, Joint};
+use nalgebra::{Isometry3, Quaternion, Translation3, UnitQuaternion, Vector3};
+
+impl Bvh {
+    /// Returns the world-space transform of every joint in the `Bvh` at the given
+    /// `frame` as `nalgebra` isometries, in the same order as [`Bvh::joints`].
+    ///
+    /// The transforms are the same as those returned by [`Bvh::world_transforms`],
+    /// except that scale channels cannot be represented by an isometry, and so are
+    /// ignored.
+    ///
+    /// This method is only available with the `nalgebra` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` has fewer values than the number of channels in the `Bvh`.
+    ///
+    /// [`Bvh::joints`]: struct.Bvh.html#method.joints
+    /// [`Bvh::world_transforms`]: struct.Bvh.html#method.world_transforms
+    pub fn world_transforms_na(&self, frame: &Frame<'_>) -> Vec<Isometry3<f32>> {
+        let motion = frame.as_slice();
+        let mut transforms: Vec<Isometry3<f32>> = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            let local = joint_local_isometry(joint, motion);
+            let transform = match joint.parent_index() {
+                Some(parent) => transforms[parent] * local,
+                None => local,
+            };
+            transforms.push(transform);
+        }
+        transforms
+    }
+
+    /// Returns the rotation of the joint at index `joint` relative to its parent at
+    /// the given `frame`, which is the composition of its rotation channels in the
+    /// order in which they are listed.
+    ///
+    /// This method is only available with the `nalgebra` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `joint` is out of range, or if `frame` has fewer values than the
+    /// number of channels in the `Bvh`.
+    pub fn joint_rotation_na(&self, joint: usize, frame: &Frame<'_>) -> UnitQuaternion<f32> {
+        let [x, y, z, w] = tensor::joint_rotation(&self.joints[joint], frame.as_slice());
+        UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z))
+    }
+}
+
+impl Joint<'_> {
+    /// Returns the offset of the joint as an `nalgebra` vector.
+    ///
+    /// This method is only available with the `nalgebra` feature.
+    #[inline]
+    pub fn offset_na(&self) -> Vector3<f32> {
+        Vector3::from(*self.offset())
+    }
+
+    /// Returns the end site of the joint as an `nalgebra` vector, if it has one.
+    ///
+    /// This method is only available with the `nalgebra` feature.
+    #[inline]
+    pub fn end_site_na(&self) -> Option<Vector3<f32>> {
+        self.end_site().map(|&end_site| Vector3::from(end_site))
+    }
+}
+
+/// Compute the transform of `joint` relative to its parent as an isometry, using
+/// the channel values in `motion`. Scale channels are ignored.
+fn joint_local_isometry(joint: &JointData, motion: &[f32]) -> Isometry3<f32> {
+    let mut transform = Isometry3::from_parts(
+        Translation3::from(Vector3::from(*joint.offset())),
+        UnitQuaternion::identity(),
+    );
+    for channel in joint.channels() {
+        let value = motion[channel.motion_index()];
+        let ty = channel.channel_type();
+        let mut axis = Vector3::zeros();
+        axis[ty.axis() as usize] = 1.0;
+        let channel_transform = if ty.is_position() {
+            Isometry3::from_parts(Translation3::from(axis * value), UnitQuaternion::identity())
+        } else if ty.is_rotation() {
+            let rotation = UnitQuaternion::from_scaled_axis(axis * value.to_radians());
+            Isometry3::from_parts(Translation3::identity(), rotation)
+        } else {
+            continue;
+        };
+        transform *= channel_transform;
+    }
+    transform
+}
@@ -0,0 +1,116 @@
+use crate::{errors::FrameInsertError, Bvh};
+use ndarray::{Array2, ArrayView2};
+
+impl Bvh {
+    /// Returns the motion values of the `Bvh` as an `ndarray` array with the shape
+    /// `(frames, channels)`, such as for numeric processing.
+    ///
+    /// Each row contains the values of one frame, in the same order as
+    /// [`Frame::as_slice`].
+    ///
+    /// This method is only available with the `ndarray` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         End Site
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 1.0 2.0
+    ///     3.0 4.0 5.0
+    /// };
+    ///
+    /// let motion = bvh.motion_ndarray();
+    /// assert_eq!(motion.shape(), &[2, 3]);
+    /// assert_eq!(motion[[1, 2]], 5.0);
+    /// ```
+    ///
+    /// [`Frame::as_slice`]: frames/struct.Frame.html#method.as_slice
+    pub fn motion_ndarray(&self) -> Array2<f32> {
+        let shape = (self.frames().len(), self.num_channels);
+        let values = self.motion_values[..shape.0 * shape.1].to_vec();
+        Array2::from_shape_vec(shape, values).expect("the motion values match the shape")
+    }
+
+    /// Create a new `Bvh` with the skeleton and frame time of `template`, whose
+    /// motion values are the rows of `motion`, in the format returned by
+    /// [`Bvh::motion_ndarray`].
+    ///
+    /// `motion` may have any memory layout, such as a transposed or sliced view.
+    ///
+    /// This method is only available with the `ndarray` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of columns of `motion` is not the number of
+    /// channels of `template`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, Bvh};
+    /// # use ndarray::array;
+    /// let template = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         End Site
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let motion = array![[0.0, 1.0, 2.0], [3.0, 4.0, 5.0]];
+    /// let bvh = Bvh::from_motion_ndarray(&template, motion.view())?;
+    /// assert_eq!(bvh.frames().len(), 2);
+    /// assert_eq!(bvh.frame_values(1), Some(&[3.0, 4.0, 5.0][..]));
+    ///
+    /// assert!(Bvh::from_motion_ndarray(&template, motion.t()).is_err());
+    /// # Result::<(), bvh_anim::errors::FrameInsertError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::motion_ndarray`]: struct.Bvh.html#method.motion_ndarray
+    pub fn from_motion_ndarray(
+        template: &Bvh,
+        motion: ArrayView2<'_, f32>,
+    ) -> Result<Bvh, FrameInsertError> {
+        let (num_frames, num_columns) = motion.dim();
+        if num_columns != template.num_channels {
+            return Err(FrameInsertError::incorrect_len(
+                template.num_channels,
+                num_columns,
+            ));
+        }
+
+        let mut motion_values = Vec::with_capacity(num_frames * num_columns);
+        for row in motion.rows() {
+            motion_values.extend(row.iter().copied());
+        }
+
+        Ok(Bvh {
+            joints: template.joints.clone(),
+            motion_values,
+            num_channels: template.num_channels,
+            frame_time: template.frame_time,
+            ..Bvh::new()
+        })
+    }
+}
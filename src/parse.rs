@@ -1,11 +1,13 @@
 #![allow(unused)]
 
 use crate::{
-    errors::{LoadJointsError, LoadMotionError},
+    diagnostic::{Diagnostic, DiagnosticKind},
+    errors::{LoadJointsError, LoadMotionError, ParseChannelError},
     joint::{JointData, JointName},
+    read::{FrameLimitPolicy, NonFinitePolicy, ParseOptions},
     Axis, Bvh, Channel, ChannelType, EnumeratedLines,
 };
-use bstr::ByteSlice;
+use bstr::{BString, ByteSlice};
 use lexical::parse;
 use std::time::Duration;
 
@@ -232,6 +234,8 @@ impl Bvh {
     pub(crate) fn read_joints(
         &mut self,
         lines: &mut EnumeratedLines<'_>,
+        options: &ParseOptions,
+        diagnostics: &mut Vec<Diagnostic>,
     ) -> Result<(), LoadJointsError> {
         const HEIRARCHY_KEYWORD: &[u8] = b"HIERARCHY";
 
@@ -304,17 +308,27 @@ impl Bvh {
                     next_expected_line = NextExpectedLine::RootName;
                 }
                 ROOT_KEYWORD => {
+                    if curr_mode == ParseMode::NotStarted {
+                        if !options.lenient {
+                            return Err(LoadJointsError::MissingHierarchy { line: line_num });
+                        }
+                        diagnostics.push(Diagnostic::new(
+                            DiagnosticKind::MissingHierarchy,
+                            Some(line_num),
+                        ));
+                        curr_mode = ParseMode::InHeirarchy;
+                        next_expected_line = NextExpectedLine::RootName;
+                    }
+
                     if curr_mode != ParseMode::InHeirarchy
                         || next_expected_line != NextExpectedLine::RootName
                     {
                         panic!("Unexpected root: {:?}", curr_mode);
                     }
 
-                    if let Some(name) = tokens.next() {
-                        curr_joint.set_name(name);
-                    } else {
-                        panic!("Missing root name!");
-                    }
+                    let name = parse_joint_name(line, &mut tokens, options)
+                        .ok_or(LoadJointsError::MissingJointName { line: line_num })?;
+                    curr_joint.set_name(name);
                 }
                 OPEN_BRACE => {
                     curr_depth += 1;
@@ -341,6 +355,22 @@ impl Bvh {
                         curr_index += 1;
                         in_end_site = false;
                         pushed_end_site_joint = true;
+                    } else if !pushed_end_site_joint {
+                        // We have closed the brace of a joint without any children or
+                        // an end site, which has not been pushed yet.
+                        if let JointData::Child {
+                            ref mut private, ..
+                        } = curr_joint
+                        {
+                            private.self_index = curr_index;
+                            private.parent_index = get_parent_index(&joints, curr_depth + 1);
+                            private.depth = curr_depth;
+                        }
+
+                        let new_joint = mem::replace(&mut curr_joint, JointData::empty_child());
+                        joints.push(new_joint);
+                        curr_index += 1;
+                        pushed_end_site_joint = true;
                     }
                 }
                 kw if kw == ENDSITE_KEYWORDS[0] => {
@@ -373,11 +403,9 @@ impl Bvh {
                         pushed_end_site_joint = false;
                     }
 
-                    if let Some(mut name) = tokens.next() {
-                        curr_joint.set_name(name);
-                    } else {
-                        panic!("Missing joint name!");
-                    }
+                    let name = parse_joint_name(line, &mut tokens, options)
+                        .ok_or(LoadJointsError::MissingJointName { line: line_num })?;
+                    curr_joint.set_name(name);
                 }
                 OFFSET_KEYWORD => {
                     if curr_mode != ParseMode::InHeirarchy {
@@ -385,6 +413,7 @@ impl Bvh {
                     }
 
                     let mut offset = [0.0, 0.0, 0.0];
+                    let mut count = 0;
 
                     macro_rules! parse_axis {
                         ($axis_field:literal, $axis_enum:ident) => {
@@ -395,7 +424,8 @@ impl Bvh {
                                         axis: Axis::$axis_enum,
                                         line: line_num,
                                     })?;
-                            } else {
+                                count += 1;
+                            } else if !options.lenient {
                                 return Err(LoadJointsError::MissingOffsetAxis {
                                     axis: Axis::$axis_enum,
                                     line: line_num,
@@ -408,6 +438,20 @@ impl Bvh {
                     parse_axis!(1, Y);
                     parse_axis!(2, Z);
 
+                    count += tokens.by_ref().count();
+                    if count > 3 && !options.lenient {
+                        return Err(LoadJointsError::ExtraOffsetValues {
+                            count,
+                            line: line_num,
+                        });
+                    }
+                    if count != 3 {
+                        diagnostics.push(Diagnostic::new(
+                            DiagnosticKind::OffsetValueCount { count },
+                            Some(line_num),
+                        ));
+                    }
+
                     curr_joint.set_offset(offset, in_end_site);
                 }
                 CHANNELS_KEYWORD => {
@@ -415,6 +459,17 @@ impl Bvh {
                         return Err(LoadJointsError::UnexpectedChannelsSection { line: line_num });
                     }
 
+                    if in_end_site {
+                        if !options.lenient {
+                            return Err(LoadJointsError::EndSiteChannels { line: line_num });
+                        }
+                        diagnostics.push(Diagnostic::new(
+                            DiagnosticKind::EndSiteChannelsIgnored,
+                            Some(line_num),
+                        ));
+                        continue;
+                    }
+
                     let num_channels: usize = tokens
                         .next()
                         .ok_or(LoadJointsError::ParseNumChannelsError {
@@ -429,24 +484,54 @@ impl Bvh {
                             }),
                         })?;
 
+                    // The stated number of channels is only checked against the listed
+                    // channels, so that a corrupt count cannot cause a huge allocation.
                     let mut channels: SmallVec<[Channel; 6]> = Default::default();
-                    channels.reserve(num_channels);
 
-                    while let Some(tok) = tokens.next() {
-                        let channel_ty = ChannelType::try_from(tok).map_err(|e| {
-                            LoadJointsError::ParseChannelError {
+                    for tok in tokens {
+                        let channel_ty = ChannelType::try_from(tok)
+                            .and_then(|ty| {
+                                if ty.is_scale() && !options.allow_scale_channels {
+                                    Err(ParseChannelError::from(tok))
+                                } else {
+                                    Ok(ty)
+                                }
+                            })
+                            .map_err(|e| LoadJointsError::ParseChannelError {
                                 error: e,
                                 line: line_num,
-                            }
-                        })?;
+                            })?;
                         let channel = Channel::new(channel_ty, curr_channel);
                         curr_channel += 1;
                         channels.push(channel);
                     }
 
+                    if channels.len() != num_channels {
+                        if !options.lenient {
+                            return Err(LoadJointsError::ChannelCountMismatch {
+                                expected: num_channels,
+                                actual: channels.len(),
+                                line: line_num,
+                            });
+                        }
+                        diagnostics.push(Diagnostic::new(
+                            DiagnosticKind::ChannelCountMismatch {
+                                expected: num_channels,
+                                actual: channels.len(),
+                            },
+                            Some(line_num),
+                        ));
+                    }
+
                     curr_joint.set_channels(channels);
                 }
-                _ => {}
+                _ => {
+                    if options.metadata_comments && curr_mode == ParseMode::NotStarted {
+                        if let Some((key, value)) = parse_metadata_comment(line) {
+                            self.metadata.insert(key, value);
+                        }
+                    }
+                }
             }
 
             if curr_mode == ParseMode::Finished {
@@ -464,14 +549,16 @@ impl Bvh {
         Ok(())
     }
 
-    pub(crate) fn read_motion(
+    /// Read the `MOTION`, `Frames` and `Frame Time` lines, setting the frame time
+    /// of the `Bvh` and returning the number of frames.
+    ///
+    /// If `fallback_frame_time` is given, then a missing `Frame Time` line is not
+    /// an error, and the frame time is set to `fallback_frame_time` instead.
+    pub(crate) fn read_motion_header(
         &mut self,
         lines: &mut EnumeratedLines<'_>,
-    ) -> Result<(), LoadMotionError> {
-        const MOTION_KEYWORD: &[u8] = b"MOTION";
-        const FRAMES_KEYWORD: &[u8] = b"Frames";
-        const FRAME_TIME_KEYWORDS: &[&[u8]] = &[b"Frame", b"Time:"];
-
+        fallback_frame_time: Option<Duration>,
+    ) -> Result<usize, LoadMotionError> {
         macro_rules! last_line_num {
             () => {
                 lines.last_enumerator().unwrap_or(0)
@@ -499,43 +586,27 @@ impl Bvh {
                 parse_error: None,
                 line: last_line_num!(),
             })
-            .and_then(|(line_num, line)| {
-                let line = line?;
-                let line = line.trim();
-                let mut tokens = line.fields_with(|c: char| c.is_ascii_whitespace() || c == ':');
-
-                if tokens.next() != Some(FRAMES_KEYWORD) {
-                    return Err(LoadMotionError::MissingNumFrames {
-                        parse_error: None,
-                        line: line_num,
-                    });
-                }
-
-                let parse_num_frames = |token: Option<&[u8]>| {
-                    if let Some(num_frames) = token.and_then(|b| str::from_utf8(b).ok()) {
-                        parse::<usize, _>(num_frames)
-                            .map_err(|e| LoadMotionError::MissingNumFrames {
-                                parse_error: Some(e),
-                                line: line_num,
-                            })
-                            .map_err(Into::into)
-                    } else {
-                        Err(LoadMotionError::MissingNumFrames {
-                            parse_error: None,
-                            line: line_num,
-                        })
-                    }
-                };
-
-                match tokens.next() {
-                    Some(tok) if tok == b":" => parse_num_frames(tokens.next()),
-                    Some(tok) => parse_num_frames(Some(tok)),
-                    None => Err(LoadMotionError::MissingNumFrames {
-                        parse_error: None,
-                        line: line_num,
-                    }),
-                }
-            })?;
+            .and_then(|(line_num, line)| parse_num_frames_line(line_num, &line?))?;
+
+        if let Some(fallback_frame_time) = fallback_frame_time {
+            let next = lines.next_non_empty_line();
+            let is_frame_time_line = match next {
+                Some((_, Ok(ref line))) => line
+                    .fields_with(|c: char| c.is_ascii_whitespace() || c == ':')
+                    .next()
+                    .is_some_and(is_frame_time_keyword),
+                Some((_, Err(_))) => true,
+                None => false,
+            };
+            if let Some(next) = next {
+                lines.put_back(next);
+            }
+            if !is_frame_time_line {
+                self.frame_time = fallback_frame_time;
+                self.frame_time_inferred = true;
+                return Ok(num_frames);
+            }
+        }
 
         self.frame_time = lines
             .next_non_empty_line()
@@ -543,84 +614,432 @@ impl Bvh {
                 parse_error: None,
                 line: last_line_num!(),
             })
-            .and_then(|(line_num, line)| {
-                let line = line?;
-                let mut tokens = line.fields();
+            .and_then(|(line_num, line)| parse_frame_time_line(line_num, &line?))?;
 
-                let frame_time_kw = tokens.next();
-                if frame_time_kw == FRAME_TIME_KEYWORDS.get(0).map(|b| *b) {
-                    // do nothing
-                } else {
-                    return Err(LoadMotionError::MissingFrameTime {
-                        parse_error: None,
-                        line: line_num,
-                    });
-                }
+        Ok(num_frames)
+    }
 
-                let frame_time_kw = tokens.next();
-                if frame_time_kw == FRAME_TIME_KEYWORDS.get(1).map(|b| *b) {
-                    // do nothing
-                } else {
-                    return Err(LoadMotionError::MissingFrameTime {
-                        parse_error: None,
-                        line: line_num,
-                    });
+    pub(crate) fn read_motion(
+        &mut self,
+        lines: &mut EnumeratedLines<'_>,
+        options: &ParseOptions,
+        diagnostics: &mut Vec<Diagnostic>,
+        stop_after_last_frame: bool,
+    ) -> Result<(), LoadMotionError> {
+        let fallback_frame_time = if options.lenient {
+            Some(options.fallback_frame_time)
+        } else {
+            None
+        };
+        let num_frames = self.read_motion_header(lines, fallback_frame_time)?;
+        if self.frame_time_inferred {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::MissingFrameTime {
+                    frame_time: self.frame_time,
+                },
+                lines.last_enumerator(),
+            ));
+        }
+        if options.skip_motion {
+            return Ok(());
+        }
+        let num_frames = match options.max_frames {
+            Some(max_frames) if num_frames > max_frames => match options.frame_limit_policy {
+                FrameLimitPolicy::Truncate => max_frames,
+                FrameLimitPolicy::Error => {
+                    return Err(LoadMotionError::TooManyFrames {
+                        max_frames,
+                        line: lines.last_enumerator().unwrap_or(0),
+                    })
                 }
-
-                let parse_frame_time = |token: Option<&[u8]>| {
-                    if let Some(frame_time) = token {
-                        let frame_time_secs = parse::<f64, _>(frame_time).map_err(|e| {
-                            LoadMotionError::MissingFrameTime {
-                                parse_error: Some(e),
+            },
+            _ => num_frames,
+        };
+        let expected_total_motion_values = self.num_channels.saturating_mul(num_frames);
+
+        // The `Frames` line may be wrong, so space for the values is not reserved up
+        // front, and the buffer grows as the rows are read instead.
+        let stride = options.frame_stride.get();
+        let mut num_motion_values = 0usize;
+
+        let row_width = self.num_channels + usize::from(options.leading_frame_column);
+        let mut num_tokens = 0usize;
+        let mut first_frame_number = None;
+        let expected_num_tokens = row_width.saturating_mul(num_frames);
+        let max_tokens = options
+            .max_frames
+            .map(|max_frames| row_width.saturating_mul(max_frames));
+
+        // Some exporters wrap the values of a single frame across several lines, so
+        // values are read as one stream and split into frames by the channel count.
+        // A line may hold several whole frames, but a frame may only start in the
+        // middle of a line if the line started with a frame; otherwise an earlier
+        // row had the wrong number of values, and the frames are misaligned.
+        'lines: while !stop_after_last_frame || num_tokens < expected_num_tokens {
+            let (line_num, line) = match lines.next() {
+                Some(next) => next,
+                None => break,
+            };
+            let line = line?;
+            let starts_with_frame = row_width == 0 || num_tokens.is_multiple_of(row_width);
+            let mut misaligned = false;
+            let separator = options.value_separator.map(char::from);
+            let tokens = line.fields_with(|c: char| c.is_whitespace() || Some(c) == separator);
+            for (token_index, token) in tokens.enumerate() {
+                if Some(num_tokens) == max_tokens {
+                    match options.frame_limit_policy {
+                        FrameLimitPolicy::Truncate => break 'lines,
+                        FrameLimitPolicy::Error => {
+                            return Err(LoadMotionError::TooManyFrames {
+                                max_frames: options.max_frames.unwrap_or(0),
                                 line: line_num,
-                            }
-                        })?;
-                        Ok(Duration::from_secs_f64(frame_time_secs))
-                    } else {
-                        Err(LoadMotionError::MissingFrameTime {
-                            parse_error: None,
-                            line: line_num,
-                        })
+                            })
+                        }
                     }
-                };
-
-                match tokens.next() {
-                    Some(tok) if tok == b":" => parse_frame_time(tokens.next()),
-                    Some(tok) => parse_frame_time(Some(tok)),
-                    None => Err(LoadMotionError::MissingNumFrames {
-                        parse_error: None,
-                        line: line_num,
-                    }),
                 }
-            })?;
-
-        let expected_total_motion_values = self.num_channels * num_frames;
 
-        self.motion_values.reserve(expected_total_motion_values);
+                if !starts_with_frame
+                    && !misaligned
+                    && token_index > 0
+                    && num_tokens.is_multiple_of(row_width)
+                {
+                    misaligned = true;
+                    let frame_index = num_tokens / row_width;
+                    if !options.lenient {
+                        return Err(LoadMotionError::MisalignedMotionRow {
+                            frame_index,
+                            line: line_num,
+                        });
+                    }
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticKind::MisalignedMotionRow { frame_index },
+                        Some(line_num),
+                    ));
+                }
 
-        for (line_num, line) in lines {
-            let line = line?;
-            let tokens = line.fields();
-            for (channel_index, token) in tokens.enumerate() {
-                let motion =
-                    parse::<f32, _>(token).map_err(|e| LoadMotionError::ParseMotionSection {
-                        parse_error: e,
-                        channel_index,
-                        line: line_num,
+                if options.leading_frame_column && num_tokens.is_multiple_of(row_width) {
+                    let frame_number = parse::<f32, _>(token).map_err(|e| {
+                        LoadMotionError::ParseMotionSection {
+                            parse_error: e,
+                            channel_index: 0,
+                            line: line_num,
+                        }
                     })?;
-                self.motion_values.push(motion);
+                    let frame_index = num_tokens / row_width;
+                    let expected = first_frame_number.map(|first: usize| first + frame_index);
+                    let is_whole = frame_number >= 0.0 && frame_number.fract() == 0.0;
+                    match expected {
+                        Some(expected) if is_whole && frame_number as usize == expected => {}
+                        None if is_whole => first_frame_number = Some(frame_number as usize),
+                        _ => {
+                            return Err(LoadMotionError::FrameIndexMismatch {
+                                expected,
+                                actual: frame_number,
+                                line: line_num,
+                            })
+                        }
+                    }
+                    num_tokens += 1;
+                    continue;
+                }
+                num_tokens += 1;
+
+                let channel_index = num_motion_values
+                    .checked_rem(self.num_channels)
+                    .unwrap_or(0);
+                let motion = parse_motion_value(token, channel_index, line_num, options)?;
+                let frame_index = num_motion_values
+                    .checked_div(self.num_channels)
+                    .unwrap_or(0);
+                if frame_index.is_multiple_of(stride) {
+                    self.motion_values.push(motion);
+                }
+                num_motion_values += 1;
             }
         }
 
-        if self.motion_values.len() != self.num_channels * num_frames {
+        if num_motion_values != expected_total_motion_values
+            && options.lenient
+            && self.num_channels > 0
+        {
+            let actual_num_frames = num_motion_values / self.num_channels;
+            let num_whole_values = self.motion_values.len() / self.num_channels * self.num_channels;
+            self.motion_values.truncate(num_whole_values);
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::FrameCountMismatch {
+                    expected: num_frames,
+                    actual: actual_num_frames,
+                },
+                None,
+            ));
+        } else if num_motion_values != expected_total_motion_values {
             return Err(LoadMotionError::MotionCountMismatch {
-                actual_total_motion_values: self.motion_values.len(),
+                actual_total_motion_values: num_motion_values,
                 expected_total_motion_values,
                 expected_num_frames: num_frames,
                 expected_num_clips: self.num_channels,
             });
         }
 
+        if stride > 1 {
+            self.frame_time = u32::try_from(stride)
+                .ok()
+                .and_then(|factor| self.frame_time.checked_mul(factor))
+                .ok_or(LoadMotionError::FrameTimeOverflow {
+                    frame_time: self.frame_time,
+                    stride,
+                })?;
+        }
+
         Ok(())
     }
+
+    /// Read motion values which are stored separately from the hierarchy, with the
+    /// values of one frame on each line. The rows may be preceded by any of the
+    /// `MOTION`, `Frames` and `Frame Time` lines; if the `Frames` line is present,
+    /// then the number of rows must match it.
+    pub(crate) fn read_motion_rows(
+        &mut self,
+        lines: &mut EnumeratedLines<'_>,
+        options: &ParseOptions,
+    ) -> Result<(), LoadMotionError> {
+        let mut expected_num_frames = None;
+        let mut num_frames = 0usize;
+
+        for (line_num, line) in lines {
+            let line = line?;
+            let line = line.trim();
+            let mut tokens = line.fields_with(|c: char| c.is_ascii_whitespace() || c == ':');
+            let first_token = match tokens.next() {
+                Some(tok) => tok,
+                None => continue,
+            };
+
+            if num_frames == 0 {
+                if first_token == MOTION_KEYWORD {
+                    continue;
+                } else if first_token.eq_ignore_ascii_case(FRAMES_KEYWORD) {
+                    expected_num_frames = Some(parse_num_frames_line(line_num, line)?);
+                    continue;
+                } else if is_frame_time_keyword(first_token) {
+                    self.frame_time = parse_frame_time_line(line_num, line)?;
+                    continue;
+                }
+            }
+
+            let mut num_values = 0usize;
+            for token in line.fields() {
+                let motion = parse_motion_value(token, num_values, line_num, options)?;
+                self.motion_values.push(motion);
+                num_values += 1;
+            }
+            if num_values != self.num_channels {
+                return Err(LoadMotionError::MotionRowWidthMismatch {
+                    expected_num_channels: self.num_channels,
+                    actual_num_values: num_values,
+                    line: line_num,
+                });
+            }
+            num_frames += 1;
+        }
+
+        match expected_num_frames {
+            Some(expected_num_frames) if expected_num_frames != num_frames => {
+                Err(LoadMotionError::MotionCountMismatch {
+                    actual_total_motion_values: self.motion_values.len(),
+                    expected_total_motion_values: expected_num_frames
+                        .saturating_mul(self.num_channels),
+                    expected_num_frames,
+                    expected_num_clips: self.num_channels,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parse a single motion value from `token`, applying the `non_finite_policy` of
+/// `options`.
+fn parse_motion_value(
+    token: &[u8],
+    channel_index: usize,
+    line_num: usize,
+    options: &ParseOptions,
+) -> Result<f32, LoadMotionError> {
+    let motion = parse::<f32, _>(token).map_err(|e| LoadMotionError::ParseMotionSection {
+        parse_error: e,
+        channel_index,
+        line: line_num,
+    })?;
+    if motion.is_finite() {
+        return Ok(motion);
+    }
+    match options.non_finite_policy {
+        NonFinitePolicy::Error => Err(LoadMotionError::NonFiniteMotionValue {
+            channel_index,
+            line: line_num,
+        }),
+        NonFinitePolicy::Zero => Ok(0.0),
+        NonFinitePolicy::Keep => Ok(motion),
+    }
+}
+
+const MOTION_KEYWORD: &[u8] = b"MOTION";
+const FRAMES_KEYWORD: &[u8] = b"Frames";
+const FRAME_TIME_KEYWORDS: &[&[u8]] = &[b"Frame", b"Time"];
+/// The spelling of the frame time keywords without a space, as written by some
+/// exporters.
+const FRAME_TIME_JOINED_KEYWORD: &[u8] = b"FrameTime";
+
+/// Returns `true` if `token` is the first token of a `Frame Time` line. Like the
+/// other motion header keywords, this ignores ASCII case.
+fn is_frame_time_keyword(token: &[u8]) -> bool {
+    token.eq_ignore_ascii_case(FRAME_TIME_KEYWORDS[0])
+        || token.eq_ignore_ascii_case(FRAME_TIME_JOINED_KEYWORD)
+}
+
+/// Parse the number of frames from a `Frames: <n>` `line`.
+fn parse_num_frames_line(line_num: usize, line: &[u8]) -> Result<usize, LoadMotionError> {
+    let line = line.trim();
+    let mut tokens = line.fields_with(|c: char| c.is_ascii_whitespace() || c == ':');
+
+    if !tokens
+        .next()
+        .is_some_and(|token| token.eq_ignore_ascii_case(FRAMES_KEYWORD))
+    {
+        return Err(LoadMotionError::MissingNumFrames {
+            parse_error: None,
+            line: line_num,
+        });
+    }
+
+    let parse_num_frames = |token: Option<&[u8]>| {
+        if let Some(num_frames) = token.and_then(|b| str::from_utf8(b).ok()) {
+            // Some exporters write the frame count in scientific notation,
+            // such as `1.2e3`, which is accepted if it is a whole number.
+            parse::<usize, _>(num_frames)
+                .or_else(|e| match parse::<f64, _>(num_frames) {
+                    Ok(n) if n >= 0.0 && n.fract() == 0.0 && n < usize::MAX as f64 => {
+                        Ok(n as usize)
+                    }
+                    _ => Err(e),
+                })
+                .map_err(|e| LoadMotionError::MissingNumFrames {
+                    parse_error: Some(e),
+                    line: line_num,
+                })
+        } else {
+            Err(LoadMotionError::MissingNumFrames {
+                parse_error: None,
+                line: line_num,
+            })
+        }
+    };
+
+    match tokens.next() {
+        Some(tok) if tok == b":" => parse_num_frames(tokens.next()),
+        Some(tok) => parse_num_frames(Some(tok)),
+        None => Err(LoadMotionError::MissingNumFrames {
+            parse_error: None,
+            line: line_num,
+        }),
+    }
+}
+
+/// Parse the frame time from a `Frame Time: <t>` `line`.
+fn parse_frame_time_line(line_num: usize, line: &[u8]) -> Result<Duration, LoadMotionError> {
+    let line = line.trim();
+    let mut tokens = line.fields_with(|c: char| c.is_ascii_whitespace() || c == ':');
+
+    let missing = || LoadMotionError::MissingFrameTime {
+        parse_error: None,
+        line: line_num,
+    };
+    match tokens.next() {
+        Some(kw) if kw.eq_ignore_ascii_case(FRAME_TIME_JOINED_KEYWORD) => {}
+        Some(kw) if kw.eq_ignore_ascii_case(FRAME_TIME_KEYWORDS[0]) => match tokens.next() {
+            Some(kw) if kw.eq_ignore_ascii_case(FRAME_TIME_KEYWORDS[1]) => {}
+            _ => return Err(missing()),
+        },
+        _ => return Err(missing()),
+    }
+
+    let parse_frame_time = |token: Option<&[u8]>| {
+        if let Some(frame_time) = token {
+            let frame_time_secs =
+                parse::<f64, _>(frame_time).map_err(|e| LoadMotionError::MissingFrameTime {
+                    parse_error: Some(e),
+                    line: line_num,
+                })?;
+            Duration::try_from_secs_f64(frame_time_secs).map_err(|_| {
+                LoadMotionError::MissingFrameTime {
+                    parse_error: None,
+                    line: line_num,
+                }
+            })
+        } else {
+            Err(LoadMotionError::MissingFrameTime {
+                parse_error: None,
+                line: line_num,
+            })
+        }
+    };
+
+    match tokens.next() {
+        Some(tok) if tok == b":" => parse_frame_time(tokens.next()),
+        Some(tok) => parse_frame_time(Some(tok)),
+        None => Err(LoadMotionError::MissingFrameTime {
+            parse_error: None,
+            line: line_num,
+        }),
+    }
+}
+
+/// Parse the name of a `ROOT` or `JOINT` from the remaining `tokens` of `line`.
+///
+/// If quoted names are enabled and the name starts with a `"`, then the name
+/// extends up to the next unescaped `"`, and may contain whitespace. Within a
+/// quoted name, `\"` is a literal quote and `\\` is a literal backslash.
+fn parse_joint_name<'a, I>(
+    line: &'a [u8],
+    tokens: &mut I,
+    options: &ParseOptions,
+) -> Option<Vec<u8>>
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    let first = tokens.next()?;
+    if !options.quoted_names || !first.starts_with(b"\"") {
+        return Some(first.to_vec());
+    }
+
+    // `first` is a subslice of `line`, so the quoted name starts at its offset.
+    let start = first.as_ptr() as usize - line.as_ptr() as usize + 1;
+    let mut name = Vec::new();
+    let mut escaped = false;
+    for &b in &line[start..] {
+        match (escaped, b) {
+            (false, b'\\') => escaped = true,
+            (false, b'"') => return Some(name),
+            _ => {
+                name.push(b);
+                escaped = false;
+            }
+        }
+    }
+
+    None
+}
+
+/// Parse a `# key: value` metadata comment from the trimmed `line`, returning
+/// `None` if it is not a comment or has no `:`.
+fn parse_metadata_comment(line: &[u8]) -> Option<(BString, BString)> {
+    let comment = line.strip_prefix(b"#")?;
+    let separator = comment.find_byte(b':')?;
+    let key = comment[..separator].trim();
+    if key.is_empty() {
+        return None;
+    }
+    let value = comment[separator + 1..].trim();
+    Some((BString::from(key), BString::from(value)))
 }
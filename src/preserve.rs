@@ -0,0 +1,315 @@
+use crate::{
+    errors::LoadError,
+    joint::JointData,
+    write::{IndentStyle, WriteOptions},
+    Bvh,
+};
+use bstr::BString;
+use std::{collections::BTreeMap, ops::Range, sync::Arc, time::Duration};
+
+/// The original source of a `Bvh` parsed with `Bvh::parse_preserving`.
+#[derive(Clone, Debug)]
+pub(crate) struct PreservedSource {
+    /// The original bytes of the file.
+    source: Vec<u8>,
+    /// The byte range of each motion value in `source`.
+    motion_spans: Vec<Range<usize>>,
+    /// The joints as they were parsed.
+    joints: Vec<JointData>,
+    /// The motion values as they were parsed.
+    motion_values: Vec<f32>,
+    /// The frame time as it was parsed.
+    frame_time: Duration,
+    /// The metadata as it was parsed.
+    metadata: BTreeMap<BString, BString>,
+    /// The indentation of the hierarchy, if it could be detected.
+    indent: Option<IndentStyle>,
+}
+
+/// The preserved source of a `Bvh`, if any.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Preserved(pub(crate) Option<Arc<PreservedSource>>);
+
+impl Preserved {
+    #[inline]
+    pub(crate) const fn none() -> Self {
+        Preserved(None)
+    }
+}
+
+/// The original formatting is not part of the value of a `Bvh`, so it is ignored
+/// when comparing.
+impl PartialEq for Preserved {
+    #[inline]
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Bvh {
+    /// Parse a sequence of bytes as if it were an in-memory `Bvh` file, and keep the
+    /// original text so that it can be written back unchanged.
+    ///
+    /// When the `Bvh` is written with [`WriteOptions::with_preserve_formatting`], the
+    /// original text is reproduced byte for byte, and only the motion values which
+    /// have been changed since parsing are reformatted. If the joints, the frame
+    /// time, the number of frames or the [`metadata`] have been changed, then the
+    /// whole `Bvh` is written normally instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{Bvh, write::WriteOptions};
+    /// let bvh_string = b"HIERARCHY
+    /// ROOT Hips
+    /// {
+    ///   OFFSET 0 0 0
+    ///   CHANNELS 1 Xposition
+    /// }
+    /// MOTION
+    /// Frames: 2
+    /// Frame Time: 0.5
+    /// 1.000   2
+    /// ";
+    ///
+    /// let bvh = Bvh::parse_preserving(&bvh_string[..])?;
+    /// let options = WriteOptions::new().with_preserve_formatting(true);
+    /// assert_eq!(options.write_to_string(&bvh), &bvh_string[..]);
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    ///
+    /// [`WriteOptions::with_preserve_formatting`]: write/struct.WriteOptions.html#method.with_preserve_formatting
+    /// [`metadata`]: struct.Bvh.html#method.metadata
+    pub fn parse_preserving<B: AsRef<[u8]>>(bytes: B) -> Result<Self, LoadError> {
+        let source = bytes.as_ref();
+        let mut bvh = Bvh::from_bytes(source)?;
+
+        let motion_spans = find_motion_spans(source);
+        if motion_spans.len() == bvh.motion_values.len() {
+            bvh.preserved = Preserved(Some(Arc::new(PreservedSource {
+                source: source.to_vec(),
+                motion_spans,
+                joints: bvh.joints.clone(),
+                motion_values: bvh.motion_values.clone(),
+                frame_time: bvh.frame_time,
+                metadata: bvh.metadata.clone(),
+                indent: detect_indent_style(source),
+            })));
+        }
+
+        Ok(bvh)
+    }
+
+    /// Returns the indentation style of the hierarchy of the original source, if the
+    /// `Bvh` was parsed with [`Bvh::parse_preserving`].
+    ///
+    /// The style is detected from the first indented line of the hierarchy. If it
+    /// is indented with spaces, then the number of spaces is the smallest
+    /// indentation of any line in the hierarchy. Returns `None` if the `Bvh` was not
+    /// parsed with `parse_preserving`, or if its hierarchy has no joints.
+    ///
+    /// When a `Bvh` which cannot be written from its preserved source is written
+    /// with [`WriteOptions::with_preserve_formatting`], this style is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{Bvh, write::IndentStyle};
+    /// let bvh_string = b"HIERARCHY
+    /// ROOT Hips
+    /// {
+    ///   OFFSET 0 0 0
+    ///   CHANNELS 1 Xposition
+    /// }
+    /// MOTION
+    /// Frames: 1
+    /// Frame Time: 0.5
+    /// 1
+    /// ";
+    ///
+    /// let bvh = Bvh::parse_preserving(&bvh_string[..])?;
+    /// assert_eq!(bvh.detected_indent_style(), Some(IndentStyle::with_spaces(2)));
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::parse_preserving`]: struct.Bvh.html#method.parse_preserving
+    /// [`WriteOptions::with_preserve_formatting`]: write/struct.WriteOptions.html#method.with_preserve_formatting
+    #[inline]
+    pub fn detected_indent_style(&self) -> Option<IndentStyle> {
+        self.preserved.0.as_ref()?.indent.clone()
+    }
+
+    /// Returns the range of bytes in the original source which contains the motion
+    /// values of the frame at index `frame`, from the start of its first value to
+    /// the end of its last value.
+    ///
+    /// Returns `None` if the `Bvh` was not parsed with [`Bvh::parse_preserving`],
+    /// if `frame` is out of range, if the `Bvh` has no channels, or if the number of
+    /// motion values has changed since it was parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// let bvh_string = b"HIERARCHY
+    /// ROOT Hips
+    /// {
+    ///   OFFSET 0 0 0
+    ///   CHANNELS 2 Xposition Yposition
+    /// }
+    /// MOTION
+    /// Frames: 2
+    /// Frame Time: 0.5
+    /// 1 2
+    /// 3 4
+    /// ";
+    ///
+    /// let bvh = Bvh::parse_preserving(&bvh_string[..])?;
+    /// let range = bvh.frame_byte_range(1).unwrap();
+    /// assert_eq!(&bvh_string[range], b"3 4");
+    /// assert!(bvh.frame_byte_range(2).is_none());
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::parse_preserving`]: struct.Bvh.html#method.parse_preserving
+    pub fn frame_byte_range(&self, frame: usize) -> Option<Range<usize>> {
+        let preserved = self.preserved.0.as_ref()?;
+        if self.num_channels == 0 || preserved.motion_values.len() != self.motion_values.len() {
+            return None;
+        }
+
+        let spans = preserved
+            .motion_spans
+            .chunks_exact(self.num_channels)
+            .nth(frame)?;
+        Some(spans.first()?.start..spans.last()?.end)
+    }
+
+    /// Write the `Bvh` using its preserved source, or return `None` if it has no
+    /// preserved source, or if it has been changed too much to use it.
+    pub(crate) fn write_preserved(&self, options: &WriteOptions) -> Option<Vec<u8>> {
+        let preserved = self.usable_preserved()?;
+        let mut out = Vec::with_capacity(preserved.source.len());
+        let mut written_to = 0;
+        let values = self.motion_values.iter().zip(&preserved.motion_values);
+        for ((value, original), span) in values.zip(&preserved.motion_spans) {
+            if value.to_bits() != original.to_bits() {
+                out.extend_from_slice(&preserved.source[written_to..span.start]);
+                out.extend_from_slice(options.format_motion_value(*value).as_bytes());
+                written_to = span.end;
+            }
+        }
+        out.extend_from_slice(&preserved.source[written_to..]);
+
+        Some(out)
+    }
+
+    /// Returns the length of the preserved source of the `Bvh`, or `None` if it would
+    /// not be used by [`Bvh::write_preserved`].
+    ///
+    /// [`Bvh::write_preserved`]: struct.Bvh.html#method.write_preserved
+    #[inline]
+    pub(crate) fn preserved_len(&self) -> Option<usize> {
+        self.usable_preserved()
+            .map(|preserved| preserved.source.len())
+    }
+
+    /// Returns the preserved source of the `Bvh`, or `None` if it has no preserved
+    /// source, or if it has been changed too much to use it.
+    fn usable_preserved(&self) -> Option<&PreservedSource> {
+        let preserved = self.preserved.0.as_ref()?;
+        if preserved.joints != self.joints
+            || preserved.frame_time != self.frame_time
+            || preserved.motion_values.len() != self.motion_values.len()
+            || preserved.metadata != self.metadata
+        {
+            return None;
+        }
+        Some(preserved)
+    }
+}
+
+/// Detect the indentation of the hierarchy of `source`, which ends at the `MOTION`
+/// line.
+fn detect_indent_style(source: &[u8]) -> Option<IndentStyle> {
+    let hierarchy = source
+        .split(|&b| b == b'\n' || b == b'\r')
+        .take_while(|line| !line.trim_ascii_start().starts_with(b"MOTION"));
+
+    let mut found_joint = false;
+    let mut spaces: Option<usize> = None;
+    for line in hierarchy {
+        let content = line.trim_ascii_start();
+        if content.is_empty() {
+            continue;
+        }
+        found_joint |= content.starts_with(b"ROOT");
+
+        let indent = &line[..line.len() - content.len()];
+        match indent.first() {
+            Some(b'\t') if spaces.is_none() => return Some(IndentStyle::Tabs),
+            Some(_) => {
+                let n = indent.iter().take_while(|&&b| b == b' ').count();
+                if n > 0 {
+                    spaces = Some(spaces.map_or(n, |spaces| spaces.min(n)));
+                }
+            }
+            None => {}
+        }
+    }
+
+    if !found_joint {
+        return None;
+    }
+    Some(spaces.map_or(IndentStyle::NoIndentation, IndentStyle::with_spaces))
+}
+
+/// Find the byte range of every value in the motion section of `source`, which
+/// follows the `MOTION`, `Frames` and `Frame Time` lines.
+fn find_motion_spans(source: &[u8]) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut headers_remaining = None::<usize>;
+    let mut line_start = 0;
+
+    for line in source.split_inclusive(|&b| b == b'\n') {
+        let tokens = tokens(line, line_start);
+        line_start += line.len();
+
+        match headers_remaining {
+            None => {
+                if tokens.first().map(|t| &source[t.clone()]) == Some(b"MOTION") {
+                    headers_remaining = Some(2);
+                }
+            }
+            Some(0) => spans.extend(tokens),
+            Some(ref mut remaining) => {
+                if !tokens.is_empty() {
+                    *remaining -= 1;
+                }
+            }
+        }
+    }
+
+    spans
+}
+
+/// Split `line` into whitespace separated tokens, returning their byte ranges
+/// offset by `start`.
+fn tokens(line: &[u8], start: usize) -> Vec<Range<usize>> {
+    let mut tokens = Vec::new();
+    let mut token_start = None;
+    for (i, b) in line.iter().enumerate() {
+        match (b.is_ascii_whitespace(), token_start) {
+            (true, Some(s)) => {
+                tokens.push(start + s..start + i);
+                token_start = None;
+            }
+            (false, None) => token_start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = token_start {
+        tokens.push(start + s..start + line.len());
+    }
+    tokens
+}
@@ -0,0 +1,810 @@
+//! Contains options for `bvh` file parsing.
+
+use crate::{diagnostic::Diagnostic, errors::LoadError, Bvh, ByteLines, CachedEnumerate};
+use bstr::io::BufReadExt;
+use std::{
+    io::{BufRead, BufReader, Cursor, Read},
+    num::NonZeroUsize,
+    time::Duration,
+};
+
+/// Specify options for parsing a `Bvh`.
+///
+/// Numbers are always parsed with `.` as the decimal separator, regardless of the
+/// locale of the system, so a value such as `1,5` is an error rather than `1.5`.
+///
+/// Lines may end with `\n`, `\r\n` or a lone `\r`, and different line endings may
+/// be mixed in the same file.
+///
+/// The `Frames` and `Frame Time` keywords are matched ignoring ASCII case, and
+/// `Frame Time` may also be written without a space, as `FrameTime`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// How to handle motion values which are `NaN` or infinite.
+    pub non_finite_policy: NonFinitePolicy,
+    /// Whether joint names may be enclosed in double quotes, such as
+    /// `JOINT "Left Hand"`, to allow them to contain whitespace. A quote inside a
+    /// quoted name is escaped as `\"`, and a backslash as `\\`.
+    ///
+    /// This is not part of the standard bvh format, so it is disabled by default.
+    pub quoted_names: bool,
+    /// Whether `Xscale`, `Yscale` and `Zscale` channels are accepted in a
+    /// `CHANNELS` section.
+    ///
+    /// This is not part of the standard bvh format, so it is disabled by default.
+    pub allow_scale_channels: bool,
+    /// Whether to recover from malformed input where possible, instead of returning
+    /// an error. Each recovery is reported as a [`Diagnostic`][`Diagnostic`], which can
+    /// be retrieved with [`ParseOptions::read_with_diagnostics`][`ParseOptions::read_with_diagnostics`].
+    ///
+    /// When lenient, the following are recovered from:
+    ///
+    /// * An `OFFSET` section with fewer than 3 values has its missing values set to
+    ///   `0.0`, and one with more than 3 values has its extra values ignored.
+    /// * A `CHANNELS` section in an `End Site` is ignored, and its channels do not
+    ///   have any motion values.
+    /// * A `CHANNELS` section which lists a different number of channels than it
+    ///   states uses the listed channels.
+    /// * A missing `HIERARCHY` keyword is inferred from the `ROOT` joint.
+    /// * A motion row with the wrong number of values is reported, but its values
+    ///   are still read in order, so the following frames may be misaligned.
+    /// * If the number of frames does not match the `Frames` line, then the frames
+    ///   which were read are kept, and an incomplete last frame is ignored.
+    /// * A missing `Frame Time` line is replaced by the `fallback_frame_time`.
+    ///
+    /// This is disabled by default.
+    ///
+    /// [`Diagnostic`]: ../diagnostic/struct.Diagnostic.html
+    /// [`ParseOptions::read_with_diagnostics`]: struct.ParseOptions.html#method.read_with_diagnostics
+    pub lenient: bool,
+    /// Keep only every `frame_stride`th frame of the motion, starting with the first,
+    /// to quickly load a preview of a large file. The frame time is multiplied by
+    /// `frame_stride`, so that the animation has the same duration.
+    ///
+    /// Each frame is still checked while parsing, but skipped frames are never
+    /// stored. The default is `1`, which keeps every frame. If the multiplied frame
+    /// time overflows a `Duration`, then parsing fails with
+    /// `LoadMotionError::FrameTimeOverflow`.
+    pub frame_stride: NonZeroUsize,
+    /// Whether every frame of the motion starts with its frame number, as written
+    /// by some exporters. The frame number is checked and then discarded, so it is
+    /// not stored as a channel value. The first frame may have any whole number,
+    /// and each following frame must have the next number.
+    ///
+    /// This is not part of the standard bvh format, so it is disabled by default.
+    pub leading_frame_column: bool,
+    /// An ASCII byte which separates motion values in addition to whitespace, such as
+    /// `b','` for exporters which write comma separated motion rows. Consecutive
+    /// separators are treated as a single separator, so empty values are skipped.
+    /// The `HIERARCHY` section is not affected.
+    ///
+    /// If this is `None`, then motion values are only separated by whitespace.
+    /// This is the default.
+    pub value_separator: Option<u8>,
+    /// Whether to read comments of the form `# key: value` before the `HIERARCHY`
+    /// keyword into the metadata of the `Bvh`. See [`Bvh::metadata`].
+    ///
+    /// The key is the text before the first `:`, and the value is the text after
+    /// it, both with surrounding whitespace removed. If a key is repeated, the last
+    /// value is kept. Comment lines without a `:` are ignored.
+    ///
+    /// This is not part of the standard bvh format, so it is disabled by default.
+    ///
+    /// [`Bvh::metadata`]: ../struct.Bvh.html#method.metadata
+    pub metadata_comments: bool,
+    /// Whether to stop reading after the `Frames` and `Frame Time` lines, without
+    /// reading the motion values, such as for tools which only need the skeleton.
+    ///
+    /// The `Bvh` then has no frames, but its hierarchy and frame time are read as
+    /// usual, and `frame_stride` is ignored. To only read the number of frames and
+    /// the other summary values, use [`ParseOptions::metadata_from_reader`].
+    ///
+    /// This is disabled by default.
+    ///
+    /// [`ParseOptions::metadata_from_reader`]: struct.ParseOptions.html#method.metadata_from_reader
+    pub skip_motion: bool,
+    /// The frame time to use in lenient mode if the `Frame Time` line is missing.
+    /// See [`Bvh::frame_time_was_inferred`].
+    ///
+    /// The default is `1/30` of a second.
+    ///
+    /// [`Bvh::frame_time_was_inferred`]: ../struct.Bvh.html#method.frame_time_was_inferred
+    pub fallback_frame_time: Duration,
+    /// The largest number of frames to read, such as to bound the memory used by
+    /// untrusted files. What happens to frames beyond the limit is given by
+    /// `frame_limit_policy`. Frames are counted before `frame_stride` is applied.
+    ///
+    /// If this is `None`, then every frame is read. This is the default.
+    pub max_frames: Option<usize>,
+    /// How frames beyond `max_frames` are handled.
+    pub frame_limit_policy: FrameLimitPolicy,
+}
+
+impl Default for ParseOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParseOptions {
+    /// Create a new `ParseOptions` with default values.
+    #[inline]
+    pub const fn new() -> Self {
+        ParseOptions {
+            non_finite_policy: NonFinitePolicy::Error,
+            quoted_names: false,
+            allow_scale_channels: false,
+            lenient: false,
+            frame_stride: NonZeroUsize::MIN,
+            leading_frame_column: false,
+            value_separator: None,
+            metadata_comments: false,
+            skip_motion: false,
+            fallback_frame_time: Duration::from_nanos(33_333_333),
+            max_frames: None,
+            frame_limit_policy: FrameLimitPolicy::Truncate,
+        }
+    }
+
+    /// Loads the `Bvh` from the `reader` with the given options. The `reader` is
+    /// buffered internally.
+    ///
+    /// Any [`Diagnostic`][`Diagnostic`]s reported while parsing are discarded; use
+    /// [`ParseOptions::read_with_diagnostics`][`ParseOptions::read_with_diagnostics`]
+    /// to retrieve them.
+    ///
+    /// [`Diagnostic`]: ../diagnostic/struct.Diagnostic.html
+    /// [`ParseOptions::read_with_diagnostics`]: struct.ParseOptions.html#method.read_with_diagnostics
+    #[inline]
+    pub fn read<R: Read>(&self, reader: R) -> Result<Bvh, LoadError> {
+        self.read_with_diagnostics(reader).map(|(bvh, _)| bvh)
+    }
+
+    /// Loads the `Bvh` from the `reader` with the given options, along with the
+    /// [`Diagnostic`][`Diagnostic`]s which were reported while parsing it. The
+    /// `reader` is buffered internally.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{diagnostic::DiagnosticKind, read::ParseOptions};
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 1.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0
+    /// "#;
+    ///
+    /// assert!(ParseOptions::new().read_bytes(&bvh_string[..]).is_err());
+    ///
+    /// let (bvh, diagnostics) = ParseOptions::new()
+    ///     .with_lenient(true)
+    ///     .read_with_diagnostics(&bvh_string[..])?;
+    /// assert_eq!(*bvh.root_joint().unwrap().offset(), [0.0, 1.0, 0.0]);
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].kind, DiagnosticKind::OffsetValueCount { count: 2 });
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    ///
+    /// [`Diagnostic`]: ../diagnostic/struct.Diagnostic.html
+    pub fn read_with_diagnostics<R: Read>(
+        &self,
+        reader: R,
+    ) -> Result<(Bvh, Vec<Diagnostic>), LoadError> {
+        read_lines(self, &mut BufReader::new(reader), false)
+    }
+
+    /// Loads a `Bvh` which is embedded in a larger stream from the current position
+    /// of `reader` with the given options, stopping after the last frame given by
+    /// the `Frames` line. See [`Bvh::parse_from`].
+    ///
+    /// Unlike [`ParseOptions::read`], the `reader` is not buffered internally, so
+    /// that it is left positioned at the start of the line after the last frame.
+    ///
+    /// [`Bvh::parse_from`]: ../struct.Bvh.html#method.parse_from
+    /// [`ParseOptions::read`]: struct.ParseOptions.html#method.read
+    pub fn read_from<R: BufRead>(&self, reader: &mut R) -> Result<Bvh, LoadError> {
+        read_lines(self, reader, true).map(|(bvh, _)| bvh)
+    }
+
+    /// Parse a sequence of bytes as if it were an in-memory `Bvh` file, with the
+    /// given options.
+    #[inline]
+    pub fn read_bytes<B: AsRef<[u8]>>(&self, bytes: B) -> Result<Bvh, LoadError> {
+        self.read(Cursor::new(bytes))
+    }
+
+    /// Sets `non_finite_policy` on `self` to the new `NonFinitePolicy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::read::{NonFinitePolicy, ParseOptions};
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 nan 0.0
+    /// "#;
+    ///
+    /// assert!(ParseOptions::new().read_bytes(&bvh_string[..]).is_err());
+    ///
+    /// let bvh = ParseOptions::new()
+    ///     .on_non_finite(NonFinitePolicy::Zero)
+    ///     .read_bytes(&bvh_string[..])?;
+    /// assert_eq!(bvh.frames().next().unwrap().as_slice(), &[0.0, 0.0, 0.0]);
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub const fn on_non_finite(self, non_finite_policy: NonFinitePolicy) -> Self {
+        Self {
+            non_finite_policy,
+            ..self
+        }
+    }
+
+    /// Sets `quoted_names` on `self` to the new `quoted_names`.
+    #[inline]
+    pub const fn with_quoted_names(self, quoted_names: bool) -> Self {
+        Self {
+            quoted_names,
+            ..self
+        }
+    }
+
+    /// Sets `allow_scale_channels` on `self` to the new `allow_scale_channels`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{read::ParseOptions, ChannelType};
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 4 Xposition Yposition Zposition Yscale
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 0.0 0.0 2.0
+    /// "#;
+    ///
+    /// assert!(ParseOptions::new().read_bytes(&bvh_string[..]).is_err());
+    ///
+    /// let bvh = ParseOptions::new()
+    ///     .allow_scale_channels(true)
+    ///     .read_bytes(&bvh_string[..])?;
+    /// let root = bvh.root_joint().unwrap();
+    /// assert_eq!(root.channels()[3].channel_type(), ChannelType::ScaleY);
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub const fn allow_scale_channels(self, allow_scale_channels: bool) -> Self {
+        Self {
+            allow_scale_channels,
+            ..self
+        }
+    }
+
+    /// Sets `lenient` on `self` to the new `lenient`.
+    #[inline]
+    pub const fn with_lenient(self, lenient: bool) -> Self {
+        Self { lenient, ..self }
+    }
+
+    /// Sets `frame_stride` on `self` to the new `frame_stride`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::read::ParseOptions;
+    /// # use std::{num::NonZeroUsize, time::Duration};
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 5
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     1.0
+    ///     2.0
+    ///     3.0
+    ///     4.0
+    /// "#;
+    ///
+    /// let bvh = ParseOptions::new()
+    ///     .frame_stride(NonZeroUsize::new(2).unwrap())
+    ///     .read_bytes(&bvh_string[..])?;
+    /// let values = bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>();
+    /// assert_eq!(values, [0.0, 2.0, 4.0]);
+    /// assert_eq!(*bvh.frame_time(), Duration::from_secs(1));
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub const fn frame_stride(self, frame_stride: NonZeroUsize) -> Self {
+        Self {
+            frame_stride,
+            ..self
+        }
+    }
+
+    /// Sets `leading_frame_column` on `self` to the new `leading_frame_column`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::read::ParseOptions;
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.5
+    ///     1 0.0
+    ///     2 0.5
+    ///     3 1.0
+    /// "#;
+    ///
+    /// let bvh = ParseOptions::new()
+    ///     .with_leading_frame_column(true)
+    ///     .read_bytes(&bvh_string[..])?;
+    /// let values = bvh.frames().map(|frame| frame[0]).collect::<Vec<_>>();
+    /// assert_eq!(values, [0.0, 0.5, 1.0]);
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub const fn with_leading_frame_column(self, leading_frame_column: bool) -> Self {
+        Self {
+            leading_frame_column,
+            ..self
+        }
+    }
+
+    /// Sets `value_separator` on `self` to the new `value_separator`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::read::ParseOptions;
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.5
+    ///     0.0,1.0,2.0
+    ///     3.0, 4.0, 5.0
+    /// "#;
+    ///
+    /// assert!(ParseOptions::new().read_bytes(&bvh_string[..]).is_err());
+    ///
+    /// let bvh = ParseOptions::new()
+    ///     .with_value_separator(Some(b','))
+    ///     .read_bytes(&bvh_string[..])?;
+    /// assert_eq!(bvh.frame_values(1), Some(&[3.0, 4.0, 5.0][..]));
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub const fn with_value_separator(self, value_separator: Option<u8>) -> Self {
+        Self {
+            value_separator,
+            ..self
+        }
+    }
+
+    /// Sets `metadata_comments` on `self` to the new `metadata_comments`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::read::ParseOptions;
+    /// use bstr::ByteSlice;
+    ///
+    /// let bvh_string = br#"
+    ///     ## source: studio capture
+    ///     ## actor: A. Smith
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.5
+    ///     0.0
+    /// "#;
+    ///
+    /// let bvh = ParseOptions::new()
+    ///     .with_metadata_comments(true)
+    ///     .read_bytes(&bvh_string[..])?;
+    /// let metadata = bvh.metadata();
+    /// assert_eq!(metadata.len(), 2);
+    /// assert_eq!(metadata[b"actor".as_bstr()], "A. Smith");
+    ///
+    /// let bvh = ParseOptions::new().read_bytes(&bvh_string[..])?;
+    /// assert!(bvh.metadata().is_empty());
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub const fn with_metadata_comments(self, metadata_comments: bool) -> Self {
+        Self {
+            metadata_comments,
+            ..self
+        }
+    }
+
+    /// Sets `skip_motion` on `self` to the new `skip_motion`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::read::ParseOptions;
+    /// # use std::time::Duration;
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     1.0
+    /// "#;
+    ///
+    /// let bvh = ParseOptions::new()
+    ///     .with_skip_motion(true)
+    ///     .read_bytes(&bvh_string[..])?;
+    /// assert_eq!(bvh.joints().count(), 2);
+    /// assert_eq!(bvh.num_frames(), 0);
+    /// assert_eq!(*bvh.frame_time(), Duration::from_millis(500));
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub const fn with_skip_motion(self, skip_motion: bool) -> Self {
+        Self {
+            skip_motion,
+            ..self
+        }
+    }
+
+    /// Sets `fallback_frame_time` on `self` to the new `fallback_frame_time`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::read::ParseOptions;
+    /// # use std::time::Duration;
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     0.0
+    ///     1.0
+    /// "#;
+    ///
+    /// let bvh = ParseOptions::new()
+    ///     .with_lenient(true)
+    ///     .with_fallback_frame_time(Duration::from_millis(40))
+    ///     .read_bytes(&bvh_string[..])?;
+    /// assert_eq!(*bvh.frame_time(), Duration::from_millis(40));
+    /// assert_eq!(bvh.num_frames(), 2);
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub const fn with_fallback_frame_time(self, fallback_frame_time: Duration) -> Self {
+        Self {
+            fallback_frame_time,
+            ..self
+        }
+    }
+
+    /// Sets `max_frames` on `self` to the new `max_frames`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::read::{FrameLimitPolicy, ParseOptions};
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 3
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     1.0
+    ///     2.0
+    /// "#;
+    ///
+    /// let options = ParseOptions::new().with_max_frames(Some(2));
+    /// let bvh = options.read_bytes(&bvh_string[..])?;
+    /// assert_eq!(bvh.num_frames(), 2);
+    ///
+    /// let options = options.on_frame_limit(FrameLimitPolicy::Error);
+    /// assert!(options.read_bytes(&bvh_string[..]).is_err());
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub const fn with_max_frames(self, max_frames: Option<usize>) -> Self {
+        Self { max_frames, ..self }
+    }
+
+    /// Sets `frame_limit_policy` on `self` to the new `FrameLimitPolicy`. See
+    /// [`ParseOptions::with_max_frames`].
+    ///
+    /// [`ParseOptions::with_max_frames`]: struct.ParseOptions.html#method.with_max_frames
+    #[inline]
+    pub const fn on_frame_limit(self, frame_limit_policy: FrameLimitPolicy) -> Self {
+        Self {
+            frame_limit_policy,
+            ..self
+        }
+    }
+}
+
+/// Specifies how motion values which are `NaN` or infinite are handled when
+/// parsing a `Bvh`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NonFinitePolicy {
+    /// Return an error when a non-finite motion value is found. This is the default.
+    Error,
+    /// Replace non-finite motion values with `0.0`.
+    Zero,
+    /// Keep non-finite motion values as they are.
+    Keep,
+}
+
+impl Default for NonFinitePolicy {
+    #[inline]
+    fn default() -> Self {
+        NonFinitePolicy::Error
+    }
+}
+
+/// Specifies how frames beyond the `max_frames` of the `ParseOptions` are handled
+/// when parsing a `Bvh`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum FrameLimitPolicy {
+    /// Stop reading after `max_frames` frames, and ignore the rest of the motion.
+    /// The `Frames` line may promise more frames than are kept. This is the
+    /// default.
+    Truncate,
+    /// Return an error if the `Frames` line promises more than `max_frames`
+    /// frames, or if more than `max_frames` frames are found.
+    Error,
+}
+
+impl Default for FrameLimitPolicy {
+    #[inline]
+    fn default() -> Self {
+        FrameLimitPolicy::Truncate
+    }
+}
+
+/// Summary information about a `bvh` file, read without parsing its motion values.
+///
+/// See [`Bvh::metadata_from_reader`][`Bvh::metadata_from_reader`].
+///
+/// [`Bvh::metadata_from_reader`]: ../struct.Bvh.html#method.metadata_from_reader
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct BvhMetadata {
+    /// The number of joints in the skeleton.
+    pub num_joints: usize,
+    /// The total number of channels of all joints.
+    pub num_channels: usize,
+    /// The number of frames promised by the `Frames` line.
+    pub num_frames: usize,
+    /// The time it takes to play one frame.
+    pub frame_time: Duration,
+}
+
+impl BvhMetadata {
+    /// Returns the total duration of the animation.
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.frame_time.mul_f64(self.num_frames as f64)
+    }
+}
+
+impl Bvh {
+    /// Read the summary information of a `bvh` file from the `reader`, parsing only
+    /// the `HIERARCHY` section and the `Frames` and `Frame Time` lines.
+    ///
+    /// The motion values are not read, so this is much faster than parsing the
+    /// whole file, and the number of motion values is not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::Bvh;
+    /// # use std::time::Duration;
+    /// let bvh_string = br#"
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 20
+    ///     Frame Time: 0.5
+    /// "#;
+    ///
+    /// let metadata = Bvh::metadata_from_reader(&mut &bvh_string[..])?;
+    /// assert_eq!(metadata.num_joints, 2);
+    /// assert_eq!(metadata.num_channels, 6);
+    /// assert_eq!(metadata.num_frames, 20);
+    /// assert_eq!(metadata.duration(), Duration::from_secs(10));
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    pub fn metadata_from_reader<R: BufReadExt>(reader: &mut R) -> Result<BvhMetadata, LoadError> {
+        #[inline(never)]
+        fn metadata_from_reader_(reader: &mut dyn BufReadExt) -> Result<BvhMetadata, LoadError> {
+            let mut lines = CachedEnumerate::new(ByteLines::new(reader).enumerate());
+
+            let mut bvh = Bvh::default();
+            bvh.read_joints(&mut lines, &ParseOptions::new(), &mut Vec::new())?;
+            let num_frames = bvh.read_motion_header(&mut lines, None)?;
+
+            Ok(BvhMetadata {
+                num_joints: bvh.joints.len(),
+                num_channels: bvh.num_channels,
+                num_frames,
+                frame_time: bvh.frame_time,
+            })
+        }
+
+        metadata_from_reader_(reader)
+    }
+}
+
+/// Read a `Bvh` from the lines of `reader`. If `stop_after_last_frame` is `true`,
+/// then no lines are read after the last frame given by the `Frames` line.
+#[inline(never)]
+fn read_lines(
+    options: &ParseOptions,
+    reader: &mut dyn BufReadExt,
+    stop_after_last_frame: bool,
+) -> Result<(Bvh, Vec<Diagnostic>), LoadError> {
+    let mut lines = CachedEnumerate::new(ByteLines::new(reader).enumerate());
+
+    let mut bvh = Bvh::default();
+    let mut diagnostics = Vec::new();
+
+    bvh.read_joints(&mut lines, options, &mut diagnostics)?;
+    bvh.read_motion(&mut lines, options, &mut diagnostics, stop_after_last_frame)?;
+
+    Ok((bvh, diagnostics))
+}
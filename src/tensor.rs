@@ -0,0 +1,819 @@
+use crate::{
+    errors::{BinaryFrameError, JointIndexError, NormalizedFrameError, QuaternionTensorError},
+    joint::{JointData, Offset},
+    math::{self, Mat, Quat},
+    Axis, Bvh,
+};
+use std::fmt;
+
+/// The pose of every joint of a `Bvh` in a single frame, with rotations as
+/// quaternions, returned by [`Bvh::quat_poses`][`Bvh::quat_poses`].
+///
+/// Rotations are computed when they are requested, so only the joints which are
+/// used cost anything.
+///
+/// [`Bvh::quat_poses`]: struct.Bvh.html#method.quat_poses
+#[derive(Clone, Copy)]
+pub struct QuatPose<'a> {
+    joints: &'a [JointData],
+    motion: &'a [f32],
+}
+
+impl QuatPose<'_> {
+    /// Returns the rotation of the joint at index `joint` relative to its parent as
+    /// a quaternion, stored as described in the [`math`] module.
+    ///
+    /// This is the composition of the joint's rotation channels in the order in
+    /// which they are listed, and is the identity if it has no rotation channels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `joint` is out of range.
+    ///
+    /// [`math`]: math/index.html
+    #[inline]
+    pub fn rotation_quat(&self, joint: usize) -> Quat {
+        joint_rotation(&self.joints[joint], self.motion)
+    }
+
+    /// Returns the values of the root joint's position channels. Missing position
+    /// channels are `0.0`.
+    #[inline]
+    pub fn root_translation(&self) -> Offset {
+        root_translation_of(self.joints, self.motion)
+    }
+
+    /// Returns the number of joints in the pose.
+    #[inline]
+    pub fn num_joints(&self) -> usize {
+        self.joints.len()
+    }
+}
+
+impl fmt::Debug for QuatPose<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("QuatPose { .. }")
+    }
+}
+
+/// How the quaternions of a binary frame are stored, used by
+/// [`Bvh::encode_binary_frame`][`Bvh::encode_binary_frame`].
+///
+/// [`Bvh::encode_binary_frame`]: struct.Bvh.html#method.encode_binary_frame
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum QuatEncoding {
+    /// Each component is stored as an `f32`, so the quaternion takes 16 bytes.
+    F32,
+    /// Each component is scaled by `32767` and rounded to an `i16`, so the
+    /// quaternion takes 8 bytes, and each component is accurate to within about
+    /// `1.5e-5`.
+    I16,
+}
+
+impl QuatEncoding {
+    /// The first byte of a binary frame which uses this encoding.
+    const fn tag(self) -> u8 {
+        match self {
+            QuatEncoding::F32 => 0,
+            QuatEncoding::I16 => 1,
+        }
+    }
+
+    /// Returns the encoding which is named by `tag`, if there is one.
+    const fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(QuatEncoding::F32),
+            1 => Some(QuatEncoding::I16),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes taken by each quaternion.
+    const fn quat_len(self) -> usize {
+        match self {
+            QuatEncoding::F32 => 16,
+            QuatEncoding::I16 => 8,
+        }
+    }
+}
+
+impl Bvh {
+    /// Returns the rotation of every joint in every frame as quaternions, along with
+    /// the translation of the root joint in every frame, as flat arrays suitable for
+    /// machine learning pipelines.
+    ///
+    /// The first element of the returned tuple contains the quaternions in the
+    /// shape given by the second element, which is `[frames, joints, 4]`. Each
+    /// quaternion is stored as `[x, y, z, w]`, as described in the [`math`] module.
+    /// A joint's quaternion is the composition of its rotation channels in the order
+    /// in which they are listed; joints without rotation channels have the identity
+    /// rotation.
+    ///
+    /// The third element contains the values of the root joint's position channels
+    /// in the shape `[frames, 3]`. Missing position channels are `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0 3.0 0.0 0.0 0.0
+    /// };
+    ///
+    /// let (quats, shape, root_translation) = bvh.to_quaternion_tensor();
+    /// assert_eq!(shape, [1, 2, 4]);
+    /// assert_eq!(quats, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+    /// assert_eq!(root_translation, [1.0, 2.0, 3.0]);
+    /// ```
+    ///
+    /// [`math`]: math/index.html
+    pub fn to_quaternion_tensor(&self) -> (Vec<f32>, [usize; 3], Vec<f32>) {
+        let shape = [self.frames().len(), self.joints.len(), 4];
+        let mut quats = Vec::with_capacity(shape.iter().product());
+        let mut root_translation = Vec::with_capacity(shape[0] * 3);
+
+        for frame in self.frames() {
+            let motion = frame.as_slice();
+            for joint in &self.joints {
+                quats.extend_from_slice(&joint_rotation(joint, motion));
+            }
+
+            root_translation.extend_from_slice(&root_translation_of(&self.joints, motion));
+        }
+
+        (quats, shape, root_translation)
+    }
+
+    /// Returns an iterator over the pose of every frame, with the rotation of each
+    /// joint as a quaternion, such as to drive a skeleton in a game engine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0 3.0 0.0 0.0 0.0
+    ///     4.0 5.0 6.0 0.0 0.0 0.0
+    /// };
+    ///
+    /// let poses = bvh.quat_poses().collect::<Vec<_>>();
+    /// assert_eq!(poses.len(), 2);
+    /// assert_eq!(poses[1].root_translation(), [4.0, 5.0, 6.0]);
+    /// assert_eq!(poses[1].rotation_quat(1), [0.0, 0.0, 0.0, 1.0]);
+    /// ```
+    #[inline]
+    pub fn quat_poses(&self) -> impl Iterator<Item = QuatPose<'_>> + '_ {
+        let num_channels = self.num_channels;
+        (0..self.frames().len()).map(move |frame| QuatPose {
+            joints: &self.joints,
+            motion: &self.motion_values[frame * num_channels..][..num_channels],
+        })
+    }
+
+    /// Returns the pose of the frame at index `frame` as a flat array of the root
+    /// translation followed by the rotation of every joint, such as to feed a
+    /// model which is trained on a fixed skeleton.
+    ///
+    /// The first 3 values are the values of the root joint's position channels, as
+    /// returned by [`QuatPose::root_translation`]. They are followed by 4 values
+    /// for each joint in joint order, which is depth first, containing its
+    /// rotation as a quaternion stored as described in the [`math`] module. The
+    /// length of the array is therefore `3 + 4 * num_joints`. Values of other
+    /// channels are not included, so [`Bvh::apply_normalized_frame_vec`] needs a
+    /// `Bvh` with the same skeleton to restore them.
+    ///
+    /// Returns `None` if there is no frame at index `frame`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0 3.0 0.0 0.0 0.0
+    /// };
+    ///
+    /// let mut values = bvh.to_normalized_frame_vec(0).unwrap();
+    /// assert_eq!(values, [1.0, 2.0, 3.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+    /// assert!(bvh.to_normalized_frame_vec(1).is_none());
+    ///
+    /// values[0] = 4.0;
+    /// bvh.apply_normalized_frame_vec(0, &values)?;
+    /// assert_eq!(bvh.frame_values(0).unwrap(), &[4.0, 2.0, 3.0, 0.0, 0.0, 0.0]);
+    /// # Result::<(), bvh_anim::errors::NormalizedFrameError>::Ok(())
+    /// ```
+    ///
+    /// [`QuatPose::root_translation`]: struct.QuatPose.html#method.root_translation
+    /// [`math`]: math/index.html
+    /// [`Bvh::apply_normalized_frame_vec`]: struct.Bvh.html#method.apply_normalized_frame_vec
+    pub fn to_normalized_frame_vec(&self, frame: usize) -> Option<Vec<f32>> {
+        let motion = self.frame_values(frame)?;
+        let mut values = Vec::with_capacity(3 + 4 * self.joints.len());
+        values.extend_from_slice(&root_translation_of(&self.joints, motion));
+        for joint in &self.joints {
+            values.extend_from_slice(&joint_rotation(joint, motion));
+        }
+        Some(values)
+    }
+
+    /// Set the frame at index `frame` to the pose given by `values`, in the layout
+    /// returned by [`Bvh::to_normalized_frame_vec`].
+    ///
+    /// The values of the root joint's position channels are set to the root
+    /// translation, and each quaternion is converted back into the joint's rotation
+    /// channels, as in [`Bvh::from_quaternion_tensor`]. The values of all other
+    /// channels are left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no frame at index `frame`, or if the length of
+    /// `values` is not `3 + 4 * num_joints`, in which case the `Bvh` is not changed.
+    ///
+    /// [`Bvh::to_normalized_frame_vec`]: struct.Bvh.html#method.to_normalized_frame_vec
+    /// [`Bvh::from_quaternion_tensor`]: struct.Bvh.html#method.from_quaternion_tensor
+    pub fn apply_normalized_frame_vec(
+        &mut self,
+        frame: usize,
+        values: &[f32],
+    ) -> Result<(), NormalizedFrameError> {
+        let expected = 3 + 4 * self.joints.len();
+        if values.len() != expected {
+            return Err(NormalizedFrameError::Length {
+                expected,
+                actual: values.len(),
+            });
+        }
+
+        let num_frames = self.num_frames();
+        if frame >= num_frames {
+            return Err(NormalizedFrameError::FrameOutOfRange {
+                index: frame,
+                num_frames,
+            });
+        }
+        let num_channels = self.num_channels;
+        let motion = &mut self.motion_values[frame * num_channels..][..num_channels];
+
+        let (translation, quats) = values.split_at(3);
+        for (joint, quat) in self.joints.iter().zip(quats.chunks_exact(4)) {
+            set_joint_rotation(joint, [quat[0], quat[1], quat[2], quat[3]], motion);
+        }
+        let translation = [translation[0], translation[1], translation[2]];
+        set_root_translation_of(&self.joints, translation, motion);
+        Ok(())
+    }
+
+    /// Set the frame at index `frame` to `pose`, such as a pose which was edited
+    /// outside of the `Bvh`. This is the counterpart of [`Bvh::quat_poses`].
+    ///
+    /// `pose` must have one joint for each joint of the `Bvh`, such as a pose of a
+    /// clip with the same skeleton. The frame is set as with
+    /// [`Bvh::apply_normalized_frame_vec`], so each quaternion is converted back
+    /// into the joint's rotation channels in its rotation order, the root's
+    /// position channels are set to the pose's root translation, and all other
+    /// channels are left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no frame at index `frame`, or if `pose` has a
+    /// different number of joints, in which case the `Bvh` is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::bvh;
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0 3.0 0.0 0.0 0.0
+    ///     4.0 5.0 6.0 0.0 0.0 0.0
+    /// };
+    ///
+    /// let source = bvh.clone();
+    /// let pose = source.quat_poses().nth(1).unwrap();
+    /// bvh.set_frame_from_quat_pose(0, &pose)?;
+    /// assert_eq!(bvh.frame_values(0), bvh.frame_values(1));
+    /// # Result::<(), bvh_anim::errors::NormalizedFrameError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::quat_poses`]: struct.Bvh.html#method.quat_poses
+    /// [`Bvh::apply_normalized_frame_vec`]: struct.Bvh.html#method.apply_normalized_frame_vec
+    pub fn set_frame_from_quat_pose(
+        &mut self,
+        frame: usize,
+        pose: &QuatPose<'_>,
+    ) -> Result<(), NormalizedFrameError> {
+        let num_joints = pose.num_joints();
+        if num_joints != self.joints.len() {
+            return Err(NormalizedFrameError::JointCount {
+                expected: self.joints.len(),
+                actual: num_joints,
+            });
+        }
+
+        let mut values = Vec::with_capacity(3 + 4 * num_joints);
+        values.extend_from_slice(&pose.root_translation());
+        for joint in 0..num_joints {
+            values.extend_from_slice(&pose.rotation_quat(joint));
+        }
+        self.apply_normalized_frame_vec(frame, &values)
+    }
+
+    /// Encodes the root translation and the rotations of the `joints` at index
+    /// `frame` into a compact buffer, such as to stream a pose over a network. The
+    /// pose is decoded with [`Bvh::decode_binary_frame`].
+    ///
+    /// All values are little-endian, and the buffer is laid out as:
+    ///
+    /// 1. One byte naming the `encoding`: `0` for [`QuatEncoding::F32`], or `1` for
+    ///    [`QuatEncoding::I16`].
+    /// 2. The root translation as three `f32`s, as returned by
+    ///    [`QuatPose::root_translation`].
+    /// 3. For each joint in `joints`, in order, its rotation relative to its parent
+    ///    as a quaternion stored as `[x, y, z, w]`, with each component encoded as
+    ///    described by `encoding`.
+    ///
+    /// The joint indices are not stored, so the decoder must be given the same
+    /// `joints`.
+    ///
+    /// Returns `None` if there is no frame at index `frame`, or if any index in
+    /// `joints` is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, QuatEncoding};
+    /// let mut bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     1.0 2.0 3.0 0.0 0.0 0.0
+    ///     0.0 0.0 0.0 0.0 0.0 0.0
+    /// };
+    ///
+    /// let bytes = bvh.encode_binary_frame(0, &[1], QuatEncoding::I16).unwrap();
+    /// assert_eq!(bytes.len(), 1 + 12 + 8);
+    ///
+    /// bvh.decode_binary_frame(1, &[1], &bytes)?;
+    /// assert_eq!(bvh.frame_values(1).unwrap(), &[1.0, 2.0, 3.0, 0.0, 0.0, 0.0]);
+    /// # Result::<(), bvh_anim::errors::BinaryFrameError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::decode_binary_frame`]: struct.Bvh.html#method.decode_binary_frame
+    /// [`QuatEncoding::F32`]: enum.QuatEncoding.html#variant.F32
+    /// [`QuatEncoding::I16`]: enum.QuatEncoding.html#variant.I16
+    /// [`QuatPose::root_translation`]: struct.QuatPose.html#method.root_translation
+    pub fn encode_binary_frame(
+        &self,
+        frame: usize,
+        joints: &[usize],
+        encoding: QuatEncoding,
+    ) -> Option<Vec<u8>> {
+        let motion = self.frame_values(frame)?;
+        let mut bytes = Vec::with_capacity(1 + 12 + encoding.quat_len() * joints.len());
+        bytes.push(encoding.tag());
+        for value in &root_translation_of(&self.joints, motion) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for &joint in joints {
+            let quat = joint_rotation(self.joints.get(joint)?, motion);
+            for component in &quat {
+                match encoding {
+                    QuatEncoding::F32 => bytes.extend_from_slice(&component.to_le_bytes()),
+                    QuatEncoding::I16 => {
+                        let quantized = (component.clamp(-1.0, 1.0) * f32::from(i16::MAX)).round();
+                        bytes.extend_from_slice(&(quantized as i16).to_le_bytes());
+                    }
+                }
+            }
+        }
+        Some(bytes)
+    }
+
+    /// Set the root translation and the rotations of the `joints` at index `frame`
+    /// from a buffer written by [`Bvh::encode_binary_frame`] with the same `joints`.
+    ///
+    /// The root translation is written to the root joint's position channels, and
+    /// each quaternion is normalized and converted back into the joint's rotation
+    /// channels, as in [`Bvh::set_frame_from_quat_pose`]. The channels of the joints
+    /// which are not in `joints` are left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no frame at index `frame`, if any index in
+    /// `joints` is out of range, or if `bytes` does not start with a known encoding
+    /// or has the wrong length for it. In that case, the `Bvh` is not changed.
+    ///
+    /// [`Bvh::encode_binary_frame`]: struct.Bvh.html#method.encode_binary_frame
+    /// [`Bvh::set_frame_from_quat_pose`]: struct.Bvh.html#method.set_frame_from_quat_pose
+    pub fn decode_binary_frame(
+        &mut self,
+        frame: usize,
+        joints: &[usize],
+        bytes: &[u8],
+    ) -> Result<(), BinaryFrameError> {
+        let num_frames = self.num_frames();
+        if frame >= num_frames {
+            return Err(BinaryFrameError::FrameOutOfRange {
+                index: frame,
+                num_frames,
+            });
+        }
+        let num_joints = self.joints.len();
+        if let Some(&joint) = joints.iter().find(|&&joint| joint >= num_joints) {
+            return Err(BinaryFrameError::JointOutOfRange(
+                JointIndexError::out_of_range(joint, num_joints),
+            ));
+        }
+
+        let (&tag, rest) = bytes.split_first().ok_or(BinaryFrameError::Length {
+            expected: 1,
+            actual: 0,
+        })?;
+        let encoding =
+            QuatEncoding::from_tag(tag).ok_or(BinaryFrameError::UnknownEncoding { tag })?;
+        let expected = 1 + 12 + encoding.quat_len() * joints.len();
+        if bytes.len() != expected {
+            return Err(BinaryFrameError::Length {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+
+        let f32_at = |bytes: &[u8]| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let (translation, quats) = rest.split_at(12);
+        let translation = [
+            f32_at(&translation[0..]),
+            f32_at(&translation[4..]),
+            f32_at(&translation[8..]),
+        ];
+
+        let num_channels = self.num_channels;
+        let motion = &mut self.motion_values[frame * num_channels..][..num_channels];
+        for (&joint, quat) in joints.iter().zip(quats.chunks_exact(encoding.quat_len())) {
+            let mut components = [0.0; 4];
+            for (index, component) in components.iter_mut().enumerate() {
+                *component = match encoding {
+                    QuatEncoding::F32 => f32_at(&quat[index * 4..]),
+                    QuatEncoding::I16 => {
+                        let quantized = i16::from_le_bytes([quat[index * 2], quat[index * 2 + 1]]);
+                        f32::from(quantized) / f32::from(i16::MAX)
+                    }
+                };
+            }
+            set_joint_rotation(&self.joints[joint], components, motion);
+        }
+        set_root_translation_of(&self.joints, translation, motion);
+        Ok(())
+    }
+
+    /// Returns the rotation of the joint at index `joint` in every frame as a
+    /// quaternion, stored as described in the [`math`] module, such as for export to
+    /// quaternion based formats.
+    ///
+    /// `q` and `-q` represent the same rotation, but interpolating between
+    /// quaternions in opposite hemispheres takes the long way around. The sign of
+    /// each quaternion is chosen so that its dot product with the previous one is
+    /// never negative, so that the series is continuous.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, math};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Zrotation
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     170.0
+    ///     -170.0
+    /// };
+    ///
+    /// let series = bvh.quaternion_series(0)?;
+    /// assert!(math::quat_dot(series[0], series[1]) > 0.0);
+    /// assert!(bvh.quaternion_series(2).is_err());
+    /// # Result::<(), bvh_anim::errors::JointIndexError>::Ok(())
+    /// ```
+    ///
+    /// [`math`]: math/index.html
+    pub fn quaternion_series(&self, joint: usize) -> Result<Vec<Quat>, JointIndexError> {
+        let joint = self
+            .joints
+            .get(joint)
+            .ok_or_else(|| JointIndexError::out_of_range(joint, self.joints.len()))?;
+
+        let mut series: Vec<Quat> = Vec::with_capacity(self.frames().len());
+        for frame in self.frames() {
+            let mut quat = joint_rotation(joint, frame.as_slice());
+            if let Some(&previous) = series.last() {
+                if math::quat_dot(previous, quat) < 0.0 {
+                    quat = [-quat[0], -quat[1], -quat[2], -quat[3]];
+                }
+            }
+            series.push(quat);
+        }
+        Ok(series)
+    }
+
+    /// Create a new `Bvh` with the skeleton of `template`, whose motion is given by
+    /// a quaternion tensor in the format returned by
+    /// [`Bvh::to_quaternion_tensor`].
+    ///
+    /// `quats` contains a quaternion for every joint of `template` in every frame,
+    /// in the given `shape`, which must be `[frames, joints, 4]`. `root_translation`
+    /// contains the values of the root's position channels in every frame, in the
+    /// shape `[frames, 3]`.
+    ///
+    /// Each quaternion is converted back into the joint's rotation channels, in the
+    /// joint's rotation order. A joint with fewer than three rotation channels
+    /// cannot represent every rotation, so only the part of the rotation about its
+    /// axes is kept. The values of all other channels are copied from the first
+    /// frame of `template`, or set to `0.0` if `template` has no frames. The frame
+    /// time is copied from `template`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `template` has no joints, if `shape` does not match the
+    /// skeleton of `template`, or if the length of `quats` or `root_translation`
+    /// does not match `shape`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, Bvh};
+    /// let template = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 3 Zrotation Xrotation Yrotation
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let quats = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+    /// let bvh = Bvh::from_quaternion_tensor(&template, &quats, [1, 2, 4], &[1.0, 2.0, 3.0])?;
+    /// assert_eq!(bvh.frames().next().unwrap().as_slice(), &[1.0, 2.0, 3.0, 0.0, 0.0, 0.0]);
+    /// # Result::<(), bvh_anim::errors::QuaternionTensorError>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::to_quaternion_tensor`]: struct.Bvh.html#method.to_quaternion_tensor
+    pub fn from_quaternion_tensor(
+        template: &Bvh,
+        quats: &[f32],
+        shape: [usize; 3],
+        root_translation: &[f32],
+    ) -> Result<Bvh, QuaternionTensorError> {
+        let [num_frames, num_joints, quat_len] = shape;
+        if num_joints == 0 || num_joints != template.joints.len() || quat_len != 4 {
+            return Err(QuaternionTensorError::ShapeMismatch {
+                shape,
+                num_joints: template.joints.len(),
+            });
+        }
+
+        let expected = shape.iter().product();
+        if quats.len() != expected {
+            return Err(QuaternionTensorError::QuaternionsLength {
+                expected,
+                actual: quats.len(),
+            });
+        }
+        if root_translation.len() != num_frames * 3 {
+            return Err(QuaternionTensorError::RootTranslationLength {
+                expected: num_frames * 3,
+                actual: root_translation.len(),
+            });
+        }
+
+        let num_channels = template.num_channels;
+        let default_frame = match template.frames().next() {
+            Some(frame) => frame.as_slice().to_vec(),
+            None => vec![0.0; num_channels],
+        };
+
+        let mut motion_values = Vec::with_capacity(num_frames * num_channels);
+        for (frame_quats, translation) in quats
+            .chunks_exact(num_joints * 4)
+            .zip(root_translation.chunks_exact(3))
+        {
+            let mut frame = default_frame.clone();
+            for (joint, quat) in template.joints.iter().zip(frame_quats.chunks_exact(4)) {
+                set_joint_rotation(joint, [quat[0], quat[1], quat[2], quat[3]], &mut frame);
+
+                if joint.is_root() {
+                    for channel in joint.channels() {
+                        let ty = channel.channel_type();
+                        if ty.is_position() {
+                            frame[channel.motion_index()] = translation[ty.axis() as usize];
+                        }
+                    }
+                }
+            }
+            motion_values.extend_from_slice(&frame);
+        }
+
+        Ok(Bvh {
+            joints: template.joints.clone(),
+            motion_values,
+            num_channels,
+            frame_time: template.frame_time,
+            ..Bvh::new()
+        })
+    }
+}
+
+/// Returns the composition of the rotation channels of `joint` using the channel
+/// values in `motion`.
+pub(crate) fn joint_rotation(joint: &JointData, motion: &[f32]) -> Quat {
+    let rotation = joint
+        .channels()
+        .iter()
+        .filter(|channel| channel.channel_type().is_rotation())
+        .fold(math::IDENTITY, |m: Mat, channel| {
+            let rotation = math::mat_rotation(
+                channel.channel_type().axis(),
+                motion[channel.motion_index()],
+            );
+            math::mat_mul(&m, &rotation)
+        });
+    math::mat_to_quat(&rotation)
+}
+
+/// Returns the values of the position channels of the first root joint in
+/// `joints` using the channel values in `motion`.
+fn root_translation_of(joints: &[JointData], motion: &[f32]) -> Offset {
+    let mut translation = [0.0; 3];
+    if let Some(root) = joints.iter().find(|joint| joint.is_root()) {
+        for channel in root.channels() {
+            let ty = channel.channel_type();
+            if ty.is_position() {
+                translation[ty.axis() as usize] = motion[channel.motion_index()];
+            }
+        }
+    }
+    translation
+}
+
+/// Set the values of the position channels of the first root joint in `joints` in
+/// `motion` to `translation`.
+fn set_root_translation_of(joints: &[JointData], translation: Offset, motion: &mut [f32]) {
+    if let Some(root) = joints.iter().find(|joint| joint.is_root()) {
+        for channel in root.channels() {
+            let ty = channel.channel_type();
+            if ty.is_position() {
+                motion[channel.motion_index()] = translation[ty.axis() as usize];
+            }
+        }
+    }
+}
+
+/// Set the rotation channels of `joint` in `motion` to the values which represent
+/// `quat`, in the joint's rotation order.
+fn set_joint_rotation(joint: &JointData, quat: Quat, motion: &mut [f32]) {
+    let mut order = Vec::with_capacity(3);
+    for channel in joint.channels() {
+        let ty = channel.channel_type();
+        if ty.is_rotation() && !order.contains(&ty.axis()) {
+            order.push(ty.axis());
+        }
+    }
+    if order.is_empty() {
+        return;
+    }
+    for &axis in &[Axis::X, Axis::Y, Axis::Z] {
+        if !order.contains(&axis) {
+            order.push(axis);
+        }
+    }
+
+    let order = [order[0], order[1], order[2]];
+    let angles = math::mat_to_euler(&math::quat_to_mat(math::quat_normalize(quat)), order);
+
+    let mut written = [false; 3];
+    for channel in joint.channels() {
+        let ty = channel.channel_type();
+        if !ty.is_rotation() {
+            continue;
+        }
+        let axis = ty.axis() as usize;
+        motion[channel.motion_index()] = if written[axis] {
+            0.0
+        } else {
+            written[axis] = true;
+            let position = order.iter().position(|&a| a == ty.axis()).unwrap_or(0);
+            angles[position]
+        };
+    }
+}
@@ -1,16 +1,68 @@
 //! Contains options for `bvh` file formatting.
 
-use crate::{frames::Frames, Bvh, Joint, Joints};
+use crate::{frames::Frames, Bvh, Channel, ChannelType, Joint, Joints};
+use bstr::{BString, ByteSlice};
 use smallvec::SmallVec;
 use std::{
+    convert::TryFrom,
     fmt,
-    io::{self, Write},
+    io::{self, Seek, SeekFrom, Write},
     iter, mem,
     num::NonZeroUsize,
+    ops::Range,
 };
 
 /// Specify formatting options for writing a `Bvh`.
+///
+/// Numbers are always written with `.` as the decimal separator and without any
+/// digit grouping, regardless of the locale of the system, so that the output can
+/// be read anywhere.
+///
+/// Every option has a corresponding `with_*` method which returns the modified
+/// `WriteOptions`, so that options can be chained starting from
+/// [`WriteOptions::new`][`WriteOptions::new`] or `WriteOptions::default()`.
+///
+/// # Examples
+///
+/// ```
+/// # use bvh_anim::{bvh, write::{IndentStyle, LineTerminator, WriteOptions}};
+/// let bvh = bvh! {
+///     HIERARCHY
+///     ROOT Hips
+///     {
+///         OFFSET 0.0 0.0 0.0
+///         CHANNELS 3 Xposition Yposition Zposition
+///         JOINT Chest
+///         {
+///             OFFSET 0.0 5.0 0.0
+///             CHANNELS 0
+///             End Site
+///             {
+///                 OFFSET 0.0 5.0 0.0
+///             }
+///         }
+///     }
+///     MOTION
+///     Frames: 1
+///     Frame Time: 0.033333333
+///     0.0 1.0 2.0
+/// };
+///
+/// let options = WriteOptions::new()
+///     .with_indent(IndentStyle::with_spaces(2))
+///     .with_line_terminator(LineTerminator::Unix)
+///     .with_offset_significant_figures(1)
+///     .with_motion_values_significant_figures(2)
+///     .with_max_line_bytes(None);
+///
+/// let written = options.write_to_string(&bvh);
+/// assert!(written.starts_with(b"HIERARCHY\nROOT Hips\n{\n  OFFSET 0.0 0.0 0.0\n"));
+/// assert!(written.ends_with(b"0.00 1.00 2.00\n"));
+/// ```
+///
+/// [`WriteOptions::new`]: struct.WriteOptions.html#method.new
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
 pub struct WriteOptions {
     /// Which indentation style to use for nested bones.
     pub indent: IndentStyle,
@@ -28,8 +80,56 @@ pub struct WriteOptions {
     ///
     /// If this is `None`, then the minimum precision required will be used
     pub motion_values_significant_figures: Option<usize>,
-    #[doc(hidden)]
-    _nonexhaustive: (),
+    /// The maximum number of bytes to write on a single line of the `MOTION` section,
+    /// not including the line terminator.
+    ///
+    /// If a frame does not fit on a single line, then its values are wrapped onto the
+    /// following lines, so that a reader must keep collecting values until it has read
+    /// one value for each channel. A single value longer than the limit is written on
+    /// its own line.
+    ///
+    /// If this is `None`, then each frame is written on a single line.
+    pub max_line_bytes: Option<usize>,
+    /// The bytes to write between the values of a frame in the `MOTION` section,
+    /// such as a tab for tools which expect tab separated values. The `HIERARCHY`
+    /// section is not affected.
+    ///
+    /// If this is `None`, then a single space is used.
+    pub value_separator: Option<BString>,
+    /// The order in which to write the channels of each joint.
+    ///
+    /// This reorders both the `CHANNELS` line of each joint and the matching
+    /// columns of the `MOTION` section, without modifying the `Bvh`. The channels
+    /// of a joint are applied in the order in which they are listed, so they are
+    /// only reordered if that does not change the pose, such as when moving a
+    /// translation along an axis past a rotation about the same axis. The channels
+    /// of any other joint are written in the order in which they are stored.
+    /// Channels of the same kind are never reordered relative to each other.
+    pub channel_order: ChannelOrder,
+    /// Whether to reproduce the original text of a `Bvh` parsed with
+    /// [`Bvh::parse_preserving`], only reformatting the motion values which have
+    /// been changed. Other `Bvh`s are written as normal.
+    ///
+    /// The original text is not reproduced if `channel_order` is not
+    /// `ChannelOrder::AsStored`, or if the `Bvh` has been changed too much. In that
+    /// case, the `Bvh` is written as normal, except that the indentation detected
+    /// in the original text is used instead of `indent`. See
+    /// [`Bvh::detected_indent_style`].
+    ///
+    /// [`Bvh::parse_preserving`]: ../struct.Bvh.html#method.parse_preserving
+    /// [`Bvh::detected_indent_style`]: ../struct.Bvh.html#method.detected_indent_style
+    pub preserve_formatting: bool,
+    /// Whether to write the metadata of the `Bvh` as `# key: value` comments before
+    /// the `HIERARCHY` keyword, so that they can be read back with
+    /// [`ParseOptions::with_metadata_comments`]. See [`Bvh::metadata`].
+    ///
+    /// Comments are not part of the standard bvh format, so this is disabled by
+    /// default. A `Bvh` which is written from its preserved source is written as it
+    /// was parsed, unless its metadata has changed since parsing.
+    ///
+    /// [`ParseOptions::with_metadata_comments`]: ../read/struct.ParseOptions.html#method.with_metadata_comments
+    /// [`Bvh::metadata`]: ../struct.Bvh.html#method.metadata
+    pub metadata_comments: bool,
 }
 
 impl Default for WriteOptions {
@@ -49,25 +149,36 @@ impl WriteOptions {
             offset_significant_figures: None,
             frame_time_significant_figures: None,
             motion_values_significant_figures: None,
-            _nonexhaustive: (),
+            max_line_bytes: None,
+            value_separator: None,
+            channel_order: ChannelOrder::AsStored,
+            preserve_formatting: false,
+            metadata_comments: false,
         }
     }
 
     /// Output the `Bvh` file to the `writer` with the given options.
     pub fn write<W: Write>(&self, bvh: &Bvh, writer: &mut W) -> io::Result<()> {
+        if let Some(options) = self.with_detected_indent(bvh) {
+            return options.write(bvh, writer);
+        }
+        if let Some(preserved) = self.preserved(bvh) {
+            writer.write_all(&preserved)?;
+            return writer.flush();
+        }
+
         let mut curr_chunk = vec![];
         let mut curr_bytes_written = 0usize;
         let mut curr_string_len = 0usize;
         let mut iter_state = WriteOptionsIterState::new();
 
-        while self.next_chunk(bvh, &mut curr_chunk, &mut iter_state) != false {
+        while self.next_chunk(bvh, &mut curr_chunk, &mut iter_state) {
             let bytes: &[u8] = curr_chunk.as_ref();
             curr_string_len += bytes.len();
             curr_bytes_written += writer.write(bytes)?;
 
             if curr_bytes_written != curr_string_len {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
+                return Err(io::Error::other(
                     "Data has been dropped while writing to file",
                 ));
             }
@@ -76,27 +187,410 @@ impl WriteOptions {
     }
 
     /// Output the `Bvh` file to the `string` with the given options.
+    #[inline]
     pub fn write_to_string(&self, bvh: &Bvh) -> Vec<u8> {
+        self.write_to_vec(bvh)
+    }
+
+    /// Output the `Bvh` file to a new `Vec<u8>` with the given options, such as to
+    /// fill a buffer for a file or a network connection.
+    ///
+    /// The output is identical to [`WriteOptions::write_to_string`]. Space for the
+    /// output is reserved up front using [`WriteOptions::estimated_size`], so that
+    /// the buffer rarely has to grow while it is written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, write::WriteOptions};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         End Site
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    ///
+    /// let bytes = WriteOptions::new().write_to_vec(&bvh);
+    /// assert!(bytes.starts_with(b"HIERARCHY"));
+    /// ```
+    ///
+    /// [`WriteOptions::write_to_string`]: struct.WriteOptions.html#method.write_to_string
+    /// [`WriteOptions::estimated_size`]: struct.WriteOptions.html#method.estimated_size
+    pub fn write_to_vec(&self, bvh: &Bvh) -> Vec<u8> {
+        if let Some(options) = self.with_detected_indent(bvh) {
+            return options.write_to_vec(bvh);
+        }
+        if let Some(preserved) = self.preserved(bvh) {
+            return preserved;
+        }
+
         let mut curr_chunk = vec![];
-        let mut out_string = vec![];
+        let mut out = Vec::with_capacity(self.estimated_size(bvh));
         let mut iter_state = WriteOptionsIterState::new();
 
-        while self.next_chunk(bvh, &mut curr_chunk, &mut iter_state) != false {
-            out_string.extend(curr_chunk.drain(..));
+        while self.next_chunk(bvh, &mut curr_chunk, &mut iter_state) {
+            out.extend_from_slice(&curr_chunk);
         }
 
-        out_string
+        out
+    }
+
+    /// Compare the output which would be written for the `Bvh` with the bytes of
+    /// the `original` file line by line, without writing anything, such as to
+    /// preview the changes before saving over the file.
+    ///
+    /// The lines which are the same at the start and at the end of both are
+    /// skipped, and the remaining lines are paired up in order: lines which differ
+    /// are [`LineDiff::Changed`], and any extra lines are [`LineDiff::Removed`] or
+    /// [`LineDiff::Added`]. Differences in line terminators alone are not reported.
+    /// If the output is the same as `original`, then the result is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, write::{LineDiff, WriteOptions}};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.5 1.0 2.0
+    /// };
+    ///
+    /// let original = WriteOptions::new().write_to_vec(&bvh);
+    /// let options = WriteOptions::new().with_motion_values_significant_figures(1);
+    /// assert_eq!(
+    ///     options.diff_preview(&bvh, &original),
+    ///     [LineDiff::Changed {
+    ///         line: 19,
+    ///         original: "0.5 1 2".into(),
+    ///         written: "0.5 1.0 2.0".into(),
+    ///     }]
+    /// );
+    /// ```
+    ///
+    /// [`LineDiff::Changed`]: enum.LineDiff.html#variant.Changed
+    /// [`LineDiff::Removed`]: enum.LineDiff.html#variant.Removed
+    /// [`LineDiff::Added`]: enum.LineDiff.html#variant.Added
+    pub fn diff_preview(&self, bvh: &Bvh, original: &[u8]) -> Vec<LineDiff> {
+        let written = self.write_to_vec(bvh);
+        let original = original.lines().collect::<Vec<_>>();
+        let written = written.lines().collect::<Vec<_>>();
+
+        let prefix = original
+            .iter()
+            .zip(&written)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = original[prefix..]
+            .iter()
+            .rev()
+            .zip(written[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let original = &original[prefix..original.len() - suffix];
+        let written = &written[prefix..written.len() - suffix];
+
+        let mut diffs = vec![];
+        for index in 0..original.len().max(written.len()) {
+            let line = prefix + index + 1;
+            match (original.get(index), written.get(index)) {
+                (Some(a), Some(b)) if a == b => {}
+                (Some(a), Some(b)) => diffs.push(LineDiff::Changed {
+                    line,
+                    original: BString::from(*a),
+                    written: BString::from(*b),
+                }),
+                (Some(a), None) => diffs.push(LineDiff::Removed {
+                    line,
+                    original: BString::from(*a),
+                }),
+                (None, Some(b)) => diffs.push(LineDiff::Added {
+                    line,
+                    written: BString::from(*b),
+                }),
+                (None, None) => {}
+            }
+        }
+        diffs
+    }
+
+    /// Returns an estimate of the number of bytes which would be written for the
+    /// `Bvh` with the given options, such as to pre-allocate a buffer or to show the
+    /// progress of writing.
+    ///
+    /// The hierarchy is measured exactly. The motion section is estimated from the
+    /// written size of up to 16 evenly spaced frames, so the estimate is exact for
+    /// short clips, and close for longer clips unless the width of the values
+    /// changes a lot over the clip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, write::WriteOptions};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         End Site
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 2
+    ///     Frame Time: 0.033333333
+    ///     0.0 1.0 2.0
+    ///     3.0 4.0 5.0
+    /// };
+    ///
+    /// let options = WriteOptions::new();
+    /// assert_eq!(options.estimated_size(&bvh), options.write_to_vec(&bvh).len());
+    /// ```
+    pub fn estimated_size(&self, bvh: &Bvh) -> usize {
+        const SAMPLE_FRAMES: usize = 16;
+
+        if let Some(options) = self.with_detected_indent(bvh) {
+            return options.estimated_size(bvh);
+        }
+        if self.preserve_formatting && self.channel_order == ChannelOrder::AsStored {
+            if let Some(len) = bvh.preserved_len() {
+                return len;
+            }
+        }
+
+        let mut chunk = vec![];
+        let mut iter_state = WriteOptionsIterState::new();
+        let mut size = 0;
+        while self.next_chunk(bvh, &mut chunk, &mut iter_state) {
+            size += chunk.len();
+            if let WriteOptionsIterState::WriteFrames { .. } = iter_state {
+                break;
+            }
+        }
+
+        let num_frames = bvh.frames().len();
+        let num_samples = num_frames.min(SAMPLE_FRAMES);
+        if num_samples == 0 {
+            return size;
+        }
+
+        let columns = self.channel_order.columns(bvh);
+        let mut sampled = 0;
+        for sample in 0..num_samples {
+            let frame = sample * num_frames / num_samples;
+            if let Some(values) = bvh.frame_values(frame) {
+                chunk.clear();
+                self.write_motion_row(values, columns.as_deref(), &mut chunk);
+                sampled += chunk.len();
+            }
+        }
+
+        size + sampled * num_frames / num_samples
+    }
+
+    /// Output the `Bvh` file to the `writer` with only every `stride`th frame in
+    /// `range`, such as to export a preview of a long clip without modifying it.
+    ///
+    /// The whole hierarchy is written. The `Frames` line gives the number of
+    /// selected frames, and the frame time is multiplied by `stride`, so that the
+    /// selected frames keep their timing. A `Bvh` parsed with
+    /// [`Bvh::parse_preserving`] is written as normal, using its detected
+    /// indentation if `preserve_formatting` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with the kind [`io::ErrorKind::InvalidInput`] if `stride` is
+    /// `0`, if `range` is not within the frames of the `Bvh`, or if the frame time
+    /// multiplied by `stride` overflows, in which case nothing is written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, write::WriteOptions};
+    /// # use std::time::Duration;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 1 Xposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 5
+    ///     Frame Time: 0.5
+    ///     0.0
+    ///     1.0
+    ///     2.0
+    ///     3.0
+    ///     4.0
+    /// };
+    ///
+    /// let mut written = vec![];
+    /// WriteOptions::new().write_frame_range(&bvh, 1..5, 2, &mut written)?;
+    /// let preview = bvh_anim::from_bytes(&written).unwrap();
+    /// assert_eq!(preview.frame_values(0), Some(&[1.0][..]));
+    /// assert_eq!(preview.frame_values(1), Some(&[3.0][..]));
+    /// assert_eq!(preview.num_frames(), 2);
+    /// assert_eq!(preview.frame_time(), &Duration::from_secs(1));
+    /// # Result::<(), std::io::Error>::Ok(())
+    /// ```
+    ///
+    /// [`Bvh::parse_preserving`]: ../struct.Bvh.html#method.parse_preserving
+    /// [`io::ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_frame_range<W: Write>(
+        &self,
+        bvh: &Bvh,
+        range: Range<usize>,
+        stride: usize,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        let invalid_input = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+
+        if stride == 0 {
+            return Err(invalid_input("The stride must not be 0".to_string()));
+        }
+        let num_frames = bvh.num_frames();
+        if range.start > range.end || range.end > num_frames {
+            return Err(invalid_input(format!(
+                "The range of frames {:?} is not within the {} frames of the bvh",
+                range, num_frames
+            )));
+        }
+        let frame_time = u32::try_from(stride)
+            .ok()
+            .and_then(|stride| bvh.frame_time.checked_mul(stride))
+            .ok_or_else(|| {
+                invalid_input(format!(
+                    "The frame time multiplied by the stride {} overflows",
+                    stride
+                ))
+            })?;
+
+        let num_channels = bvh.num_channels;
+        let mut motion_values = Vec::with_capacity(range.len().div_ceil(stride) * num_channels);
+        for frame in range.step_by(stride) {
+            motion_values
+                .extend_from_slice(&bvh.motion_values[frame * num_channels..][..num_channels]);
+        }
+        let subset = Bvh {
+            joints: bvh.joints.clone(),
+            motion_values,
+            num_channels,
+            frame_time,
+            metadata: bvh.metadata.clone(),
+            ..Bvh::new()
+        };
+
+        let mut options = self.clone();
+        if self.preserve_formatting {
+            if let Some(indent) = bvh.detected_indent_style() {
+                options.indent = indent;
+            }
+        }
+        options.write(&subset, writer)
+    }
+
+    /// Output the `Bvh` file to the `fmt::Write` `writer` with the given options, such
+    /// as a `String` or a `fmt::Formatter`.
+    ///
+    /// Joint names which are not valid UTF-8 have the invalid bytes replaced with
+    /// `U+FFFD REPLACEMENT CHARACTER`, so the output is only identical to
+    /// [`WriteOptions::write_to_string`] when all joint names are valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, write::WriteOptions};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 1.0 2.0
+    /// };
+    ///
+    /// let options = WriteOptions::new();
+    /// let mut string = String::new();
+    /// options.write_to_fmt(&bvh, &mut string).unwrap();
+    /// assert_eq!(string.as_bytes(), &options.write_to_string(&bvh)[..]);
+    /// ```
+    ///
+    /// [`WriteOptions::write_to_string`]: struct.WriteOptions.html#method.write_to_string
+    pub fn write_to_fmt<W: fmt::Write>(&self, bvh: &Bvh, writer: &mut W) -> fmt::Result {
+        if let Some(options) = self.with_detected_indent(bvh) {
+            return options.write_to_fmt(bvh, writer);
+        }
+        if let Some(preserved) = self.preserved(bvh) {
+            return writer.write_str(&String::from_utf8_lossy(&preserved));
+        }
+
+        let mut curr_chunk = vec![];
+        let mut iter_state = WriteOptionsIterState::new();
+
+        while self.next_chunk(bvh, &mut curr_chunk, &mut iter_state) {
+            writer.write_str(&String::from_utf8_lossy(&curr_chunk))?;
+        }
+        Ok(())
     }
 
     /// Sets `indent` on `self` to the new `IndentStyle`.
     #[inline]
-    pub const fn with_indent(self, indent: IndentStyle) -> Self {
+    pub fn with_indent(self, indent: IndentStyle) -> Self {
         Self { indent, ..self }
     }
 
     /// Sets `line_terminator` on `self` to the new `LineTerminator`.
     #[inline]
-    pub const fn with_line_terminator(self, line_terminator: LineTerminator) -> Self {
+    pub fn with_line_terminator(self, line_terminator: LineTerminator) -> Self {
         Self {
             line_terminator,
             ..self
@@ -115,7 +609,7 @@ impl WriteOptions {
         }
     }
 
-    /// Sets `motion_values_significant_figures` on `self` to the new `motion_values_significant_figures`.
+    /// Sets `frame_time_significant_figures` on `self` to the new `frame_time_significant_figures`.
     #[inline]
     pub fn with_frame_time_significant_figures<N>(self, frame_time_significant_figures: N) -> Self
     where
@@ -142,6 +636,212 @@ impl WriteOptions {
         }
     }
 
+    /// Sets `max_line_bytes` on `self` to the new `max_line_bytes`.
+    #[inline]
+    pub fn with_max_line_bytes<N>(self, max_line_bytes: N) -> Self
+    where
+        N: Into<Option<usize>>,
+    {
+        Self {
+            max_line_bytes: max_line_bytes.into(),
+            ..self
+        }
+    }
+
+    /// Sets `value_separator` on `self` to the new `value_separator`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, write::{LineTerminator, WriteOptions}};
+    /// # use bstr::BString;
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Yposition Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     0.0 1.0 2.0
+    /// };
+    ///
+    /// let written = WriteOptions::new()
+    ///     .with_line_terminator(LineTerminator::Unix)
+    ///     .with_value_separator(BString::from("\t"))
+    ///     .write_to_string(&bvh);
+    /// assert!(written.ends_with(b"\n0\t1\t2\n"));
+    /// ```
+    #[inline]
+    pub fn with_value_separator<S>(self, value_separator: S) -> Self
+    where
+        S: Into<Option<BString>>,
+    {
+        Self {
+            value_separator: value_separator.into(),
+            ..self
+        }
+    }
+
+    /// Sets `channel_order` on `self` to the new `ChannelOrder`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{bvh, write::{ChannelOrder, LineTerminator, WriteOptions}};
+    /// let bvh = bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 3 Xposition Zrotation Zposition
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 1
+    ///     Frame Time: 0.033333333
+    ///     1.0 90.0 3.0
+    /// };
+    ///
+    /// let written = WriteOptions::new()
+    ///     .with_line_terminator(LineTerminator::Unix)
+    ///     .with_channel_order(ChannelOrder::PositionsFirst)
+    ///     .write_to_string(&bvh);
+    /// let written = String::from_utf8(written).unwrap();
+    /// assert!(written.contains("CHANNELS 3 Xposition Zposition Zrotation\n"));
+    /// assert!(written.ends_with("\n1 3 90\n"));
+    ///
+    /// // Moving the rotation before the X translation would change the pose, so the
+    /// // channels are written in the order in which they are stored.
+    /// let written = WriteOptions::new()
+    ///     .with_line_terminator(LineTerminator::Unix)
+    ///     .with_channel_order(ChannelOrder::RotationsFirst)
+    ///     .write_to_string(&bvh);
+    /// let written = String::from_utf8(written).unwrap();
+    /// assert!(written.contains("CHANNELS 3 Xposition Zrotation Zposition\n"));
+    /// assert!(written.ends_with("\n1 90 3\n"));
+    /// ```
+    #[inline]
+    pub fn with_channel_order(self, channel_order: ChannelOrder) -> Self {
+        Self {
+            channel_order,
+            ..self
+        }
+    }
+
+    /// Sets `preserve_formatting` on `self` to the new `preserve_formatting`.
+    #[inline]
+    pub fn with_preserve_formatting(self, preserve_formatting: bool) -> Self {
+        Self {
+            preserve_formatting,
+            ..self
+        }
+    }
+
+    /// Sets `metadata_comments` on `self` to the new `metadata_comments`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bvh_anim::{read::ParseOptions, write::{LineTerminator, WriteOptions}};
+    /// let mut bvh = bvh_anim::bvh! {
+    ///     HIERARCHY
+    ///     ROOT Hips
+    ///     {
+    ///         OFFSET 0.0 0.0 0.0
+    ///         CHANNELS 0
+    ///         JOINT Chest
+    ///         {
+    ///             OFFSET 0.0 5.0 0.0
+    ///             CHANNELS 0
+    ///             End Site
+    ///             {
+    ///                 OFFSET 0.0 5.0 0.0
+    ///             }
+    ///         }
+    ///     }
+    ///     MOTION
+    ///     Frames: 0
+    ///     Frame Time: 0.033333333
+    /// };
+    /// bvh.metadata_mut().insert("source".into(), "studio capture".into());
+    ///
+    /// let written = WriteOptions::new()
+    ///     .with_line_terminator(LineTerminator::Unix)
+    ///     .with_metadata_comments(true)
+    ///     .write_to_string(&bvh);
+    /// assert!(written.starts_with(b"# source: studio capture\nHIERARCHY\n"));
+    ///
+    /// let reparsed = ParseOptions::new()
+    ///     .with_metadata_comments(true)
+    ///     .read_bytes(&written)?;
+    /// assert_eq!(reparsed.metadata(), bvh.metadata());
+    /// # Result::<(), bvh_anim::errors::LoadError>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_metadata_comments(self, metadata_comments: bool) -> Self {
+        Self {
+            metadata_comments,
+            ..self
+        }
+    }
+
+    /// If formatting is preserved, but the `Bvh` cannot be written from its
+    /// preserved source, returns the options to write it normally, with the
+    /// indentation which was detected in its source.
+    fn with_detected_indent(&self, bvh: &Bvh) -> Option<WriteOptions> {
+        if !self.preserve_formatting {
+            return None;
+        }
+        if self.channel_order == ChannelOrder::AsStored && bvh.preserved_len().is_some() {
+            return None;
+        }
+
+        let indent = bvh.detected_indent_style()?;
+        Some(WriteOptions {
+            indent,
+            preserve_formatting: false,
+            ..self.clone()
+        })
+    }
+
+    #[inline]
+    fn preserved(&self, bvh: &Bvh) -> Option<Vec<u8>> {
+        if self.preserve_formatting && self.channel_order == ChannelOrder::AsStored {
+            bvh.write_preserved(self)
+        } else {
+            None
+        }
+    }
+
+    /// Format a single motion value.
+    #[inline]
+    pub(crate) fn format_motion_value(&self, motion: f32) -> String {
+        match self.motion_values_significant_figures {
+            Some(sf) => format!("{:.*}", sf, motion),
+            None => format!("{:.}", motion),
+        }
+    }
+
     // @TODO: Refactor all of this
     /// Get the next text chunk of the written bvh file. This function is
     /// structured so that the `chunk` string can be continually
@@ -164,8 +864,17 @@ impl WriteOptions {
         match *iter_state {
             WriteOptionsIterState::WriteHierarchy { ref mut written } => {
                 if !*written {
-                    *chunk = b"HIERARCHY".to_vec();
-                    chunk.extend_from_slice(self.line_terminator.as_bytes());
+                    if self.metadata_comments {
+                        for (key, value) in bvh.metadata() {
+                            chunk.extend_from_slice(b"# ");
+                            chunk.extend_from_slice(key);
+                            chunk.extend_from_slice(b": ");
+                            chunk.extend_from_slice(value);
+                            chunk.extend_from_slice(terminator);
+                        }
+                    }
+                    chunk.extend_from_slice(b"HIERARCHY");
+                    chunk.extend_from_slice(terminator);
                     *written = true;
                 } else {
                     let mut joints = bvh.joints();
@@ -206,7 +915,7 @@ impl WriteOptions {
                             } else {
                                 chunk.extend_from_slice(b"JOINT ");
                             }
-                            chunk.extend_from_slice(joint_data.name().as_ref());
+                            write_joint_name(joint_data.name(), chunk);
                             chunk.extend_from_slice(terminator);
                             chunk.extend(self.indent.prefix_chars(depth));
                             chunk.push(b'{');
@@ -231,16 +940,13 @@ impl WriteOptions {
                         (&mut true, &mut true, &mut false) => {
                             chunk.extend(self.indent.prefix_chars(depth));
 
-                            let channels = joint_data.channels();
-                            let channels_str = channels
-                                .iter()
-                                .map(|ch| ch.channel_type().as_str())
-                                .collect::<SmallVec<[_; 6]>>()
-                                .join(" ");
-
-                            let channels_str =
-                                format!("CHANNELS {} {}", channels.len(), channels_str);
-                            chunk.extend_from_slice(channels_str.as_bytes());
+                            let channels = self.channel_order.sorted(joint_data.channels());
+                            // Writing to a `Vec` cannot fail.
+                            let _ = write!(chunk, "CHANNELS {}", channels.len());
+                            for channel in &channels {
+                                chunk.push(b' ');
+                                chunk.extend_from_slice(channel.channel_type().as_str().as_bytes());
+                            }
                             chunk.extend_from_slice(terminator);
                             *wrote_channels = true;
                         }
@@ -267,27 +973,25 @@ impl WriteOptions {
                                 chunk.extend(self.indent.prefix_chars(depth));
                                 chunk.push(b'}');
                                 chunk.extend_from_slice(terminator);
+                            }
 
-                                let next_joint = joints.next();
-                                let prev_joint = mem::replace(current_joint, next_joint).unwrap();
-
-                                let (curr_depth, mut depth_difference) =
-                                    if let Some(ref curr_j) = *current_joint {
-                                        let curr_depth = curr_j.data().depth();
-                                        (curr_depth, Some(prev_joint.data().depth() - curr_depth))
-                                    } else {
-                                        (0, Some(prev_joint.data().depth()))
-                                    };
+                            let next_joint = joints.next();
+                            let prev_joint = mem::replace(current_joint, next_joint).unwrap();
+                            let prev_depth = prev_joint.data().depth();
 
-                                while let Some(d) = depth_difference {
-                                    chunk.extend(self.indent.prefix_chars(curr_depth + d));
+                            // Close the joint, and any of its ancestors which the next
+                            // joint is not inside of, unless the next joint is its child.
+                            let outer_depth = match *current_joint {
+                                Some(ref curr_j) if curr_j.data().depth() > prev_depth => None,
+                                Some(ref curr_j) => Some(curr_j.data().depth()),
+                                None => Some(0),
+                            };
+                            if let Some(outer_depth) = outer_depth {
+                                for d in (outer_depth..=prev_depth).rev() {
+                                    chunk.extend(self.indent.prefix_chars(d));
                                     chunk.push(b'}');
                                     chunk.extend_from_slice(terminator);
-                                    depth_difference =
-                                        depth_difference.and_then(|d| d.checked_sub(1));
                                 }
-                            } else {
-                                *current_joint = joints.next();
                             }
                             *wrote_name = false;
                             *wrote_offset = false;
@@ -329,24 +1033,18 @@ impl WriteOptions {
                     *written = true;
                 } else {
                     let frames = bvh.frames();
-                    *iter_state = WriteOptionsIterState::WriteFrames { frames };
+                    let columns = self.channel_order.columns(bvh);
+                    *iter_state = WriteOptionsIterState::WriteFrames { frames, columns };
                 }
             }
-            WriteOptionsIterState::WriteFrames { ref mut frames } => {
+            WriteOptionsIterState::WriteFrames {
+                ref mut frames,
+                ref columns,
+            } => {
                 return frames
                     .next()
                     .map(|frame| {
-                        let motion_values = frame
-                            .as_slice()
-                            .iter()
-                            .map(|motion| match self.motion_values_significant_figures {
-                                Some(sf) => format!("{:.*}", sf, motion),
-                                None => format!("{:.}", motion),
-                            })
-                            .collect::<Vec<_>>()
-                            .join(" ");
-                        *chunk = motion_values.into_bytes();
-                        chunk.extend_from_slice(terminator);
+                        self.write_motion_row(frame.as_slice(), columns.as_deref(), chunk);
                         true
                     })
                     .unwrap_or_default();
@@ -355,8 +1053,230 @@ impl WriteOptions {
 
         true
     }
+
+    /// Append the motion `values` of a single frame to `chunk`, followed by the line
+    /// terminator. If `columns` is given, then the `i`th value written is
+    /// `values[columns[i]]`.
+    fn write_motion_row(&self, values: &[f32], columns: Option<&[usize]>, chunk: &mut Vec<u8>) {
+        let terminator = self.line_terminator.as_bytes();
+        let separator = self
+            .value_separator
+            .as_ref()
+            .map(|separator| separator.as_slice())
+            .unwrap_or(b" ");
+        let mut line_len = 0usize;
+        for i in 0..values.len() {
+            let motion = values[columns.map_or(i, |columns| columns[i])];
+            let motion = self.format_motion_value(motion);
+
+            if line_len > 0 {
+                let wrap = self
+                    .max_line_bytes
+                    .map(|max| line_len + separator.len() + motion.len() > max)
+                    .unwrap_or(false);
+                if wrap {
+                    chunk.extend_from_slice(terminator);
+                    line_len = 0;
+                } else {
+                    chunk.extend_from_slice(separator);
+                    line_len += separator.len();
+                }
+            }
+
+            chunk.extend_from_slice(motion.as_bytes());
+            line_len += motion.len();
+        }
+        chunk.extend_from_slice(terminator);
+    }
 }
 
+/// The number of bytes reserved for the frame count by a [`BvhWriter`], which is
+/// enough for any `u64`.
+///
+/// [`BvhWriter`]: struct.BvhWriter.html
+const FRAME_COUNT_WIDTH: usize = 20;
+
+/// Writes a `bvh` file one frame at a time, so that a large clip never has to be
+/// held in memory.
+///
+/// The hierarchy and the frame time are taken from a skeleton `Bvh` when the
+/// `BvhWriter` is created, and each frame is written immediately by
+/// [`BvhWriter::write_frame`]. Since the number of frames is not known until the
+/// end, a fixed-width field of 20 bytes is reserved for it after `Frames:`, which
+/// is filled in by [`BvhWriter::finish`] by seeking back to it. The field is padded
+/// with trailing spaces, which readers ignore.
+///
+/// If `finish` is not called, then the written file reports `0` frames.
+///
+/// # Examples
+///
+/// ```
+/// # use bvh_anim::{bvh, write::{BvhWriter, WriteOptions}};
+/// # use std::io::Cursor;
+/// let skeleton = bvh! {
+///     HIERARCHY
+///     ROOT Hips
+///     {
+///         OFFSET 0.0 0.0 0.0
+///         CHANNELS 3 Xposition Yposition Zposition
+///         JOINT Chest
+///         {
+///             OFFSET 0.0 5.0 0.0
+///             CHANNELS 0
+///             End Site
+///             {
+///                 OFFSET 0.0 5.0 0.0
+///             }
+///         }
+///     }
+///     MOTION
+///     Frames: 0
+///     Frame Time: 0.033333333
+/// };
+///
+/// let mut writer = BvhWriter::new(&skeleton, Cursor::new(Vec::new()), WriteOptions::new())?;
+/// for i in 0..10 {
+///     writer.write_frame(&[i as f32, 0.0, 0.0])?;
+/// }
+/// let bytes = writer.finish()?.into_inner();
+///
+/// let bvh = bvh_anim::from_bytes(&bytes).unwrap();
+/// assert_eq!(bvh.num_frames(), 10);
+/// # Result::<(), std::io::Error>::Ok(())
+/// ```
+///
+/// [`BvhWriter::write_frame`]: struct.BvhWriter.html#method.write_frame
+/// [`BvhWriter::finish`]: struct.BvhWriter.html#method.finish
+#[derive(Debug)]
+pub struct BvhWriter<W: Write + Seek> {
+    writer: W,
+    options: WriteOptions,
+    num_channels: usize,
+    num_frames: usize,
+    frame_count_position: u64,
+    columns: Option<Vec<usize>>,
+    chunk: Vec<u8>,
+}
+
+impl<W: Write + Seek> BvhWriter<W> {
+    /// Create a new `BvhWriter` which writes the hierarchy and frame time of
+    /// `skeleton` to `writer` with the given `options`. The frames of `skeleton` are
+    /// not written.
+    ///
+    /// The `preserve_formatting` option is ignored.
+    pub fn new(skeleton: &Bvh, mut writer: W, options: WriteOptions) -> io::Result<Self> {
+        let skeleton = Bvh {
+            joints: skeleton.joints.clone(),
+            num_channels: skeleton.num_channels,
+            frame_time: skeleton.frame_time,
+            metadata: skeleton.metadata.clone(),
+            ..Bvh::new()
+        };
+
+        let mut chunk = vec![];
+        let mut iter_state = WriteOptionsIterState::new();
+        let mut frame_count_position = 0;
+        loop {
+            if let WriteOptionsIterState::WriteNumFrames { written: false } = iter_state {
+                writer.write_all(b"Frames: ")?;
+                frame_count_position = writer.stream_position()?;
+                write!(writer, "{:<width$}", 0, width = FRAME_COUNT_WIDTH)?;
+                writer.write_all(options.line_terminator.as_bytes())?;
+                iter_state = WriteOptionsIterState::WriteNumFrames { written: true };
+                continue;
+            }
+            if let WriteOptionsIterState::WriteFrames { .. } = iter_state {
+                break;
+            }
+
+            options.next_chunk(&skeleton, &mut chunk, &mut iter_state);
+            writer.write_all(&chunk)?;
+        }
+
+        Ok(BvhWriter {
+            writer,
+            num_channels: skeleton.num_channels,
+            num_frames: 0,
+            frame_count_position,
+            columns: options.channel_order.columns(&skeleton),
+            options,
+            chunk,
+        })
+    }
+
+    /// Write the motion values of a single frame, which must have one value for
+    /// each channel of the skeleton, in the order in which they are stored in the
+    /// skeleton. The values are reordered to match the `channel_order` option.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with the kind [`io::ErrorKind::InvalidInput`] if `frame`
+    /// does not have one value for each channel, in which case nothing is written.
+    ///
+    /// [`io::ErrorKind::InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_frame(&mut self, frame: &[f32]) -> io::Result<()> {
+        if frame.len() != self.num_channels {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "The frame has {} values, but the skeleton has {} channels",
+                    frame.len(),
+                    self.num_channels
+                ),
+            ));
+        }
+
+        self.chunk.clear();
+        self.options
+            .write_motion_row(frame, self.columns.as_deref(), &mut self.chunk);
+        self.writer.write_all(&self.chunk)?;
+        self.num_frames += 1;
+        Ok(())
+    }
+
+    /// Returns the number of frames which have been written so far.
+    #[inline]
+    pub fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    /// Fill in the number of frames which were written, flush the writer, and
+    /// return it. The writer is left positioned at the end of the written file.
+    pub fn finish(mut self) -> io::Result<W> {
+        let end = self.writer.stream_position()?;
+        self.writer
+            .seek(SeekFrom::Start(self.frame_count_position))?;
+        write!(
+            self.writer,
+            "{:<width$}",
+            self.num_frames,
+            width = FRAME_COUNT_WIDTH
+        )?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Write the joint `name` to `chunk`. Names containing whitespace are quoted, so
+/// that they can be read with `ParseOptions::with_quoted_names`.
+fn write_joint_name(name: &[u8], chunk: &mut Vec<u8>) {
+    if !name.iter().any(u8::is_ascii_whitespace) {
+        chunk.extend_from_slice(name);
+        return;
+    }
+
+    chunk.push(b'"');
+    for &b in name {
+        if b == b'"' || b == b'\\' {
+            chunk.push(b'\\');
+        }
+        chunk.push(b);
+    }
+    chunk.push(b'"');
+}
+
+#[allow(clippy::enum_variant_names)]
 enum WriteOptionsIterState<'a> {
     WriteHierarchy {
         written: bool,
@@ -379,6 +1299,7 @@ enum WriteOptionsIterState<'a> {
     },
     WriteFrames {
         frames: Frames<'a>,
+        columns: Option<Vec<usize>>,
     },
 }
 
@@ -396,6 +1317,137 @@ impl Default for WriteOptionsIterState<'_> {
     }
 }
 
+/// A difference between a line of an original file and the output of the writer,
+/// as returned by [`WriteOptions::diff_preview`][`WriteOptions::diff_preview`].
+///
+/// Line numbers start at `1`. Lines do not include their line terminators.
+///
+/// [`WriteOptions::diff_preview`]: struct.WriteOptions.html#method.diff_preview
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum LineDiff {
+    /// The line at `line` would be replaced.
+    Changed {
+        /// The number of the line in both the original file and the output.
+        line: usize,
+        /// The line in the original file.
+        original: BString,
+        /// The line which would be written instead.
+        written: BString,
+    },
+    /// The line at `line` of the original file would not be written.
+    Removed {
+        /// The number of the line in the original file.
+        line: usize,
+        /// The line in the original file.
+        original: BString,
+    },
+    /// The line at `line` of the output is not in the original file.
+    Added {
+        /// The number of the line in the output.
+        line: usize,
+        /// The line which would be written.
+        written: BString,
+    },
+}
+
+/// Specify the order in which to write the channels of each joint.
+///
+/// By default, channels are written in the order in which they are stored. The
+/// channels of a joint are only reordered if that does not change the pose. See
+/// [`WriteOptions::channel_order`].
+///
+/// [`WriteOptions::channel_order`]: struct.WriteOptions.html#structfield.channel_order
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChannelOrder {
+    /// Write the channels in the order in which they are stored in the `Bvh`.
+    AsStored,
+    /// Write the position channels of each joint before its other channels.
+    PositionsFirst,
+    /// Write the rotation channels of each joint before its other channels.
+    RotationsFirst,
+}
+
+impl ChannelOrder {
+    /// Returns the `channels` of a joint in the order in which they are written.
+    ///
+    /// If the new order would change the transform of the joint, then the
+    /// channels are returned in the order in which they are stored.
+    fn sorted(self, channels: &[Channel]) -> SmallVec<[Channel; 6]> {
+        let mut order = (0..channels.len()).collect::<SmallVec<[usize; 6]>>();
+        match self {
+            ChannelOrder::AsStored => {}
+            ChannelOrder::PositionsFirst => {
+                order.sort_by_key(|&i| !channels[i].channel_type().is_position())
+            }
+            ChannelOrder::RotationsFirst => {
+                order.sort_by_key(|&i| !channels[i].channel_type().is_rotation())
+            }
+        }
+
+        // The transform is unchanged if every pair of channels which swapped places
+        // commutes, as the new order can then be reached by swapping neighbours.
+        let keeps_transform = order.iter().enumerate().all(|(n, &i)| {
+            order[n + 1..]
+                .iter()
+                .all(|&j| j > i || commutes(channels[i].channel_type(), channels[j].channel_type()))
+        });
+        if keeps_transform {
+            order.iter().map(|&i| channels[i]).collect()
+        } else {
+            SmallVec::from_slice(channels)
+        }
+    }
+
+    /// Returns the motion index of each column in the order in which they are
+    /// written, or `None` if the columns are written in the order in which they
+    /// are stored.
+    fn columns(self, bvh: &Bvh) -> Option<Vec<usize>> {
+        if self == ChannelOrder::AsStored {
+            return None;
+        }
+
+        let mut joint_channels: Vec<_> = bvh.joints.iter().map(|joint| joint.channels()).collect();
+        joint_channels.sort_by_key(|channels| channels.first().map(Channel::motion_index));
+
+        let columns = joint_channels
+            .into_iter()
+            .flat_map(|channels| self.sorted(channels))
+            .map(|channel| channel.motion_index())
+            .collect();
+        Some(columns)
+    }
+}
+
+/// Returns `true` if applying the channels `a` and `b` in either order gives the
+/// same transform, whatever their values.
+fn commutes(a: ChannelType, b: ChannelType) -> bool {
+    let same_axis = a.axis() == b.axis();
+    match (
+        a.is_position(),
+        a.is_rotation(),
+        b.is_position(),
+        b.is_rotation(),
+    ) {
+        // Translations commute with each other, and scales commute with each other.
+        (true, _, true, _) => true,
+        (false, false, false, false) => true,
+        // A translation or a scale along an axis is unaffected by a rotation about
+        // that axis.
+        (_, true, _, _) | (_, _, _, true) => same_axis,
+        // A translation is unaffected by a scale along a different axis.
+        _ => !same_axis,
+    }
+}
+
+/// Writes the channels in the order in which they are stored.
+impl Default for ChannelOrder {
+    #[inline]
+    fn default() -> Self {
+        ChannelOrder::AsStored
+    }
+}
+
 /// Specify indentation style to use when writing the `Bvh` joints.
 ///
 /// By default, this value is set to 1 tab.
@@ -0,0 +1,768 @@
+use bvh_anim::bvh;
+use std::collections::HashMap;
+
+#[test]
+fn center_of_mass_weighted_midpoint() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Xposition Yposition Zposition
+            JOINT Tip
+            {
+                OFFSET 12.0 0.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 1.0 0.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 2
+        Frame Time: 0.033333333
+        0.0 0.0 0.0 0.0 0.0 0.0
+        0.0 4.0 0.0 0.0 0.0 0.0
+    };
+
+    let mut weights = HashMap::new();
+    weights.insert(b"Base".to_vec(), 3.0);
+    weights.insert(b"Tip".to_vec(), 1.0);
+
+    let center = bvh.center_of_mass(&weights);
+    assert_eq!(center, [[3.0, 0.0, 0.0], [3.0, 4.0, 0.0]]);
+
+    let uniform = bvh.center_of_mass(&HashMap::new());
+    assert_eq!(uniform, [[6.0, 0.0, 0.0], [6.0, 4.0, 0.0]]);
+}
+
+#[test]
+fn detect_contacts_planted_frames() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Xposition Yposition Zposition
+            JOINT Foot
+            {
+                OFFSET 0.0 -10.0 0.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 0.0 1.0
+                }
+            }
+        }
+        MOTION
+        Frames: 6
+        Frame Time: 0.1
+        0.0 10.0 0.0
+        0.0 10.0 0.0
+        0.0 10.0 0.0
+        5.0 15.0 0.0
+        10.0 15.0 0.0
+        10.0 10.0 0.0
+    };
+
+    let contacts = bvh.detect_contacts(b"Foot", 0.5, 1.0);
+    assert_eq!(contacts, [true, true, true, false, false, false]);
+
+    let missing = bvh.detect_contacts(b"Hand", 0.5, 1.0);
+    assert_eq!(missing, [false; 6]);
+}
+
+#[test]
+fn longest_chain_asymmetric_skeleton() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let names = |chain: Vec<usize>| {
+        chain
+            .into_iter()
+            .map(|i| String::from_utf8(bvh.joints().nth(i).unwrap().name().to_vec()).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(bvh.longest_chain()),
+        [
+            "Hips",
+            "Chest",
+            "Chest2",
+            "LeftCollar",
+            "LeftShoulder",
+            "LeftElbow",
+            "LeftWrist"
+        ]
+    );
+
+    let chain = bvh.longest_chain_by_length();
+    assert_eq!(chain[0], 0);
+    for pair in chain.windows(2) {
+        assert_eq!(
+            bvh.joints().nth(pair[1]).unwrap().parent_index(),
+            Some(pair[0])
+        );
+    }
+}
+
+#[test]
+fn auto_name_map_renamed_skeleton() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let renamed_bytes = BVH_BYTES
+        .split(|&b| b == b'\n')
+        .enumerate()
+        .map(|(i, line)| {
+            let line = String::from_utf8_lossy(line);
+            match line.split_whitespace().next() {
+                Some("ROOT") | Some("JOINT") => {
+                    let keyword_end = line.find("T ").unwrap() + 2;
+                    format!("{}bone_{}", &line[..keyword_end], i)
+                }
+                _ => line.into_owned(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let renamed = bvh_anim::from_bytes(renamed_bytes.as_bytes()).unwrap();
+    assert_ne!(renamed.joints().nth(1).unwrap().name(), b"Chest");
+
+    let map = bvh.auto_name_map(&renamed);
+    let expected = (0..bvh.joints().count())
+        .map(|i| (i, i))
+        .collect::<Vec<_>>();
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn channel_stats_constant_and_ramp() {
+    use bvh_anim::ChannelType;
+
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Xposition Yposition Zposition
+            JOINT Tip
+            {
+                OFFSET 0.0 1.0 0.0
+                CHANNELS 2 Zrotation Xrotation
+                End Site
+                {
+                    OFFSET 0.0 1.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 5
+        Frame Time: 0.033333333
+        0.0 0.0 0.0 45.0 0.0
+        0.0 0.0 0.0 45.0 10.0
+        0.0 0.0 0.0 45.0 20.0
+        0.0 0.0 0.0 45.0 30.0
+        0.0 0.0 0.0 45.0 40.0
+    };
+
+    let stats = bvh.channel_stats();
+    assert_eq!(stats.len(), 5);
+
+    let constant = stats[&(1, ChannelType::RotationZ)];
+    assert_eq!(constant.min, 45.0);
+    assert_eq!(constant.max, 45.0);
+    assert_eq!(constant.mean, 45.0);
+    assert_eq!(constant.std_dev, 0.0);
+    assert_eq!(constant.range(), 0.0);
+
+    let ramp = stats[&(1, ChannelType::RotationX)];
+    assert_eq!(ramp.min, 0.0);
+    assert_eq!(ramp.max, 40.0);
+    assert_eq!(ramp.mean, 20.0);
+    assert!((ramp.std_dev - 200.0f32.sqrt()).abs() < 1.0e-4);
+}
+
+#[test]
+fn joint_distance_series_constant_for_rigid_pose() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Chest
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT LeftArm
+            {
+                OFFSET 2.0 0.0 0.0
+                CHANNELS 0
+                JOINT LeftHand
+                {
+                    OFFSET 2.0 0.0 0.0
+                    CHANNELS 0
+                    End Site
+                    {
+                        OFFSET 1.0 0.0 0.0
+                    }
+                }
+            }
+            JOINT RightArm
+            {
+                OFFSET 0.0 0.0 3.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 0.0 1.0
+                }
+            }
+        }
+        MOTION
+        Frames: 3
+        Frame Time: 0.033333333
+        0.0 0.0 0.0 0.0 0.0 0.0
+        1.0 2.0 3.0 45.0 0.0 0.0
+        -5.0 0.0 0.0 30.0 60.0 90.0
+    };
+
+    let distances = bvh.joint_distance_series(2, 3).unwrap();
+    assert_eq!(distances.len(), 3);
+    for distance in distances {
+        assert!((distance - 5.0).abs() < 1.0e-4, "{}", distance);
+    }
+
+    let err = bvh.joint_distance_series(0, 4).unwrap_err();
+    assert_eq!(err.index(), 4);
+    assert_eq!(err.num_joints(), 4);
+}
+
+#[test]
+fn pose_error_of_identical_and_shifted_clips() {
+    use bvh_anim::{errors::ClipMismatchError, math, PoseMetric};
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    for &metric in &[
+        PoseMetric::MeanJointPositionError,
+        PoseMetric::MeanQuaternionAngle,
+    ] {
+        assert!(bvh.pose_error(&bvh, metric).unwrap().abs() < 1.0e-6);
+    }
+
+    let mut shifted = bvh.clone();
+    shifted.transform(math::mat_translation([3.0, 0.0, 4.0]));
+    let position_error = bvh
+        .pose_error(&shifted, PoseMetric::MeanJointPositionError)
+        .unwrap();
+    assert!((position_error - 5.0).abs() < 1.0e-3, "{}", position_error);
+    let angle_error = bvh
+        .pose_error(&shifted, PoseMetric::MeanQuaternionAngle)
+        .unwrap();
+    assert!(angle_error.abs() < 1.0e-2, "{}", angle_error);
+
+    let mut truncated = bvh.clone();
+    truncated.extract_frames();
+    assert!(matches!(
+        bvh.pose_error(&truncated, PoseMetric::MeanJointPositionError),
+        Err(ClipMismatchError::FrameCount { .. })
+    ));
+}
+
+#[test]
+fn skeleton_hash_ignores_motion() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let mut moved = bvh.clone();
+    moved.map_frames(|index, frame| frame[0] += index as f32);
+    assert_eq!(bvh.skeleton_hash(), moved.skeleton_hash());
+
+    let source = std::str::from_utf8(BVH_BYTES).unwrap();
+    let renamed = bvh_anim::from_str(&source.replacen("Chest2", "Torso", 1)).unwrap();
+    assert_ne!(bvh.skeleton_hash(), renamed.skeleton_hash());
+
+    let offset = bvh_anim::from_str(&source.replacen("-0.1728", "-0.1729", 1)).unwrap();
+    assert_ne!(bvh.skeleton_hash(), offset.skeleton_hash());
+
+    let mut pruned = bvh.clone();
+    pruned.prune_constant_channels(1.0e6);
+    assert_ne!(bvh.skeleton_hash(), pruned.skeleton_hash());
+}
+
+#[test]
+fn estimate_character_scale_is_leg_length() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT LeftUpLeg
+            {
+                OFFSET 10.0 0.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                JOINT LeftLeg
+                {
+                    OFFSET 0.0 -45.0 0.0
+                    CHANNELS 3 Zrotation Xrotation Yrotation
+                    End Site
+                    {
+                        OFFSET 0.0 -45.0 0.0
+                    }
+                }
+            }
+            JOINT Spine
+            {
+                OFFSET 0.0 20.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 0.0 50.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 1
+        Frame Time: 0.033333333
+        5.0 90.0 5.0 0.0 0.0 0.0 45.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+    };
+
+    let expected = (10.0f32 * 10.0 + 90.0 * 90.0).sqrt();
+    assert!((bvh.estimate_character_scale() - expected).abs() < 1.0e-4);
+
+    assert_eq!(bvh_anim::Bvh::new().estimate_character_scale(), 0.0);
+}
+
+#[test]
+fn ground_penetration_of_dipping_foot() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Xposition Yposition Zposition
+            JOINT LeftFoot
+            {
+                OFFSET 0.0 -10.0 0.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 0.0 2.0
+                }
+            }
+        }
+        MOTION
+        Frames: 4
+        Frame Time: 0.033333333
+        0.0 10.0 0.0
+        0.0 8.0 0.0
+        0.0 8.0 0.0
+        0.0 11.0 0.0
+    };
+
+    let penetration = bvh.ground_penetration(b"LeftFoot");
+    assert_eq!(penetration, [0.0, 2.0, 2.0, 0.0]);
+    assert_eq!(bvh.ground_penetration(b"RightFoot"), [0.0; 4]);
+}
+
+#[test]
+fn sanity_check_skeleton_flags_infinite_offset() {
+    use bvh_anim::diagnostic::DiagnosticKind;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_infinite_offset.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let diagnostics = bvh.sanity_check_skeleton(1000.0);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].kind,
+        DiagnosticKind::NonFiniteOffset {
+            joint_index: 1,
+            end_site: false,
+        }
+    );
+    assert_eq!(diagnostics[0].line, None);
+
+    assert!(bvh.sanity_check_skeleton(20.0).iter().any(|diagnostic| {
+        diagnostic.kind
+            == DiagnosticKind::LargeOffset {
+                joint_index: 2,
+                end_site: true,
+                length: 30.0,
+            }
+    }));
+
+    const CLEAN_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let clean = bvh_anim::from_bytes(CLEAN_BYTES).unwrap();
+    assert!(clean.sanity_check_skeleton(1000.0).is_empty());
+}
+
+#[test]
+fn dominant_rotation_order_is_the_majority() {
+    use bvh_anim::Axis;
+
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Chest
+            {
+                OFFSET 0.0 5.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                JOINT Head
+                {
+                    OFFSET 0.0 5.0 0.0
+                    CHANNELS 3 Zrotation Xrotation Yrotation
+                    End Site
+                    {
+                        OFFSET 0.0 1.0 0.0
+                    }
+                }
+            }
+            JOINT Leg
+            {
+                OFFSET 0.0 -5.0 0.0
+                CHANNELS 3 Xrotation Yrotation Zrotation
+                End Site
+                {
+                    OFFSET 0.0 -5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 0
+        Frame Time: 0.033333333
+    };
+    assert_eq!(
+        bvh.dominant_rotation_order(),
+        Some([Axis::Z, Axis::X, Axis::Y])
+    );
+
+    let split = bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Chest
+            {
+                OFFSET 0.0 5.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                JOINT Head
+                {
+                    OFFSET 0.0 5.0 0.0
+                    CHANNELS 3 Yrotation Xrotation Zrotation
+                    End Site
+                    {
+                        OFFSET 0.0 1.0 0.0
+                    }
+                }
+            }
+            JOINT Leg
+            {
+                OFFSET 0.0 -5.0 0.0
+                CHANNELS 3 Xrotation Yrotation Zrotation
+                End Site
+                {
+                    OFFSET 0.0 -5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 0
+        Frame Time: 0.033333333
+    };
+    assert_eq!(split.dominant_rotation_order(), None);
+}
+
+#[test]
+fn assert_root_only_translation_names_stray_joints() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    assert!(bvh.assert_root_only_translation().is_ok());
+
+    let stray = bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Chest
+            {
+                OFFSET 0.0 5.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                JOINT LeftHand
+                {
+                    OFFSET 0.0 5.0 0.0
+                    CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+                    End Site
+                    {
+                        OFFSET 0.0 1.0 0.0
+                    }
+                }
+            }
+        }
+        MOTION
+        Frames: 0
+        Frame Time: 0.033333333
+    };
+    let err = stray.assert_root_only_translation().unwrap_err();
+    assert_eq!(err.joints().len(), 1);
+    assert_eq!(err.joints()[0].0, 2);
+    assert_eq!(err.joints()[0].1, "LeftHand");
+    assert!(err.to_string().contains("LeftHand"));
+}
+
+#[test]
+fn infer_symmetry_pairs_on_humanoid() {
+    use bvh_anim::Bvh;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let joints = bvh.joints().collect::<Vec<_>>();
+
+    let pairs = bvh
+        .infer_symmetry_pairs(Bvh::DEFAULT_SYMMETRY_PATTERNS, 3.0)
+        .into_iter()
+        .map(|(left, right)| {
+            (
+                joints[left].name_lossy().into_owned(),
+                joints[right].name_lossy().into_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let expected = [
+        "Collar", "Shoulder", "Elbow", "Wrist", "Hip", "Knee", "Ankle",
+    ]
+    .iter()
+    .map(|part| (format!("Left{}", part), format!("Right{}", part)))
+    .collect::<Vec<_>>();
+    assert_eq!(pairs, expected);
+
+    let no_patterns: &[(&[u8], &[u8])] = &[];
+    assert!(bvh.infer_symmetry_pairs(no_patterns, 3.0).is_empty());
+
+    // The recorded skeleton is not exactly symmetric.
+    assert!(bvh
+        .infer_symmetry_pairs(Bvh::DEFAULT_SYMMETRY_PATTERNS, 1.0e-3)
+        .is_empty());
+}
+
+#[test]
+fn root_heading_series_follows_turn() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Yrotation Xrotation Zrotation
+            JOINT Chest
+            {
+                OFFSET 0.0 5.0 0.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 6
+        Frame Time: 0.033333333
+        0.0 0.0 0.0 0.0 20.0 15.0
+        0.0 0.0 0.0 30.0 20.0 15.0
+        0.0 0.0 0.0 60.0 20.0 15.0
+        0.0 0.0 0.0 90.0 20.0 15.0
+        0.0 0.0 0.0 150.0 20.0 15.0
+        0.0 0.0 0.0 -150.0 20.0 15.0
+    };
+
+    let headings = bvh.root_heading_series();
+    assert_eq!(headings.len(), 6);
+    assert!(
+        (headings[3] - headings[0] - 90.0).abs() < 1.0e-3,
+        "{:?}",
+        headings
+    );
+    for (heading, expected) in headings.iter().zip(&[0.0, 30.0, 60.0, 90.0, 150.0, 210.0]) {
+        assert!((heading - expected).abs() < 1.0e-3, "{:?}", headings);
+    }
+}
+
+#[test]
+fn acceleration_of_constant_velocity_clip_is_zero() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Chest
+            {
+                OFFSET 0.0 5.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 5
+        Frame Time: 0.033333333
+        0.0 0.0 0.0 0.0 0.0 0.0 30.0 0.0 0.0
+        1.0 0.5 0.0 0.0 0.0 0.0 30.0 0.0 0.0
+        2.0 1.0 0.0 0.0 0.0 0.0 30.0 0.0 0.0
+        3.0 1.5 0.0 0.0 0.0 0.0 30.0 0.0 0.0
+        4.0 2.0 0.0 0.0 0.0 0.0 30.0 0.0 0.0
+    };
+
+    for joint in 0..2 {
+        let acceleration = bvh.joint_acceleration_series(joint).unwrap();
+        assert_eq!(acceleration.len(), 3);
+        for value in acceleration.iter().flatten() {
+            assert!(value.abs() < 1.0e-2, "{:?}", acceleration);
+        }
+    }
+    assert!(bvh.smoothness_score() < 1.0);
+
+    let mut jittery = bvh.clone();
+    jittery.frames_mut().nth(2).unwrap()[0] += 0.5;
+    assert!(jittery.smoothness_score() > 1.0e3);
+}
+
+#[test]
+fn infer_up_axis_of_humanoid() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    assert_eq!(bvh.infer_up_axis(), bvh_anim::Axis::Y);
+
+    let z_up = String::from_utf8_lossy(BVH_BYTES)
+        .lines()
+        .map(|line| match line.trim_start().strip_prefix("OFFSET ") {
+            Some(offset) => {
+                let values = offset.split_whitespace().collect::<Vec<_>>();
+                format!("OFFSET {} {} {}", values[0], values[2], values[1])
+            }
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let bvh = bvh_anim::from_bytes(z_up.as_bytes()).unwrap();
+    assert_eq!(bvh.infer_up_axis(), bvh_anim::Axis::Z);
+
+    assert_eq!(bvh_anim::Bvh::new().infer_up_axis(), bvh_anim::Axis::Y);
+}
+
+#[test]
+fn static_joints_finds_frozen_joint() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let before = bvh.static_joints(1.0e-4);
+
+    let frozen = bvh
+        .joints()
+        .find(|joint| !joint.channels().is_empty() && !before.contains(&joint.index()))
+        .unwrap()
+        .index();
+    let columns = bvh
+        .joint(frozen)
+        .unwrap()
+        .channels()
+        .iter()
+        .map(|channel| channel.motion_index())
+        .collect::<Vec<_>>();
+    let first = bvh.frame_values(0).unwrap().to_vec();
+    for frame in 0..bvh.num_frames() {
+        let values = bvh.frame_values_mut(frame).unwrap();
+        for &column in &columns {
+            values[column] = first[column];
+        }
+    }
+
+    let mut expected = before;
+    expected.push(frozen);
+    expected.sort_unstable();
+    assert_eq!(bvh.static_joints(1.0e-4), expected);
+}
+
+#[test]
+fn matches_rig_reports_every_mismatch() {
+    use bvh_anim::{ChannelType, RigMismatch};
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let rotations = &[
+        ChannelType::RotationX,
+        ChannelType::RotationY,
+        ChannelType::RotationZ,
+    ];
+    let rig: &[(&[u8], &[ChannelType])] = &[
+        (b"Hips", &[ChannelType::PositionX, ChannelType::RotationY]),
+        (b"Chest", &[ChannelType::PositionY, ChannelType::RotationZ]),
+        (b"Tail", rotations),
+        (b"Head", rotations),
+    ];
+
+    let chest = bvh
+        .joints()
+        .find(|joint| joint.name() == &b"Chest"[..])
+        .unwrap()
+        .index();
+    assert_eq!(
+        bvh.matches_rig(rig),
+        Err(vec![
+            RigMismatch::MissingChannels {
+                name: "Chest".into(),
+                joint_index: chest,
+                channels: vec![ChannelType::PositionY],
+            },
+            RigMismatch::MissingJoint {
+                name: "Tail".into()
+            },
+        ])
+    );
+    assert_eq!(bvh.matches_rig(&rig[..1]), Ok(()));
+}
+
+#[test]
+fn channel_spectrum_peaks_at_sinusoid_frequency() {
+    use bvh_anim::{Bvh, ChannelType};
+    use std::{f32::consts::PI, time::Duration};
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    // 64 frames at 32 frames per second, with a 4Hz sinusoid in the root's Y
+    // position. Its peak should be at bin 4 * 64 / 32 = 8.
+    let num_channels = bvh.frame_values(0).unwrap().len();
+    let frames = (0..64)
+        .map(|i| {
+            let mut frame = vec![0.0; num_channels];
+            frame[1] = 3.0 * (2.0 * PI * 4.0 * i as f32 / 32.0).sin() + 1.0;
+            frame
+        })
+        .collect();
+    let bvh: Bvh = bvh
+        .with_frame_time(Duration::from_secs_f64(1.0 / 32.0))
+        .with_frames(frames)
+        .unwrap();
+
+    let spectrum = bvh.channel_spectrum(0, ChannelType::PositionY).unwrap();
+    assert_eq!(spectrum.len(), 33);
+    let peak = (1..spectrum.len())
+        .max_by(|&a, &b| spectrum[a].partial_cmp(&spectrum[b]).unwrap())
+        .unwrap();
+    assert_eq!(peak, 8);
+    assert!((spectrum[8] - 3.0 * 32.0).abs() < 1.0e-2);
+    assert!((spectrum[0] - 64.0).abs() < 1.0e-2);
+
+    let flat = bvh.channel_spectrum(1, ChannelType::RotationZ).unwrap();
+    assert!(flat.iter().all(|&magnitude| magnitude.abs() < 1.0e-4));
+    assert!(bvh.channel_spectrum(1, ChannelType::PositionX).is_none());
+    assert!(bvh.channel_spectrum(1000, ChannelType::PositionX).is_none());
+}
@@ -0,0 +1,25 @@
+const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+
+#[test]
+fn annotate_frame_round_trip() {
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let written = bvh.to_string();
+
+    bvh.annotate_frame(5, "footstep");
+    bvh.annotate_frame(5, b"attack start".to_vec());
+    bvh.annotate_frame(7, "land");
+
+    assert_eq!(bvh.annotations_at(5), &["footstep", "attack start"]);
+    assert_eq!(bvh.annotations_at(7), &["land"]);
+    assert!(bvh.annotations_at(6).is_empty());
+    assert_eq!(
+        bvh.annotations().keys().copied().collect::<Vec<_>>(),
+        [5, 7]
+    );
+
+    // Annotations are not part of the bvh format.
+    assert_eq!(bvh.to_string(), written);
+
+    assert_eq!(bvh.clear_annotations_at(7), ["land"]);
+    assert!(bvh.annotations_at(7).is_empty());
+}
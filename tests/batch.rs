@@ -0,0 +1,27 @@
+#![cfg(feature = "rayon")]
+
+use std::fs;
+
+#[test]
+fn load_dir_reports_each_file() {
+    let dir = std::env::temp_dir().join(format!("bvh_anim_load_dir_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    fs::copy("./data/test_simple.bvh", dir.join("a.bvh")).unwrap();
+    fs::copy("./data/test_mocapbank.bvh", dir.join("b.BVH")).unwrap();
+    fs::write(dir.join("c.bvh"), b"HIERARCHY\nnot a bvh file\n").unwrap();
+    fs::write(dir.join("notes.txt"), b"ignored").unwrap();
+
+    let results = bvh_anim::load_dir(&dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let names = results
+        .iter()
+        .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["a.bvh", "b.BVH", "c.bvh"]);
+
+    assert_eq!(results[0].1.as_ref().unwrap().frames().len(), 2);
+    assert!(results[1].1.is_ok());
+    assert!(results[2].1.is_err());
+}
@@ -0,0 +1,970 @@
+use bvh_anim::math;
+
+const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+
+fn assert_close(a: [f32; 3], b: [f32; 3]) {
+    for (x, y) in a.iter().zip(b.iter()) {
+        assert!((x - y).abs() < 1.0e-2, "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn transform_translation_shifts_every_joint() {
+    let original = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let mut transformed = original.clone();
+    transformed.transform(math::mat_translation([10.0, 0.0, 0.0]));
+
+    for (before, after) in original.frames().zip(transformed.frames()) {
+        let before = original.world_positions(&before);
+        let after = transformed.world_positions(&after);
+        for (b, a) in before.iter().zip(after.iter()) {
+            assert_close(*a, [b[0] + 10.0, b[1], b[2]]);
+        }
+    }
+}
+
+#[test]
+fn transform_without_channels_moves_offsets() {
+    let mut bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 1.0 0.0 0.0
+            CHANNELS 0
+            JOINT Chest
+            {
+                OFFSET 0.0 2.0 0.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 3.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 0
+        Frame Time: 0.033333333
+    };
+    assert_eq!(bvh.num_channels(), 0);
+
+    bvh.transform(math::mat_mul(
+        &math::mat_translation([0.0, 0.0, 5.0]),
+        &math::mat_scale([2.0, 2.0, 2.0]),
+    ));
+    let mut joints = bvh.joints();
+    let hips = joints.next().unwrap();
+    assert_close(*hips.offset(), [2.0, 0.0, 5.0]);
+    let chest = joints.next().unwrap();
+    assert_close(*chest.offset(), [0.0, 4.0, 0.0]);
+    assert_close(*chest.end_site().unwrap(), [0.0, 6.0, 0.0]);
+    assert_eq!(bvh.num_frames(), 0);
+
+    let mut empty = bvh_anim::Bvh::new();
+    empty.transform(math::mat_translation([1.0, 0.0, 0.0]));
+    assert_eq!(empty, bvh_anim::Bvh::new());
+}
+
+#[test]
+fn transform_rotation_rotates_every_joint() {
+    let original = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let matrix = math::mat_mul(
+        &math::mat_translation([0.0, 5.0, 0.0]),
+        &math::mat_rotation(bvh_anim::Axis::Y, 90.0),
+    );
+    let mut transformed = original.clone();
+    transformed.transform(matrix);
+
+    for (before, after) in original.frames().zip(transformed.frames()) {
+        let before = original.world_positions(&before);
+        let after = transformed.world_positions(&after);
+        for (b, a) in before.iter().zip(after.iter()) {
+            assert_close(*a, math::transform_point(&matrix, *b));
+        }
+    }
+}
+
+#[test]
+fn retain_joints_spine_only() {
+    use std::collections::HashSet;
+
+    let original = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let index_of = |name: &str| original.joints().find_by_name(name).unwrap().index();
+    let keep = ["Hips", "Chest", "Neck", "Head"]
+        .iter()
+        .map(|name| index_of(name))
+        .collect::<HashSet<_>>();
+
+    let mut spine = original.clone();
+    spine.retain_joints(&keep).unwrap();
+
+    let names = spine
+        .joints()
+        .map(|joint| joint.name().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(names, [&b"Hips"[..], b"Chest", b"Neck", b"Head"]);
+
+    let parents = spine
+        .joints()
+        .map(|joint| joint.parent_index())
+        .collect::<Vec<_>>();
+    assert_eq!(parents, [None, Some(0), Some(1), Some(2)]);
+
+    let chest2 = original.joints().find_by_name("Chest2").unwrap();
+    let neck = original.joints().find_by_name("Neck").unwrap();
+    let new_neck = spine.joints().find_by_name("Neck").unwrap();
+    for axis in 0..3 {
+        let expected = chest2.offset()[axis] + neck.offset()[axis];
+        assert!((new_neck.offset()[axis] - expected).abs() < 1.0e-5);
+    }
+
+    assert_eq!(spine.num_channels(), 6 + 3 * 3);
+    let original_head = original.joints().find_by_name("Head").unwrap();
+    let new_head = spine.joints().find_by_name("Head").unwrap();
+    assert_eq!(new_head.end_site(), original_head.end_site());
+    for (before, after) in original.frames().zip(spine.frames()) {
+        for (old, new) in original_head.channels().iter().zip(new_head.channels()) {
+            assert_eq!(before[old], after[new]);
+        }
+    }
+}
+
+#[test]
+fn retain_joints_requires_root() {
+    use std::collections::HashSet;
+
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let keep = [1, 2].iter().copied().collect::<HashSet<_>>();
+    let err = bvh.retain_joints(&keep).unwrap_err();
+    assert_eq!(err.root_index(), 0);
+    assert_eq!(bvh, bvh_anim::from_bytes(BVH_BYTES).unwrap());
+}
+
+#[test]
+fn regularize_euler_removes_flips() {
+    let mut bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            JOINT Tip
+            {
+                OFFSET 0.0 10.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 5
+        Frame Time: 0.033333333
+        170.0 10.0 20.0 0.0 0.0 0.0
+        178.0 10.0 20.0 0.0 0.0 0.0
+        -174.0 10.0 20.0 0.0 0.0 0.0
+        -166.0 10.0 20.0 -355.0 0.0 0.0
+        -158.0 10.0 20.0 10.0 0.0 0.0
+    };
+    let original = bvh.clone();
+    bvh.regularize_euler();
+
+    let frames = bvh
+        .frames()
+        .map(|frame| frame.as_slice().to_vec())
+        .collect::<Vec<_>>();
+    for pair in frames.windows(2) {
+        for (a, b) in pair[0].iter().zip(pair[1].iter()) {
+            assert!((a - b).abs() < 10.0, "{:?} -> {:?}", pair[0], pair[1]);
+        }
+    }
+
+    for (before, after) in original.frames().zip(bvh.frames()) {
+        let before = original.world_positions(&before);
+        let after = bvh.world_positions(&after);
+        for (b, a) in before.iter().zip(after.iter()) {
+            assert_close(*a, *b);
+        }
+    }
+}
+
+#[test]
+fn prune_constant_channels_preserves_motion() {
+    let mut bvh = bvh_anim::from_str(
+        r#"
+HIERARCHY
+ROOT Base
+{
+    OFFSET 0.0 0.0 0.0
+    CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    JOINT Middle
+    {
+        OFFSET 0.0 5.0 0.0
+        CHANNELS 3 Zrotation Xrotation Yrotation
+        JOINT Tip
+        {
+            OFFSET 0.0 5.0 0.0
+            CHANNELS 4 Xposition Zrotation Xrotation Yrotation
+            End Site
+            {
+                OFFSET 0.0 5.0 0.0
+            }
+        }
+    }
+}
+MOTION
+Frames: 3
+Frame Time: 0.033333333
+0.0 0.0 0.0 0.0 0.0 0.0 30.0 0.0 0.0 1.5 0.0 0.0 10.0
+0.0 0.0 0.0 0.0 0.0 0.0 30.0 0.0 20.0 1.5 0.0 0.0 20.0
+0.0 0.0 0.0 0.0 0.0 0.0 30.0 0.0 40.0 1.5 0.0 0.0 30.0
+"#,
+    )
+    .unwrap();
+    let original = bvh.clone();
+    bvh.prune_constant_channels(1.0e-4);
+
+    let root = bvh.root_joint().unwrap();
+    assert_eq!(root.channels().len(), 3);
+
+    let channel_types = |name: &str| {
+        bvh.joints()
+            .find_by_name(name)
+            .unwrap()
+            .channels()
+            .iter()
+            .map(|channel| channel.channel_type())
+            .collect::<Vec<_>>()
+    };
+    use bvh_anim::ChannelType::*;
+    assert_eq!(channel_types("Middle"), [RotationZ, RotationY]);
+    assert_eq!(channel_types("Tip"), [RotationY]);
+    assert_eq!(bvh.num_channels(), 6);
+    assert_eq!(
+        *bvh.joints().find_by_name("Tip").unwrap().offset(),
+        [1.5, 5.0, 0.0]
+    );
+
+    for (before, after) in original.frames().zip(bvh.frames()) {
+        let before = original.world_positions(&before);
+        let after = bvh.world_positions(&after);
+        for (b, a) in before.iter().zip(after.iter()) {
+            assert_close(*a, *b);
+        }
+    }
+}
+
+#[test]
+fn reduce_keyframes_collapses_linear_ramp() {
+    let mut bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Tip
+            {
+                OFFSET 0.0 10.0 0.0
+                CHANNELS 1 Zrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 5
+        Frame Time: 0.033333333
+        0.0 0.0 0.0 0.0 0.0 0.0 0.0
+        1.001 0.5 0.0 10.02 0.0 0.0 5.0
+        1.999 1.0 0.0 19.99 0.0 0.0 10.01
+        3.0 1.5 0.0 30.01 0.0 0.0 14.99
+        4.0 2.0 0.0 40.0 0.0 0.0 20.0
+    };
+    bvh.reduce_keyframes(0.05);
+
+    let last = bvh.frames().next_back().unwrap().as_slice().to_vec();
+    for (index, frame) in bvh.frames().enumerate() {
+        let t = index as f32 / 4.0;
+        for (&value, &end) in frame.as_slice().iter().zip(last.iter()) {
+            assert!((value - end * t).abs() < 1.0e-3, "{} != {}", value, end * t);
+        }
+    }
+}
+
+#[test]
+fn reduce_keyframes_keeps_corners() {
+    let mut bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            JOINT Tip
+            {
+                OFFSET 0.0 10.0 0.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 5
+        Frame Time: 0.033333333
+        0.0 0.0 0.0
+        10.0 0.0 0.0
+        20.0 0.0 0.0
+        20.0 10.0 0.0
+        20.0 20.0 0.0
+    };
+    let original = bvh.clone();
+    bvh.reduce_keyframes(0.01);
+
+    for (before, after) in original.frames().zip(bvh.frames()) {
+        for (b, a) in before.as_slice().iter().zip(after.as_slice().iter()) {
+            assert!((a - b).abs() < 1.0e-2, "{} != {}", a, b);
+        }
+    }
+}
+
+#[test]
+fn rotate_y_without_channels_rotates_offsets() {
+    let mut bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 1.0 0.0 0.0
+            CHANNELS 0
+            JOINT Chest
+            {
+                OFFSET 0.0 2.0 0.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 3.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 0
+        Frame Time: 0.033333333
+    };
+    bvh.rotate_y(90.0);
+
+    let mut joints = bvh.joints();
+    assert_close(*joints.next().unwrap().offset(), [0.0, 0.0, -1.0]);
+    assert_close(*joints.next().unwrap().offset(), [0.0, 2.0, 0.0]);
+
+    let mut empty = bvh_anim::Bvh::new();
+    empty.rotate_y(90.0);
+    assert_eq!(empty, bvh_anim::Bvh::new());
+}
+
+#[test]
+fn rotate_y_turns_path_and_facing() {
+    let mut bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Nose
+            {
+                OFFSET 0.0 0.0 1.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 0.0 1.0
+                }
+            }
+        }
+        MOTION
+        Frames: 3
+        Frame Time: 0.033333333
+        0.0 1.0 0.0 0.0 0.0 0.0
+        0.0 1.0 1.0 0.0 0.0 10.0
+        0.0 1.0 2.0 0.0 0.0 20.0
+    };
+    let original = bvh.clone();
+    bvh.rotate_y(90.0);
+
+    let rotation = math::mat_rotation(bvh_anim::Axis::Y, 90.0);
+    for (index, (before, after)) in original.frames().zip(bvh.frames()).enumerate() {
+        let before = original.world_positions(&before);
+        let after = bvh.world_positions(&after);
+
+        assert_close(after[0], [index as f32, 1.0, 0.0]);
+        for (b, a) in before.iter().zip(after.iter()) {
+            assert_close(*a, math::transform_point(&rotation, *b));
+        }
+    }
+
+    let frame = bvh.frames().next().unwrap();
+    let positions = bvh.world_positions(&frame);
+    let facing = [
+        positions[1][0] - positions[0][0],
+        positions[1][1] - positions[0][1],
+        positions[1][2] - positions[0][2],
+    ];
+    assert_close(facing, [1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn deduplicate_names_makes_names_unique() {
+    let mut bvh = bvh_anim::from_str(
+        r#"
+HIERARCHY
+ROOT Hips
+{
+    OFFSET 0.0 0.0 0.0
+    CHANNELS 3 Xposition Yposition Zposition
+    JOINT LeftHand
+    {
+        OFFSET 1.0 0.0 0.0
+        CHANNELS 1 Zrotation
+        End Site
+        {
+            OFFSET 1.0 0.0 0.0
+        }
+    }
+    JOINT LeftHand
+    {
+        OFFSET -1.0 0.0 0.0
+        CHANNELS 1 Zrotation
+        JOINT LeftHand.001
+        {
+            OFFSET -1.0 0.0 0.0
+            CHANNELS 0
+            End Site
+            {
+                OFFSET -1.0 0.0 0.0
+            }
+        }
+    }
+}
+MOTION
+Frames: 1
+Frame Time: 0.033333333
+0.0 1.0 2.0 3.0 4.0
+"#,
+    )
+    .unwrap();
+    let original = bvh.clone();
+    assert!(bvh.has_duplicate_names());
+
+    bvh.deduplicate_names();
+    assert!(!bvh.has_duplicate_names());
+
+    let names = bvh
+        .joints()
+        .map(|joint| String::from_utf8(joint.name().to_vec()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Hips", "LeftHand", "LeftHand.002", "LeftHand.001"]);
+
+    for (before, after) in original.joints().zip(bvh.joints()) {
+        assert_eq!(before.offset(), after.offset());
+        assert_eq!(
+            before.parent().map(|p| p.index()),
+            after.parent().map(|p| p.index())
+        );
+    }
+    assert_eq!(original.frames().next(), bvh.frames().next());
+}
+
+#[test]
+fn map_frames_zeroes_y_rotations() {
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let y_rotations = bvh
+        .joints()
+        .flat_map(|joint| joint.channels().to_vec())
+        .filter(|channel| channel.channel_type() == bvh_anim::ChannelType::RotationY)
+        .map(|channel| channel.motion_index())
+        .collect::<Vec<_>>();
+    assert!(!y_rotations.is_empty());
+
+    let original = bvh.clone();
+    let mut visited = Vec::new();
+    bvh.map_frames(|index, frame| {
+        visited.push(index);
+        for &column in &y_rotations {
+            frame[column] = 0.0;
+        }
+    });
+    assert_eq!(visited, (0..bvh.frames().len()).collect::<Vec<_>>());
+
+    for (before, after) in original.frames().zip(bvh.frames()) {
+        for (column, (b, a)) in before.as_slice().iter().zip(after.as_slice()).enumerate() {
+            if y_rotations.contains(&column) {
+                assert_eq!(*a, 0.0);
+            } else {
+                assert_eq!(a, b);
+            }
+        }
+    }
+}
+
+#[test]
+fn resize_frames_clamp_last_pads_with_final_frame() {
+    use bvh_anim::ResizeMode;
+
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    bvh.resize_frames(5, ResizeMode::ClampLast);
+    assert_eq!(bvh.frames().len(), 5);
+    let original = bvh.clone();
+
+    bvh.resize_frames(8, ResizeMode::ClampLast);
+    assert_eq!(bvh.frames().len(), 8);
+
+    let frames = bvh.frames().collect::<Vec<_>>();
+    let original_frames = original.frames().collect::<Vec<_>>();
+    assert_eq!(&frames[..5], &original_frames[..]);
+    for frame in &frames[5..] {
+        assert_eq!(frame, &original_frames[4]);
+    }
+    assert_eq!(bvh.frame_time(), original.frame_time());
+
+    let mut resampled = original.clone();
+    resampled.resize_frames(9, ResizeMode::Interpolate);
+    assert_eq!(resampled.frames().len(), 9);
+    assert_eq!(*resampled.frame_time(), *original.frame_time() / 2);
+    for (index, frame) in resampled.frames().enumerate() {
+        let before = &original_frames[index / 2];
+        let after = &original_frames[index.div_ceil(2)];
+        for (value, (a, b)) in frame
+            .as_slice()
+            .iter()
+            .zip(before.as_slice().iter().zip(after.as_slice()))
+        {
+            assert!((value - (a + b) / 2.0).abs() < 1.0e-4);
+        }
+    }
+}
+
+#[test]
+fn append_blended_interpolates_the_seam() {
+    let bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Chest
+            {
+                OFFSET 0.0 5.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 4
+        Frame Time: 0.033333333
+        0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+        0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+        0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+        0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+    };
+    let mut other = bvh.clone();
+    other.map_frames(|_, frame| {
+        frame[0] = 10.0;
+        frame[6] = 90.0;
+    });
+
+    let mut hard = bvh.clone();
+    hard.append_blended(&other, 0).unwrap();
+    assert_eq!(hard.frames().len(), 8);
+    assert_eq!(hard.frames().nth(3).unwrap()[0], 0.0);
+    assert_eq!(hard.frames().nth(4).unwrap()[0], 10.0);
+
+    let mut blended = bvh.clone();
+    blended.append_blended(&other, 3).unwrap();
+    assert_eq!(blended.frames().len(), 5);
+
+    let rows = blended.frame_rows().collect::<Vec<_>>();
+    for (row, t) in rows[1..4].iter().zip(&[0.25f32, 0.5, 0.75]) {
+        assert!((row[0] - 10.0 * t).abs() < 1.0e-4, "{:?}", row);
+        assert!((row[6] - 90.0 * t).abs() < 1.0e-2, "{:?}", row);
+        assert!(row[7].abs() < 1.0e-2 && row[8].abs() < 1.0e-2, "{:?}", row);
+    }
+    assert_eq!(rows[4][0], 10.0);
+
+    let mut other_time = other.clone();
+    other_time.set_frame_time(std::time::Duration::from_millis(10));
+    assert!(matches!(
+        blended.append_blended(&other_time, 3),
+        Err(bvh_anim::errors::ClipMismatchError::FrameTime { .. })
+    ));
+    assert_eq!(blended.frames().len(), 5);
+}
+
+#[test]
+fn renormalize_rotations_keeps_orientation() {
+    let mut bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Chest
+            {
+                OFFSET 0.0 5.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 2
+        Frame Time: 0.033333333
+        400.0 0.0 0.0 370.0 0.0 0.0 20.0 -350.0 725.0
+        0.0 0.0 0.0 10.0 0.0 0.0 0.0 0.0 0.0
+    };
+    let original = bvh.clone();
+
+    bvh.renormalize_rotations();
+
+    let first = bvh.frames().next().unwrap();
+    assert_eq!(first[0], 400.0);
+    assert_close([first[3], first[4], first[5]], [10.0, 0.0, 0.0]);
+    assert_close([first[6], first[7], first[8]], [20.0, 10.0, 5.0]);
+    for value in &first.as_slice()[3..] {
+        assert!((-180.0..=180.0).contains(value), "{}", value);
+    }
+
+    for (a, b) in original.frames().zip(bvh.frames()) {
+        for (p, q) in original
+            .world_positions(&a)
+            .iter()
+            .zip(bvh.world_positions(&b).iter())
+        {
+            assert_close(*p, *q);
+        }
+    }
+}
+
+#[test]
+fn ensure_end_sites_adds_missing_tips() {
+    const MISSING_BYTES: &[u8] = include_bytes!("../data/test_missing_end_site.bvh");
+    let mut bvh = bvh_anim::from_bytes(MISSING_BYTES).unwrap();
+
+    let end_sites = |bvh: &bvh_anim::Bvh| {
+        bvh.joints()
+            .map(|joint| joint.end_site().copied())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        end_sites(&bvh),
+        [None, None, None, Some([0.0, -15.0, 0.0]), None]
+    );
+
+    let parents = (0..bvh.joint_count())
+        .map(|index| bvh.parent_of(index))
+        .collect::<Vec<_>>();
+    assert_eq!(parents, [None, Some(0), Some(1), Some(0), Some(0)]);
+
+    bvh.ensure_end_sites(5.0);
+    assert_eq!(
+        end_sites(&bvh),
+        [
+            None,
+            None,
+            Some([0.0, 5.0, 0.0]),
+            Some([0.0, -15.0, 0.0]),
+            Some([5.0, 0.0, 0.0]),
+        ]
+    );
+
+    let written = bvh_anim::write::WriteOptions::new().write_to_string(&bvh);
+    assert_eq!(bvh_anim::from_bytes(&written).unwrap(), bvh);
+}
+
+#[test]
+fn append_motion_tolerates_rounded_frame_times() {
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let num_frames = bvh.frames().len();
+
+    let mut nearly_equal = bvh.clone();
+    nearly_equal.set_frame_time(*bvh.frame_time() + std::time::Duration::from_nanos(10));
+    let mut stitched = bvh.clone();
+    stitched.append_motion(&nearly_equal).unwrap();
+    assert_eq!(stitched.frames().len(), num_frames * 2);
+    assert_eq!(stitched.frame_time(), bvh.frame_time());
+
+    let mut different = bvh.clone();
+    different.set_frame_time(*bvh.frame_time() + std::time::Duration::from_millis(10));
+    let mut unchanged = bvh.clone();
+    assert!(matches!(
+        unchanged.append_motion(&different),
+        Err(bvh_anim::errors::ClipMismatchError::FrameTime { .. })
+    ));
+    assert_eq!(unchanged.frames().len(), num_frames);
+
+    unchanged
+        .append_motion_with_tolerance(&different, std::time::Duration::from_millis(20))
+        .unwrap();
+    assert_eq!(unchanged.frames().len(), num_frames * 2);
+}
+
+#[test]
+fn canonicalize_order_keeps_parsed_order() {
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let mut canonical = bvh.clone();
+    canonical.canonicalize_order();
+    assert_eq!(canonical, bvh);
+}
+
+#[test]
+fn trim_static_removes_padding() {
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let rows = bvh
+        .frames()
+        .map(|frame| frame.as_slice().to_vec())
+        .collect::<Vec<_>>();
+    let (first, last) = (rows[0].clone(), rows[rows.len() - 1].clone());
+
+    let mut padded_rows = vec![first; 5];
+    padded_rows.extend(rows.iter().cloned());
+    padded_rows.extend(vec![last; 7]);
+    let mut padded = bvh.clone().with_frames(padded_rows).unwrap();
+    assert_eq!(padded.frames().len(), rows.len() + 12);
+
+    let mut trimmed = bvh.clone();
+    trimmed.trim_static(1.0e-4);
+    padded.trim_static(1.0e-4);
+    assert!(trimmed.frames().len() <= rows.len());
+    assert_eq!(padded, trimmed);
+
+    let mut still = bvh.clone().with_frames(vec![rows[0].clone(); 4]).unwrap();
+    still.trim_static(1.0e-4);
+    assert_eq!(still.frames().len(), 1);
+}
+
+#[test]
+fn remove_and_insert_frame_in_the_middle() {
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let num_frames = bvh.num_frames();
+    let middle = num_frames / 2;
+    let removed = bvh.frame_values(middle).unwrap().to_vec();
+
+    let mut edited = bvh.clone();
+    edited.remove_frame(middle).unwrap();
+    assert_eq!(edited.num_frames(), num_frames - 1);
+    assert_eq!(
+        edited.frame_values(middle - 1),
+        bvh.frame_values(middle - 1)
+    );
+    assert_eq!(edited.frame_values(middle), bvh.frame_values(middle + 1));
+    assert!(edited.remove_frame(num_frames - 1).is_err());
+
+    edited.insert_frame(middle, &removed).unwrap();
+    assert_eq!(edited.num_frames(), num_frames);
+    assert_eq!(edited, bvh);
+
+    assert!(edited.insert_frame(num_frames + 1, &removed).is_err());
+    assert!(edited.insert_frame(0, &removed[1..]).is_err());
+    assert_eq!(edited, bvh);
+}
+
+#[test]
+fn clear_motion_keeps_hierarchy() {
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let original = bvh.clone();
+
+    bvh.clear_motion();
+    assert_eq!(bvh.num_frames(), 0);
+    assert_eq!(bvh.frame_time(), original.frame_time());
+    assert_eq!(bvh.num_channels(), original.num_channels());
+    assert!(bvh.joints().eq(original.joints()));
+
+    let written = bvh.to_string();
+    let reparsed = bvh_anim::from_bytes(&written).unwrap();
+    assert_eq!(reparsed, bvh);
+
+    bvh.push_frame(original.frame_values(0).unwrap());
+    assert_eq!(bvh.num_frames(), 1);
+}
+
+#[test]
+fn set_frame_time_keep_duration_resamples() {
+    let original = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let num_frames = original.num_frames();
+    let duration = original.frame_time().as_secs_f64() * (num_frames - 1) as f64;
+
+    let mut bvh = original.clone();
+    let new_frame_time = *original.frame_time() * 2;
+    bvh.set_frame_time_keep_duration(new_frame_time);
+    assert_eq!(*bvh.frame_time(), new_frame_time);
+    let expected = (duration / new_frame_time.as_secs_f64()).round() as usize + 1;
+    assert_eq!(bvh.num_frames(), expected);
+    let new_duration = new_frame_time.as_secs_f64() * (bvh.num_frames() - 1) as f64;
+    assert!((new_duration - duration).abs() <= new_frame_time.as_secs_f64() / 2.0);
+    assert_eq!(bvh.frame_values(0), original.frame_values(0));
+
+    let mut relabelled = original.clone();
+    relabelled.set_frame_time(new_frame_time);
+    assert_eq!(relabelled.num_frames(), num_frames);
+}
+
+#[test]
+fn rename_joints_round_trips() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let map: &[(&[u8], &[u8])] = &[
+        (b"Hips", b"pelvis"),
+        (b"Head", b"head"),
+        (b"LeftWrist", b"hand_l"),
+        (b"Tail", b"tail"),
+    ];
+    assert_eq!(bvh.rename_joints(map), 3);
+
+    let reparsed = bvh_anim::from_bytes(bvh.to_string()).unwrap();
+    for &new_name in &[&b"pelvis"[..], b"head", b"hand_l"] {
+        assert!(reparsed.joints().any(|joint| joint.name() == new_name));
+    }
+    assert!(!reparsed.joints().any(|joint| joint.name() == &b"Hips"[..]));
+    assert_eq!(reparsed.joints().count(), bvh.joints().count());
+}
+
+#[test]
+fn flip_handedness_reflects_positions_and_round_trips() {
+    use bvh_anim::Axis;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let original = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    for &axis in &[Axis::X, Axis::Y, Axis::Z] {
+        let mut bvh = original.clone();
+        bvh.flip_handedness(axis);
+
+        let frame = 7;
+        let before = original.world_positions(&original.frames().nth(frame).unwrap());
+        let after = bvh.world_positions(&bvh.frames().nth(frame).unwrap());
+        for (a, b) in before.iter().zip(&after) {
+            let mut expected = *a;
+            expected[axis as usize] = -expected[axis as usize];
+            for (x, y) in expected.iter().zip(b) {
+                assert!((x - y).abs() < 1.0e-3, "{:?} != {:?}", expected, b);
+            }
+        }
+
+        bvh.flip_handedness(axis);
+        assert_eq!(bvh, original);
+    }
+}
+
+#[test]
+fn add_channel_inserts_default_column() {
+    use bvh_anim::{errors::AddChannelError, ChannelType};
+
+    let original = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let mut bvh = original.clone();
+
+    match bvh.add_channel(1, ChannelType::RotationY, 0.0) {
+        Err(AddChannelError::DuplicateChannel {
+            joint_index: 1,
+            channel_type: ChannelType::RotationY,
+        }) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(bvh.add_channel(1000, ChannelType::RotationY, 0.0).is_err());
+    assert_eq!(bvh, original);
+
+    bvh.add_channel(1, ChannelType::PositionY, 2.5).unwrap();
+    assert_eq!(bvh.channel_index(1, ChannelType::PositionY), Some(9));
+    assert_eq!(bvh.channel_index(2, ChannelType::RotationZ), Some(10));
+    assert_eq!(bvh.frames().len(), original.frames().len());
+
+    for (before, after) in original.frames().zip(bvh.frames()) {
+        let (before, after) = (before.as_slice(), after.as_slice());
+        assert_eq!(after.len(), before.len() + 1);
+        assert_eq!(&after[..9], &before[..9]);
+        assert_eq!(after[9], 2.5);
+        assert_eq!(&after[10..], &before[9..]);
+    }
+
+    let reparsed = bvh_anim::from_bytes(bvh.to_string()).unwrap();
+    assert_eq!(reparsed.channel_index(1, ChannelType::PositionY), Some(9));
+}
+
+#[test]
+fn side_by_side_offsets_second_clip() {
+    use bvh_anim::errors::ClipMismatchError;
+
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let mut other = bvh.clone();
+    other.rotate_y(90.0);
+
+    let offset = [100.0, 0.0, -20.0];
+    let both = bvh_anim::side_by_side(&bvh, &other, offset).unwrap();
+    let num_joints = bvh.joints().count();
+    assert_eq!(both.joints().count(), 1 + 2 * num_joints);
+    assert_eq!(both.num_frames(), bvh.num_frames());
+    assert!(!both.has_duplicate_names());
+
+    for frame in [0, 200, 454].iter().copied() {
+        let combined = both.world_positions(&both.frames().nth(frame).unwrap());
+        let first = bvh.world_positions(&bvh.frames().nth(frame).unwrap());
+        let second = other.world_positions(&other.frames().nth(frame).unwrap());
+        for (a, b) in first.iter().zip(&combined[1..=num_joints]) {
+            assert_close(*a, *b);
+        }
+        for (a, b) in second.iter().zip(&combined[1 + num_joints..]) {
+            assert_close([a[0] + offset[0], a[1] + offset[1], a[2] + offset[2]], *b);
+        }
+    }
+
+    let reparsed = bvh_anim::from_bytes(both.to_string()).unwrap();
+    assert_eq!(reparsed.joints().count(), both.joints().count());
+
+    let mut shorter = bvh.clone();
+    shorter.remove_frame(0).unwrap();
+    match bvh_anim::side_by_side(&bvh, &shorter, offset) {
+        Err(ClipMismatchError::FrameCount { expected, actual }) => {
+            assert_eq!((expected, actual), (455, 454))
+        }
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn total_channels_matches_frame_width_after_edits() {
+    use bvh_anim::{Bvh, ChannelType};
+    use std::collections::HashSet;
+
+    fn assert_consistent(bvh: &Bvh) {
+        let declared = bvh
+            .joints()
+            .map(|joint| joint.channels().len())
+            .sum::<usize>();
+        assert_eq!(bvh.num_channels(), declared);
+        assert_eq!(bvh.total_channels(), declared);
+        for row in bvh.frame_rows() {
+            assert_eq!(row.len(), bvh.total_channels());
+        }
+    }
+
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    assert_eq!(bvh.total_channels(), bvh.frame_values(0).unwrap().len());
+    assert_consistent(&bvh);
+
+    bvh.add_channel(1, ChannelType::PositionX, 0.0).unwrap();
+    assert_consistent(&bvh);
+
+    bvh.prune_constant_channels(1.0e-6);
+    assert_consistent(&bvh);
+
+    let keep = (0..4).collect::<HashSet<_>>();
+    bvh.retain_joints(&keep).unwrap();
+    assert_consistent(&bvh);
+
+    let both = bvh_anim::side_by_side(&bvh, &bvh, [1.0, 0.0, 0.0]).unwrap();
+    assert_eq!(both.num_channels(), 2 * bvh.num_channels());
+    assert_consistent(&both);
+}
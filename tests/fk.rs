@@ -0,0 +1,355 @@
+use bvh_anim::{bvh, math, Axis};
+
+fn assert_mat_close(a: &math::Mat, b: &math::Mat) {
+    for (col_a, col_b) in a.iter().zip(b.iter()) {
+        for (x, y) in col_a.iter().zip(col_b.iter()) {
+            assert!((x - y).abs() < 1.0e-5, "{:?} != {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn local_transforms_root_is_translation_then_rotation() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 1.0 2.0 3.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Tip
+            {
+                OFFSET 0.0 10.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 1
+        Frame Time: 0.033333333
+        4.0 5.0 6.0 30.0 45.0 60.0 10.0 20.0 30.0
+    };
+
+    let frame = bvh.frames().next().unwrap();
+    let locals = bvh.local_transforms(&frame);
+    assert_eq!(locals.len(), 2);
+
+    let rotation = math::euler_to_mat([Axis::Z, Axis::X, Axis::Y], [30.0, 45.0, 60.0]);
+    let expected = math::mat_mul(&math::mat_translation([5.0, 7.0, 9.0]), &rotation);
+    assert_mat_close(&locals[0], &expected);
+
+    let worlds = bvh.world_transforms(&frame);
+    assert_mat_close(&worlds[0], &locals[0]);
+    assert_mat_close(&worlds[1], &math::mat_mul(&locals[0], &locals[1]));
+}
+
+#[test]
+fn scale_channels_scale_children() {
+    use bvh_anim::read::ParseOptions;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_scale_channels.bvh");
+
+    assert!(bvh_anim::from_bytes(BVH_BYTES).is_err());
+    let bvh = ParseOptions::new()
+        .allow_scale_channels(true)
+        .read_bytes(BVH_BYTES)
+        .unwrap();
+
+    let positions = bvh
+        .frames()
+        .map(|frame| bvh.world_positions(&frame))
+        .collect::<Vec<_>>();
+    assert_eq!(positions[0], [[0.0, 0.0, 0.0], [0.0, 5.0, 0.0]]);
+    assert_eq!(positions[1], [[1.0, 0.0, 0.0], [1.0, 10.0, 0.0]]);
+
+    let written = bvh.to_string();
+    let reparsed = ParseOptions::new()
+        .allow_scale_channels(true)
+        .read_bytes(&written)
+        .unwrap();
+    assert_eq!(reparsed, bvh);
+}
+
+#[test]
+fn world_transforms_into_reuses_buffer() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let mut buffer = vec![math::IDENTITY; 3];
+    for frame in bvh.frames() {
+        bvh.world_transforms_into(&frame, &mut buffer);
+        assert_eq!(buffer, bvh.world_transforms(&frame));
+    }
+    assert_eq!(buffer.len(), bvh.joints().count());
+}
+
+#[test]
+fn joint_axis_angle_of_single_and_composed_rotations() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            JOINT Tip
+            {
+                OFFSET 0.0 10.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 1
+        Frame Time: 0.033333333
+        0.0 0.0 90.0 30.0 40.0 0.0
+    };
+    let frame = bvh.frames().next().unwrap();
+
+    let (axis, angle) = bvh.joint_axis_angle(0, &frame);
+    for (a, b) in axis.iter().zip(&[0.0, 1.0, 0.0]) {
+        assert!((a - b).abs() < 1.0e-5, "{:?}", axis);
+    }
+    assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 1.0e-5);
+
+    let (axis, angle) = bvh.joint_axis_angle(1, &frame);
+    let length = axis.iter().map(|a| a * a).sum::<f32>().sqrt();
+    assert!((length - 1.0).abs() < 1.0e-5);
+    let expected = math::mat_mul(
+        &math::mat_rotation(Axis::Z, 30.0),
+        &math::mat_rotation(Axis::X, 40.0),
+    );
+    let point = [0.3, 0.5, -0.8];
+    let rotated = rotate_about_axis(point, axis, angle);
+    let transformed = math::transform_point(&expected, point);
+    for (a, b) in rotated.iter().zip(transformed.iter()) {
+        assert!((a - b).abs() < 1.0e-4, "{:?} != {:?}", rotated, transformed);
+    }
+}
+
+/// Rotate `p` about the unit `axis` by `angle` radians, using Rodrigues' formula.
+fn rotate_about_axis(p: [f32; 3], axis: [f32; 3], angle: f32) -> [f32; 3] {
+    let (s, c) = angle.sin_cos();
+    let dot = p[0] * axis[0] + p[1] * axis[1] + p[2] * axis[2];
+    let cross = [
+        axis[1] * p[2] - axis[2] * p[1],
+        axis[2] * p[0] - axis[0] * p[2],
+        axis[0] * p[1] - axis[1] * p[0],
+    ];
+    let mut out = [0.0; 3];
+    for i in 0..3 {
+        out[i] = p[i] * c + cross[i] * s + axis[i] * dot * (1.0 - c);
+    }
+    out
+}
+
+#[test]
+fn world_orientations_match_world_transforms() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    for frame in bvh.frames().step_by(10) {
+        let orientations = bvh.world_orientations(&frame);
+        let transforms = bvh.world_transforms(&frame);
+        assert_eq!(orientations.len(), transforms.len());
+        for (orientation, transform) in orientations.iter().zip(transforms.iter()) {
+            let mut rotation = *transform;
+            rotation[3] = [0.0, 0.0, 0.0, 1.0];
+            assert_mat_close(&math::quat_to_mat(*orientation), &rotation);
+        }
+    }
+}
+
+#[test]
+fn fk_is_consistent_with_offsets() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    assert_eq!(bvh.verify_fk_consistency(), Ok(()));
+}
+
+#[test]
+fn bone_segments_of_static_pose() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Xposition Yposition Zposition
+            JOINT Arm
+            {
+                OFFSET 10.0 0.0 0.0
+                CHANNELS 1 Zrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+            JOINT Leg
+            {
+                OFFSET 0.0 -10.0 0.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 -8.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 1
+        Frame Time: 0.033333333
+        0.0 2.0 0.0 90.0
+    };
+
+    let frame = bvh.frames().next().unwrap();
+    let segments = bvh.bone_segments(&frame);
+    let expected = [
+        ([0.0, 2.0, 0.0], [10.0, 2.0, 0.0]),
+        ([10.0, 2.0, 0.0], [5.0, 2.0, 0.0]),
+        ([0.0, 2.0, 0.0], [0.0, -8.0, 0.0]),
+        ([0.0, -8.0, 0.0], [0.0, -16.0, 0.0]),
+    ];
+    assert_eq!(segments.len(), expected.len());
+    for ((start, end), (expected_start, expected_end)) in segments.iter().zip(&expected) {
+        for i in 0..3 {
+            assert!(
+                (start[i] - expected_start[i]).abs() < 1.0e-4,
+                "{:?}",
+                segments
+            );
+            assert!((end[i] - expected_end[i]).abs() < 1.0e-4, "{:?}", segments);
+        }
+    }
+}
+
+#[test]
+fn relative_transform_to_self_is_identity() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let frame = bvh.frames().nth(10).unwrap();
+    let transforms = bvh.world_transforms(&frame);
+
+    for joint in 0..transforms.len() {
+        let relative = bvh.relative_transform(joint, joint, &frame).unwrap();
+        assert_mat_close(&relative, &math::IDENTITY);
+    }
+
+    let (a, b) = (2, transforms.len() - 1);
+    let relative = bvh.relative_transform(a, b, &frame).unwrap();
+    let composed = math::mat_mul(&transforms[a], &relative);
+    for (col_a, col_b) in composed.iter().zip(transforms[b].iter()) {
+        for (x, y) in col_a.iter().zip(col_b.iter()) {
+            assert!(
+                (x - y).abs() < 1.0e-3,
+                "{:?} != {:?}",
+                composed,
+                transforms[b]
+            );
+        }
+    }
+}
+
+#[test]
+fn position_records_cover_every_joint_and_frame() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let num_joints = bvh.joints().count();
+
+    let records = bvh.position_records().collect::<Vec<_>>();
+    assert_eq!(records.len(), bvh.num_frames() * num_joints);
+
+    let frame = bvh.num_frames() - 1;
+    let positions = bvh.world_positions(&bvh.frames().nth(frame).unwrap());
+    let (time, joint, position) = records[frame * num_joints + 3];
+    assert!((time - frame as f64 * bvh.frame_time().as_secs_f64()).abs() < 1.0e-9);
+    assert_eq!(joint, 3);
+    assert_eq!(position, positions[3]);
+}
+
+#[test]
+fn world_positions_at_time_interpolates_pose() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Tip
+            {
+                OFFSET 0.0 10.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 2
+        Frame Time: 0.5
+        0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+        2.0 0.0 0.0 90.0 0.0 0.0 0.0 0.0 0.0
+    };
+
+    let last = bvh.frames().nth(1).unwrap();
+    assert_eq!(bvh.world_positions_at_time(0.5), bvh.world_positions(&last));
+
+    let mut halfway = bvh.clone();
+    halfway
+        .frame_values_mut(0)
+        .unwrap()
+        .copy_from_slice(&[1.0, 0.0, 0.0, 45.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+    let expected = halfway.world_positions(&halfway.frames().next().unwrap());
+    let positions = bvh.world_positions_at_time(0.25);
+    assert_eq!(positions.len(), expected.len());
+    for (position, expected) in positions.iter().zip(&expected) {
+        for (a, b) in position.iter().zip(expected) {
+            assert!((a - b).abs() < 1.0e-4, "{:?} != {:?}", position, expected);
+        }
+    }
+}
+
+#[test]
+fn world_transforms_for_values_match_frames() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    for frame in bvh.frames().take(5) {
+        let expected = bvh.world_transforms(&frame);
+        let transforms = bvh.world_transforms_for_values(frame.as_slice()).unwrap();
+        assert_eq!(transforms, expected);
+    }
+
+    let err = bvh.world_transforms_for_values(&[0.0; 3]).unwrap_err();
+    assert_eq!(err.expected(), bvh.num_channels());
+    assert_eq!(err.actual(), 3);
+}
+
+#[test]
+fn joint_trajectory_matches_world_positions() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let root = bvh.joint_trajectory(0);
+    assert_eq!(root.len(), bvh.num_frames());
+    for (position, frame) in root.iter().zip(bvh.frames()) {
+        let translation = [frame[0], frame[1], frame[2]];
+        for (a, b) in position.iter().zip(&translation) {
+            assert!(
+                (a - b).abs() < 1.0e-4,
+                "{:?} != {:?}",
+                position,
+                translation
+            );
+        }
+    }
+
+    let leaf = *bvh.leaf_joints().last().unwrap();
+    let trail = bvh.joint_trajectory(leaf);
+    for (position, frame) in trail.iter().zip(bvh.frames()) {
+        assert_eq!(*position, bvh.world_positions(&frame)[leaf]);
+    }
+}
@@ -0,0 +1,291 @@
+use bvh_anim::{bvh, JointKind};
+
+#[test]
+fn find_joint_indexed_matches_find_by_name() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let index = bvh.build_name_index();
+
+    assert_eq!(index.len(), bvh.joints().count());
+    for joint in bvh.joints() {
+        let found = bvh.find_joint_indexed(&index, joint.name()).unwrap();
+        assert_eq!(found.index(), joint.index());
+        assert_eq!(
+            bvh.joints().find_by_name(joint.name()).unwrap().index(),
+            found.index()
+        );
+    }
+
+    assert!(bvh.find_joint_indexed(&index, "NotAJoint").is_none());
+}
+
+#[test]
+fn find_joint_indexed_stale_index() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            End Site
+            {
+                OFFSET 0.0 0.0 30.0
+            }
+        }
+        MOTION
+        Frames: 0
+        Frame Time: 0.033333333
+    };
+
+    let mut index = bvh.build_name_index();
+    index.insert("Tip".into(), 0);
+    index.insert("Gone".into(), 5);
+
+    assert!(bvh.find_joint_indexed(&index, "Tip").is_none());
+    assert!(bvh.find_joint_indexed(&index, "Gone").is_none());
+    assert_eq!(bvh.find_joint_indexed(&index, "Base").unwrap().index(), 0);
+}
+
+#[test]
+fn joint_path_round_trip() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let wrist = bvh.joints().find_by_name("RightWrist").unwrap().index();
+    let path = bvh.joint_path(wrist);
+    assert_eq!(
+        path,
+        "Hips/Chest/Chest2/RightCollar/RightShoulder/RightElbow/RightWrist"
+    );
+    assert_eq!(bvh.find_by_path(&path), Some(wrist));
+
+    for joint in bvh.joints() {
+        assert_eq!(
+            bvh.find_by_path(&bvh.joint_path(joint.index())),
+            Some(joint.index())
+        );
+    }
+
+    assert_eq!(bvh.joint_path(0), "Hips");
+    assert_eq!(bvh.find_by_path(b"Hips/RightWrist"), None);
+    assert_eq!(bvh.find_by_path(b"Chest"), None);
+}
+
+#[test]
+fn name_lossy_replaces_invalid_utf8() {
+    let bytes = b"HIERARCHY
+ROOT Hips
+{
+    OFFSET 0.0 0.0 0.0
+    CHANNELS 0
+    JOINT Left\xffHand
+    {
+        OFFSET 1.0 0.0 0.0
+        CHANNELS 0
+        End Site
+        {
+            OFFSET 1.0 0.0 0.0
+        }
+    }
+}
+MOTION
+Frames: 0
+Frame Time: 0.033333333
+";
+    let bvh = bvh_anim::from_bytes(&bytes[..]).unwrap();
+    let names = bvh
+        .joints()
+        .map(|joint| joint.name_lossy().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Hips", "Left\u{FFFD}Hand"]);
+    assert_eq!(bvh.joints().nth(1).unwrap().name(), b"Left\xffHand");
+}
+
+#[test]
+fn parent_of_follows_the_hierarchy() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    for joint in bvh.joints() {
+        assert_eq!(bvh.parent_of(joint.index()), joint.parent_index());
+    }
+
+    let mut chain = vec![];
+    let mut current = bvh.joints().last().map(|joint| joint.index());
+    while let Some(index) = current {
+        chain.push(index);
+        current = bvh.parent_of(index);
+    }
+    assert_eq!(chain.last(), Some(&0));
+    assert!(chain.windows(2).all(|pair| pair[1] < pair[0]));
+
+    assert_eq!(bvh.parent_of(0), None);
+    assert_eq!(bvh.parent_of(bvh.joint_count()), None);
+}
+
+#[test]
+fn channels_iter_covers_every_column() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let columns = bvh
+        .channels_iter()
+        .map(|(_, column, _)| column)
+        .collect::<Vec<_>>();
+    assert_eq!(columns, (0..bvh.num_channels()).collect::<Vec<_>>());
+
+    for (joint_index, column, channel) in bvh.channels_iter() {
+        let joint = bvh.joints().nth(joint_index).unwrap();
+        assert!(joint.channels().contains(&channel));
+        assert_eq!(channel.motion_index(), column);
+    }
+}
+
+#[test]
+fn leaf_joints_are_the_extremities() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let joints: Vec<_> = bvh.joints().collect();
+    let names: Vec<_> = bvh
+        .leaf_joints()
+        .into_iter()
+        .map(|index| joints[index].name().to_vec())
+        .collect();
+    let expected: &[&[u8]] = &[
+        b"LeftWrist",
+        b"RightWrist",
+        b"Head",
+        b"LeftAnkle",
+        b"RightAnkle",
+    ];
+    assert_eq!(names, expected);
+
+    for &index in &bvh.leaf_joints() {
+        assert!(joints[index].end_site().is_some());
+    }
+}
+
+#[test]
+fn roots_of_single_root_file() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    assert_eq!(bvh.roots().collect::<Vec<_>>(), [0]);
+    assert_eq!(bvh.num_roots(), 1);
+    for root in bvh.roots() {
+        assert_eq!(bvh.parent_of(root), None);
+    }
+
+    let empty = bvh_anim::Bvh::new();
+    assert_eq!(empty.num_roots(), 0);
+}
+
+#[test]
+fn frame_channels_pairs_every_channel() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let pairs = bvh.frame_channels(1).collect::<Vec<_>>();
+    assert_eq!(pairs.len(), bvh.num_channels());
+
+    let root = bvh.root_joint().unwrap();
+    let frame = bvh.frames().nth(1).unwrap();
+    let first = root.channels()[0];
+    assert_eq!(pairs[0], (first, frame[&first]));
+    for (channel, value) in pairs {
+        assert_eq!(value, frame[&channel]);
+    }
+
+    assert_eq!(bvh.frame_channels(bvh.num_frames()).count(), 0);
+}
+
+#[test]
+fn joint_kind_matches_hierarchy() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    for joint in bvh.joints() {
+        let expected = if joint.index() == 0 {
+            JointKind::Root
+        } else if joint.end_site().is_some() {
+            JointKind::EndSite
+        } else {
+            JointKind::Child
+        };
+        assert_eq!(joint.kind(), expected, "{}", joint.name_lossy());
+    }
+
+    let end_sites = bvh
+        .joints()
+        .filter(|joint| joint.kind() == JointKind::EndSite)
+        .count();
+    assert_eq!(end_sites, bvh.leaf_joints().len());
+}
+
+#[test]
+fn joint_by_index_children_match_parents() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    for index in 0..bvh.joints().count() {
+        let joint = bvh.joint(index).unwrap();
+        assert_eq!(joint.index(), index);
+
+        let children = joint
+            .children()
+            .map(|child| child.index())
+            .collect::<Vec<_>>();
+        let expected = bvh
+            .joints()
+            .filter(|other| other.parent_index() == Some(index))
+            .map(|other| other.index())
+            .collect::<Vec<_>>();
+        assert_eq!(children, expected);
+
+        for child in joint.children() {
+            assert_eq!(child.depth(), joint.depth() + 1);
+        }
+    }
+    assert!(bvh.joint(bvh.joints().count()).is_none());
+}
+
+#[test]
+fn offsets_in_joint_order() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let num_joints = bvh.joints().count();
+
+    let offsets = bvh.offsets(false);
+    assert_eq!(offsets.len(), num_joints);
+    assert_eq!(offsets[1], [-0.1728, 10.2870, 0.1254]);
+
+    let with_end_sites = bvh.offsets(true);
+    assert_eq!(with_end_sites.len(), num_joints + bvh.leaf_joints().len());
+    assert_eq!(with_end_sites[7], [1.4871, -13.9919, 1.1523]);
+}
+
+#[test]
+fn channel_index_matches_column_counting() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let mut column = 0;
+    for joint in bvh.joints() {
+        for channel in joint.channels() {
+            assert_eq!(
+                bvh.channel_index(joint.index(), channel.channel_type()),
+                Some(column)
+            );
+            column += 1;
+        }
+    }
+    assert_eq!(column, bvh.num_channels());
+
+    let num_joints = bvh.joints().count();
+    assert_eq!(
+        bvh.channel_index(num_joints, bvh_anim::ChannelType::RotationX),
+        None
+    );
+    assert_eq!(bvh.channel_index(1, bvh_anim::ChannelType::PositionX), None);
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "nalgebra")]
+
+use bvh_anim::bvh;
+use nalgebra::{Isometry3, Translation3, UnitQuaternion, Vector3};
+
+#[test]
+fn world_transforms_na_matches_hand_built_chain() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 1.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Tip
+            {
+                OFFSET 0.0 10.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 1
+        Frame Time: 0.033333333
+        0.0 2.0 0.0 90.0 0.0 0.0 0.0 45.0 0.0
+    };
+
+    let frame = bvh.frames().next().unwrap();
+    let transforms = bvh.world_transforms_na(&frame);
+
+    let base = Isometry3::from_parts(
+        Translation3::new(1.0, 2.0, 0.0),
+        UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 90.0f32.to_radians()),
+    );
+    let tip = base
+        * Isometry3::from_parts(
+            Translation3::new(0.0, 10.0, 0.0),
+            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 45.0f32.to_radians()),
+        );
+    assert!(
+        (transforms[0].to_homogeneous() - base.to_homogeneous())
+            .abs()
+            .max()
+            < 1.0e-5
+    );
+    assert!(
+        (transforms[1].to_homogeneous() - tip.to_homogeneous())
+            .abs()
+            .max()
+            < 1.0e-5
+    );
+
+    let positions = bvh.world_positions(&frame);
+    for (transform, position) in transforms.iter().zip(positions.iter()) {
+        let translation = transform.translation.vector;
+        assert!((translation - Vector3::from(*position)).abs().max() < 1.0e-4);
+    }
+
+    let rotation = bvh.joint_rotation_na(1, &frame);
+    let expected = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 45.0f32.to_radians());
+    assert!(rotation.angle_to(&expected) < 1.0e-4);
+
+    let tip_joint = bvh.joints().find_by_name("Tip").unwrap();
+    assert_eq!(tip_joint.offset_na(), Vector3::new(0.0, 10.0, 0.0));
+    assert_eq!(tip_joint.end_site_na(), Some(Vector3::new(0.0, 5.0, 0.0)));
+}
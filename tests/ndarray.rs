@@ -0,0 +1,22 @@
+#![cfg(feature = "ndarray")]
+
+use bvh_anim::Bvh;
+
+const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+
+#[test]
+fn motion_ndarray_round_trip() {
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let motion = bvh.motion_ndarray();
+    assert_eq!(motion.dim(), (bvh.frames().len(), bvh.num_channels()));
+    for (row, frame) in motion.rows().into_iter().zip(bvh.frames()) {
+        assert_eq!(row.as_slice().unwrap(), frame.as_slice());
+    }
+
+    let rebuilt = Bvh::from_motion_ndarray(&bvh, motion.view()).unwrap();
+    assert_eq!(rebuilt, bvh);
+
+    let too_narrow = motion.slice(ndarray::s![.., 1..]);
+    assert!(Bvh::from_motion_ndarray(&bvh, too_narrow).is_err());
+}
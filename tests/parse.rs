@@ -1,4 +1,4 @@
-use bvh_anim;
+use bstr::ByteSlice;
 use pretty_assertions::assert_eq;
 use std::{fs::File, io::BufReader};
 
@@ -130,3 +130,992 @@ fn nonstandard_formatting() {
 
     assert_eq!(bvh, bvh_from_macro);
 }
+
+#[test]
+fn parse_channels_zero() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_channels_zero.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    assert_eq!(bvh.num_channels(), 9);
+    assert_eq!(bvh.frames().len(), 2);
+
+    let pivot = bvh.joints().find_by_name("Pivot").unwrap();
+    assert!(pivot.channels().is_empty());
+
+    let end = bvh.joints().find_by_name("End").unwrap();
+    let end_motion_indices = end
+        .channels()
+        .iter()
+        .map(|c| c.motion_index())
+        .collect::<Vec<_>>();
+    assert_eq!(end_motion_indices, [6, 7, 8]);
+
+    let frame = bvh.frames().nth(1).unwrap();
+    let positions = bvh.world_positions(&frame);
+    assert_eq!(positions[pivot.index()], [1.0, 7.0, 3.0]);
+    assert_eq!(positions[end.index()], [1.0, 7.0, 18.0]);
+}
+
+#[test]
+fn channel_type_round_trip() {
+    use bvh_anim::ChannelType;
+
+    for &channel_type in &ChannelType::ALL {
+        let string = channel_type.to_string();
+        assert_eq!(string.parse::<ChannelType>().unwrap(), channel_type);
+        assert_eq!(ChannelType::from_bytes(&string).unwrap(), channel_type);
+    }
+
+    assert_eq!(
+        "Xrotation".parse::<ChannelType>().unwrap(),
+        ChannelType::RotationX
+    );
+    assert!("xrotation".parse::<ChannelType>().is_err());
+}
+
+#[test]
+fn parse_wrapped_motion_rows() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_wrapped_rows.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let frames = bvh
+        .frames()
+        .map(|frame| frame.as_slice().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        frames,
+        [
+            [0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8],
+            [1.0, 1.1, 1.2, 1.3, 1.4, 1.5, 1.6, 1.7, 1.8],
+            [2.0, 2.1, 2.2, 2.3, 2.4, 2.5, 2.6, 2.7, 2.8],
+        ]
+    );
+}
+
+#[test]
+fn parse_non_finite_policies() {
+    use bvh_anim::{
+        errors::{LoadErrorKind, LoadMotionError},
+        read::{NonFinitePolicy, ParseOptions},
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_non_finite.bvh");
+
+    let err = bvh_anim::from_bytes(BVH_BYTES).unwrap_err();
+    match err.kind() {
+        LoadErrorKind::Motion(LoadMotionError::NonFiniteMotionValue { channel_index, .. }) => {
+            assert_eq!(*channel_index, 4);
+        }
+        kind => panic!("Unexpected error: {:?}", kind),
+    }
+    assert_eq!(err.line(), Some(18));
+
+    let zeroed = ParseOptions::new()
+        .on_non_finite(NonFinitePolicy::Zero)
+        .read_bytes(BVH_BYTES)
+        .unwrap();
+    let frames = zeroed
+        .frames()
+        .map(|frame| frame.as_slice().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        frames,
+        [
+            [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 1.0, 0.0],
+        ]
+    );
+
+    let kept = ParseOptions::new()
+        .on_non_finite(NonFinitePolicy::Keep)
+        .read_bytes(BVH_BYTES)
+        .unwrap();
+    let mut frames = kept.frames();
+    assert!(frames.next().unwrap().as_slice()[4].is_nan());
+    let second = frames.next().unwrap();
+    assert_eq!(second.as_slice()[6], f32::INFINITY);
+    assert_eq!(second.as_slice()[8], f32::NEG_INFINITY);
+}
+
+#[test]
+fn metadata_matches_full_parse() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let metadata = bvh_anim::Bvh::metadata_from_reader(&mut &BVH_BYTES[..]).unwrap();
+
+    assert_eq!(metadata.num_joints, bvh.joints().count());
+    assert_eq!(metadata.num_channels, bvh.num_channels());
+    assert_eq!(metadata.num_frames, bvh.frames().len());
+    assert_eq!(metadata.frame_time, *bvh.frame_time());
+
+    // The motion values are never read, so invalid values do not cause an error.
+    let mut truncated = BVH_BYTES.to_vec();
+    let motion_start = truncated.find("Frame Time").unwrap();
+    let motion_start = motion_start + truncated[motion_start..].find_byte(b'\n').unwrap();
+    truncated.truncate(motion_start + 1);
+    truncated.extend_from_slice(b"not a number\n");
+    assert!(bvh_anim::from_bytes(&truncated).is_err());
+    let truncated_metadata = bvh_anim::Bvh::metadata_from_reader(&mut &truncated[..]).unwrap();
+    assert_eq!(truncated_metadata, metadata);
+}
+
+#[test]
+fn error_categories() {
+    use bvh_anim::errors::{LoadErrorCategory, LoadErrorKind, LoadMotionError};
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_simple.bvh");
+    let text = BVH_BYTES.to_str().unwrap();
+
+    let short = &text[..text.trim_end().rfind('\n').unwrap()];
+    let err = bvh_anim::from_str(short).unwrap_err();
+    assert_eq!(err.category(), LoadErrorCategory::FrameCountMismatch);
+    match err.kind() {
+        LoadErrorKind::Motion(LoadMotionError::MotionCountMismatch {
+            expected_num_frames,
+            ..
+        }) => assert_eq!(*expected_num_frames, 2),
+        kind => panic!("Unexpected error: {:?}", kind),
+    }
+
+    let no_motion = &text[..text.find("MOTION").unwrap()];
+    let err = bvh_anim::from_str(no_motion).unwrap_err();
+    assert_eq!(err.category(), LoadErrorCategory::MissingSection);
+
+    let bad_value = text.replacen("1.0", "1.0x", 1);
+    let err = bvh_anim::from_str(&bad_value).unwrap_err();
+    assert_eq!(err.category(), LoadErrorCategory::InvalidNumber);
+}
+
+#[test]
+fn identically_parsed_clips_are_equal() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let a = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let b = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    assert_eq!(a, b);
+    assert!(a.approx_eq(&b, 0.0));
+
+    let mut c = b.clone();
+    c.frames_mut().next().unwrap().as_mut_slice()[0] += 1.0e-3;
+    assert_ne!(a, c);
+    assert!(a.approx_eq(&c, 1.0e-2));
+    assert!(!a.approx_eq(&c, 1.0e-4));
+}
+
+#[test]
+fn parse_quoted_names() {
+    use bvh_anim::{read::ParseOptions, write::WriteOptions};
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_quoted_names.bvh");
+    let options = ParseOptions::new().with_quoted_names(true);
+    let bvh = options.read_bytes(BVH_BYTES).unwrap();
+
+    let names = bvh
+        .joints()
+        .map(|joint| joint.name().to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(names, [&b"Root Joint"[..], b"Left Hand", b"Say \"Hi\""]);
+
+    let written = WriteOptions::new().write_to_string(&bvh);
+    assert!(written.find(r#"JOINT "Say \"Hi\"""#).is_some());
+    assert_eq!(options.read_bytes(&written).unwrap(), bvh);
+
+    // Without quoted names, only the first token is used as the name.
+    let unquoted = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    assert_eq!(unquoted.joints().nth(1).unwrap().name(), b"\"Left");
+}
+
+#[test]
+fn parse_mixed_whitespace() {
+    const MIXED_BYTES: &[u8] = include_bytes!("../data/test_mixed_whitespace.bvh");
+    const CLEAN_BYTES: &[u8] = include_bytes!("../data/test_simple.bvh");
+
+    let mixed = bvh_anim::from_bytes(MIXED_BYTES).unwrap();
+    let clean = bvh_anim::from_bytes(CLEAN_BYTES).unwrap();
+    assert_eq!(mixed, clean);
+}
+
+#[test]
+fn frame_byte_ranges() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_wrapped_rows.bvh");
+    let bvh = bvh_anim::Bvh::parse_preserving(BVH_BYTES).unwrap();
+
+    let range = bvh.frame_byte_range(1).unwrap();
+    assert!(BVH_BYTES[range.clone()].starts_with(b"1.0 1.1"));
+    assert!(BVH_BYTES[range].ends_with(b"1.8"));
+
+    assert!(bvh.frame_byte_range(3).is_none());
+    assert!(bvh_anim::from_bytes(BVH_BYTES)
+        .unwrap()
+        .frame_byte_range(0)
+        .is_none());
+}
+
+#[test]
+fn offset_value_count_strict_and_lenient() {
+    use bvh_anim::{
+        diagnostic::DiagnosticKind,
+        errors::{LoadErrorKind, LoadJointsError},
+        read::ParseOptions,
+        Axis,
+    };
+
+    const TWO_VALUES: &[u8] = include_bytes!("../data/test_offset_two_values.bvh");
+    const SIX_VALUES: &[u8] = include_bytes!("../data/test_offset_six_values.bvh");
+
+    let err = bvh_anim::from_bytes(TWO_VALUES).unwrap_err();
+    match err.kind() {
+        LoadErrorKind::Joints(LoadJointsError::MissingOffsetAxis { axis, .. }) => {
+            assert_eq!(*axis, Axis::Z);
+        }
+        kind => panic!("Unexpected error: {:?}", kind),
+    }
+    assert_eq!(err.line(), Some(7));
+
+    let err = bvh_anim::from_bytes(SIX_VALUES).unwrap_err();
+    match err.kind() {
+        LoadErrorKind::Joints(LoadJointsError::ExtraOffsetValues { count, .. }) => {
+            assert_eq!(*count, 6);
+        }
+        kind => panic!("Unexpected error: {:?}", kind),
+    }
+    assert_eq!(err.line(), Some(7));
+
+    let lenient = ParseOptions::new().with_lenient(true);
+    for &(bytes, offset, count) in &[
+        (TWO_VALUES, [1.0, 15.0, 0.0], 2),
+        (SIX_VALUES, [1.0, 15.0, 2.0], 6),
+    ] {
+        let (bvh, diagnostics) = lenient.read_with_diagnostics(bytes).unwrap();
+        let joint = bvh.joints().find_by_name("End").unwrap();
+        assert_eq!(*joint.offset(), offset);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].kind,
+            DiagnosticKind::OffsetValueCount { count }
+        );
+        assert_eq!(diagnostics[0].line, Some(7));
+    }
+}
+
+#[test]
+fn frame_stride_matches_full_parse() {
+    use bvh_anim::read::ParseOptions;
+    use std::num::NonZeroUsize;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+
+    let full = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let strided = ParseOptions::new()
+        .frame_stride(NonZeroUsize::new(2).unwrap())
+        .read_bytes(BVH_BYTES)
+        .unwrap();
+
+    let expected = full.frames().step_by(2).collect::<Vec<_>>();
+    let actual = strided.frames().collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+    assert_eq!(*strided.frame_time(), *full.frame_time() * 2);
+    assert_eq!(strided.num_channels(), full.num_channels());
+}
+
+#[test]
+fn frame_stride_frame_time_overflow_is_an_error() {
+    use bvh_anim::{
+        errors::{LoadErrorKind, LoadMotionError},
+        read::ParseOptions,
+    };
+    use std::num::NonZeroUsize;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = String::from_utf8_lossy(BVH_BYTES).replacen(
+        "Frame Time:\t0.033333",
+        "Frame Time:\t10000000000000000000",
+        1,
+    );
+    assert!(bvh.contains("10000000000000000000"));
+
+    let result = ParseOptions::new()
+        .frame_stride(NonZeroUsize::new(1 << 20).unwrap())
+        .read_bytes(bvh.as_bytes());
+    match result.unwrap_err().kind() {
+        LoadErrorKind::Motion(LoadMotionError::FrameTimeOverflow { stride, .. }) => {
+            assert_eq!(*stride, 1 << 20)
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn motion_header_variants() {
+    use std::time::Duration;
+
+    const NO_SPACE: &[u8] = include_bytes!("../data/test_frame_time_no_space.bvh");
+    const SCIENTIFIC: &[u8] = include_bytes!("../data/test_frame_time_scientific.bvh");
+    const SPACING: &[u8] = include_bytes!("../data/test_frame_time_spacing.bvh");
+
+    for &(bytes, frame_time) in &[
+        (NO_SPACE, 0.0083333),
+        (SCIENTIFIC, 8.3e-3),
+        (SPACING, 8.3e-3),
+    ] {
+        let bvh = bvh_anim::from_bytes(bytes).unwrap();
+        assert_eq!(*bvh.frame_time(), Duration::from_secs_f64(frame_time));
+        assert_eq!(bvh.frames().len(), 2);
+    }
+
+    let negative = std::str::from_utf8(SCIENTIFIC)
+        .unwrap()
+        .replace("8.3e-3", "-8.3e-3");
+    assert!(bvh_anim::from_str(&negative).is_err());
+}
+
+#[test]
+fn end_site_channels_strict_and_lenient() {
+    use bvh_anim::{
+        diagnostic::DiagnosticKind,
+        errors::{LoadErrorKind, LoadJointsError},
+        read::ParseOptions,
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_end_site_channels.bvh");
+
+    let err = bvh_anim::from_bytes(BVH_BYTES).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Joints(LoadJointsError::EndSiteChannels { .. })
+    ));
+    assert_eq!(err.line(), Some(12));
+
+    let (bvh, diagnostics) = ParseOptions::new()
+        .with_lenient(true)
+        .read_with_diagnostics(BVH_BYTES)
+        .unwrap();
+    assert_eq!(bvh.num_channels(), 9);
+    assert_eq!(bvh.frames().len(), 2);
+    let end = bvh.joints().find_by_name("End").unwrap();
+    assert_eq!(end.channels().len(), 3);
+    assert_eq!(end.end_site(), Some(&[0.0, 0.0, 30.0]));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::EndSiteChannelsIgnored);
+    assert_eq!(diagnostics[0].line, Some(12));
+}
+
+#[test]
+fn frame_rows_match_frames() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let mut total = 0;
+    for (row, frame) in bvh.frame_rows().zip(bvh.frames()) {
+        assert_eq!(row.len(), bvh.num_channels());
+        assert_eq!(row, frame.as_slice());
+        total += row.len();
+    }
+    assert_eq!(total, bvh.frames().len() * bvh.num_channels());
+    assert_eq!(bvh.frame_rows().count(), bvh.frames().len());
+
+    assert_eq!(bvh_anim::Bvh::new().frame_rows().count(), 0);
+}
+
+#[test]
+fn leading_frame_column() {
+    use bvh_anim::{
+        errors::{LoadErrorKind, LoadMotionError},
+        read::ParseOptions,
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_frame_index_column.bvh");
+
+    let bvh = ParseOptions::new()
+        .with_leading_frame_column(true)
+        .read_bytes(BVH_BYTES)
+        .unwrap();
+    assert_eq!(bvh.num_channels(), 9);
+    assert_eq!(bvh.frames().len(), 3);
+    for (i, frame) in bvh.frames().enumerate() {
+        assert_eq!(frame.as_slice(), &[i as f32; 9][..]);
+    }
+
+    assert!(bvh_anim::from_bytes(BVH_BYTES).is_err());
+
+    let source = std::str::from_utf8(BVH_BYTES).unwrap();
+    let skipped = source.replace("\n2 2.0", "\n3 2.0");
+    let err = ParseOptions::new()
+        .with_leading_frame_column(true)
+        .read_bytes(skipped)
+        .unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Motion(LoadMotionError::FrameIndexMismatch {
+            expected: Some(2),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn missing_hierarchy_strict_and_lenient() {
+    use bvh_anim::{
+        diagnostic::DiagnosticKind,
+        errors::{LoadErrorKind, LoadJointsError},
+        read::ParseOptions,
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_missing_hierarchy.bvh");
+
+    let err = bvh_anim::from_bytes(BVH_BYTES).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Joints(LoadJointsError::MissingHierarchy { line: 0 })
+    ));
+
+    let (bvh, diagnostics) = ParseOptions::new()
+        .with_lenient(true)
+        .read_with_diagnostics(BVH_BYTES)
+        .unwrap();
+
+    let mut with_header = b"HIERARCHY\n".to_vec();
+    with_header.extend_from_slice(BVH_BYTES);
+    let expected = bvh_anim::from_bytes(&with_header).unwrap();
+    assert_eq!(bvh, expected);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::MissingHierarchy);
+    assert_eq!(diagnostics[0].line, Some(0));
+}
+
+#[test]
+fn from_parts_recombines_hierarchy_and_motion() {
+    use bstr::ByteSlice;
+    use bvh_anim::{
+        errors::{LoadErrorKind, LoadMotionError},
+        Bvh,
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let written = bvh.to_string();
+    let split = written.find("MOTION").unwrap();
+    let (hierarchy, motion) = written.split_at(split);
+    let recombined = Bvh::from_parts(hierarchy, motion).unwrap();
+    assert_eq!(recombined, bvh);
+
+    let rows = bvh
+        .frame_rows()
+        .map(|row| {
+            row.iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let bare = Bvh::from_parts(hierarchy, rows.as_bytes()).unwrap();
+    assert_eq!(bare.num_frames(), bvh.num_frames());
+    assert!(bare.frames().eq(bvh.frames()));
+
+    let err = Bvh::from_parts(hierarchy, &b"1.0 2.0 3.0\n"[..]).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Motion(LoadMotionError::MotionRowWidthMismatch {
+            actual_num_values: 3,
+            line: 0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn short_motion_row_strict_and_lenient() {
+    use bvh_anim::{
+        diagnostic::DiagnosticKind,
+        errors::{LoadErrorKind, LoadMotionError},
+        read::ParseOptions,
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_short_motion_row.bvh");
+
+    let err = bvh_anim::from_bytes(BVH_BYTES).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Motion(LoadMotionError::MisalignedMotionRow { frame_index: 2, .. })
+    ));
+    assert_eq!(err.line(), Some(20));
+
+    let (bvh, diagnostics) = ParseOptions::new()
+        .with_lenient(true)
+        .read_with_diagnostics(BVH_BYTES)
+        .unwrap();
+    assert_eq!(bvh.num_frames(), 3);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].kind,
+        DiagnosticKind::MisalignedMotionRow { frame_index: 2 }
+    );
+    assert_eq!(diagnostics[0].line, Some(20));
+}
+
+#[test]
+fn huge_declared_frame_count() {
+    use bvh_anim::{
+        diagnostic::DiagnosticKind,
+        errors::{LoadErrorKind, LoadMotionError},
+        read::ParseOptions,
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_frame_time_spacing.bvh");
+    let source = std::str::from_utf8(BVH_BYTES).unwrap();
+    let huge = source.replace("Frames\t:\t 2", "Frames: 4000000000");
+    assert_ne!(huge, source);
+
+    let err = bvh_anim::from_str(&huge).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Motion(LoadMotionError::MotionCountMismatch {
+            actual_total_motion_values: 18,
+            expected_num_frames: 4_000_000_000,
+            ..
+        })
+    ));
+
+    let (bvh, diagnostics) = ParseOptions::new()
+        .with_lenient(true)
+        .read_with_diagnostics(huge.as_bytes())
+        .unwrap();
+    assert_eq!(bvh.num_frames(), 2);
+    assert_eq!(
+        diagnostics,
+        [bvh_anim::diagnostic::Diagnostic::new(
+            DiagnosticKind::FrameCountMismatch {
+                expected: 4_000_000_000,
+                actual: 2,
+            },
+            None,
+        )]
+    );
+}
+
+#[test]
+fn frame_rows_mut_negates_every_value() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let original = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let mut bvh = original.clone();
+
+    let mut num_rows = 0;
+    for row in bvh.frame_rows_mut() {
+        assert_eq!(row.len(), original.num_channels());
+        for value in row.iter_mut() {
+            *value = -*value;
+        }
+        num_rows += 1;
+    }
+    assert_eq!(num_rows, original.num_frames());
+
+    for (row, original_row) in bvh.frame_rows().zip(original.frame_rows()) {
+        for (value, original_value) in row.iter().zip(original_row.iter()) {
+            assert_eq!(*value, -*original_value);
+        }
+    }
+
+    assert_eq!(bvh_anim::Bvh::new().frame_rows_mut().count(), 0);
+}
+
+#[test]
+fn frame_index_at_time_rounds_to_nearest() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let frame_time = bvh.frame_time().as_secs_f64();
+    let last = bvh.num_frames() - 1;
+
+    assert_eq!(bvh.frame_index_at_time(5.0 * frame_time + 1.0e-6), 5);
+    assert_eq!(bvh.frame_index_at_time(5.4 * frame_time), 5);
+    assert_eq!(bvh.frame_index_at_time(5.6 * frame_time), 6);
+    assert_eq!(bvh.frame_index_at_time(-frame_time), 0);
+    assert_eq!(bvh.frame_index_at_time(1.0e9), last);
+    assert_eq!(bvh.frame_index_at_time(f64::NAN), 0);
+}
+
+#[test]
+fn frame_values_is_bounds_checked() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let last = bvh.num_frames() - 1;
+
+    let expected = bvh.frames().nth(last).unwrap().as_slice().to_vec();
+    assert_eq!(bvh.frame_values(last), Some(&expected[..]));
+    assert_eq!(bvh.frame_values(last + 1), None);
+    assert_eq!(bvh.frame_values(usize::MAX), None);
+
+    bvh.frame_values_mut(0).unwrap()[0] = 42.0;
+    assert_eq!(bvh.frames().next().unwrap().as_slice()[0], 42.0);
+    assert!(bvh.frame_values_mut(last + 1).is_none());
+}
+
+#[test]
+fn parse_from_stops_after_last_frame() {
+    use bvh_anim::Bvh;
+    use std::io::{Cursor, Read, Seek};
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    const TRAILER: &[u8] = b"--- custom container data ---\n\x00\x01\x02";
+
+    let mut container = BVH_BYTES.to_vec();
+    if !container.ends_with(b"\n") {
+        container.push(b'\n');
+    }
+    let bvh_len = container.len();
+    container.extend_from_slice(TRAILER);
+
+    let mut reader = Cursor::new(&container[..]);
+    let bvh = Bvh::parse_from(&mut reader).unwrap();
+    assert_eq!(bvh, bvh_anim::from_bytes(BVH_BYTES).unwrap());
+    assert_eq!(reader.stream_position().unwrap(), bvh_len as u64);
+
+    let mut rest = vec![];
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, TRAILER);
+
+    // The trailing data is not a motion value, so reading to the end fails.
+    assert!(bvh_anim::from_bytes(&container).is_err());
+}
+
+#[test]
+fn channel_count_mismatch_strict_and_lenient() {
+    use bvh_anim::{
+        diagnostic::DiagnosticKind,
+        errors::{LoadErrorKind, LoadJointsError},
+        read::ParseOptions,
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_channel_count_mismatch.bvh");
+
+    let err = bvh_anim::from_bytes(BVH_BYTES).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Joints(LoadJointsError::ChannelCountMismatch {
+            expected: 3,
+            actual: 2,
+            line: 8,
+        })
+    ));
+
+    let (bvh, diagnostics) = ParseOptions::new()
+        .with_lenient(true)
+        .read_with_diagnostics(BVH_BYTES)
+        .unwrap();
+    assert_eq!(bvh.num_channels(), 8);
+    assert_eq!(bvh.num_frames(), 2);
+    let end = bvh.joints().find_by_name("End").unwrap();
+    assert_eq!(end.channels().len(), 2);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].kind,
+        DiagnosticKind::ChannelCountMismatch {
+            expected: 3,
+            actual: 2,
+        }
+    );
+    assert_eq!(diagnostics[0].line, Some(8));
+}
+
+#[test]
+fn reload_motion_replaces_frames() {
+    use std::{io::Cursor, time::Duration};
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let skeleton = bvh
+        .joints()
+        .map(|joint| joint.name().to_vec())
+        .collect::<Vec<_>>();
+    let num_channels = bvh.num_channels();
+
+    let mut motion = String::from("MOTION\nFrames: 3\nFrame Time: 0.01\n");
+    for i in 0..3 {
+        let row = vec![i.to_string(); num_channels].join(" ");
+        motion.push_str(&row);
+        motion.push('\n');
+    }
+
+    bvh.reload_motion(&mut Cursor::new(motion)).unwrap();
+    assert_eq!(bvh.num_frames(), 3);
+    assert_eq!(*bvh.frame_time(), Duration::from_millis(10));
+    assert_eq!(bvh.frame_values(2).unwrap(), &vec![2.0; num_channels][..]);
+    assert_eq!(
+        bvh.joints()
+            .map(|joint| joint.name().to_vec())
+            .collect::<Vec<_>>(),
+        skeleton
+    );
+
+    let short_row = "Frames: 1\n0.0 0.0\n";
+    assert!(bvh.reload_motion(&mut Cursor::new(short_row)).is_err());
+    assert_eq!(bvh.num_frames(), 3);
+}
+
+#[test]
+fn parse_cr_line_endings() {
+    const CR_BYTES: &[u8] = include_bytes!("../data/test_cr_line_endings.bvh");
+    const LF_BYTES: &[u8] = include_bytes!("../data/test_simple.bvh");
+
+    let lf = bvh_anim::from_bytes(LF_BYTES).unwrap();
+    assert_eq!(bvh_anim::from_bytes(CR_BYTES).unwrap(), lf);
+
+    let crlf = LF_BYTES.replace("\n", "\r\n");
+    assert_eq!(bvh_anim::from_bytes(&crlf).unwrap(), lf);
+
+    // A `\r\n` split between two reads of the buffer is still a single line break.
+    let mut reader = BufReader::with_capacity(1, &crlf[..]);
+    assert_eq!(bvh_anim::Bvh::parse_from(&mut reader).unwrap(), lf);
+}
+
+#[test]
+fn sample_at_phase_interpolates_frames() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let last = bvh.num_frames() - 1;
+
+    assert_eq!(
+        bvh.sample_at_phase(0.0).unwrap(),
+        bvh.frame_values(0).unwrap()
+    );
+    assert_eq!(
+        bvh.sample_at_phase(1.0).unwrap(),
+        bvh.frame_values(last).unwrap()
+    );
+
+    let before = bvh.frame_values(last / 2).unwrap();
+    let after = bvh.frame_values(last.div_ceil(2)).unwrap();
+    let middle = bvh.sample_at_phase(0.5).unwrap();
+    assert_eq!(middle.len(), before.len());
+    for ((value, a), b) in middle.iter().zip(before).zip(after) {
+        assert!(
+            (value - (a + b) / 2.0).abs() < 1.0e-3,
+            "{} {} {}",
+            value,
+            a,
+            b
+        );
+    }
+
+    assert!(bvh_anim::Bvh::new().sample_at_phase(0.5).is_none());
+}
+
+#[test]
+fn interpolate_euler_across_wrap() {
+    let bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            End Site
+            {
+                OFFSET 0.0 5.0 0.0
+            }
+        }
+        MOTION
+        Frames: 2
+        Frame Time: 0.5
+        0.0 0.0 0.0 350.0 -170.0 20.0
+        4.0 0.0 0.0 10.0 170.0 40.0
+    };
+
+    let middle = bvh.interpolate_euler(0, 1, 0.5).unwrap();
+    assert_eq!(&middle[..3], &[2.0, 0.0, 0.0]);
+    assert_eq!(middle[3].rem_euclid(360.0), 0.0);
+    assert_eq!(middle[4].rem_euclid(360.0), 180.0);
+    assert_eq!(middle[5], 30.0);
+
+    let end = bvh.interpolate_euler(0, 1, 1.0).unwrap();
+    assert_eq!(end[3].rem_euclid(360.0), 10.0);
+    assert_eq!(
+        bvh.interpolate_euler(1, 0, 0.0).unwrap(),
+        bvh.frame_values(1).unwrap()
+    );
+}
+
+#[test]
+fn comma_separated_motion_values() {
+    use bvh_anim::read::ParseOptions;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_comma_separated.bvh");
+    const SIMPLE_BYTES: &[u8] = include_bytes!("../data/test_simple.bvh");
+
+    let bvh = ParseOptions::new()
+        .with_value_separator(Some(b','))
+        .read_bytes(BVH_BYTES)
+        .unwrap();
+    assert_eq!(bvh, bvh_anim::from_bytes(SIMPLE_BYTES).unwrap());
+
+    assert!(bvh_anim::from_bytes(BVH_BYTES).is_err());
+}
+
+#[test]
+fn metadata_comments_round_trip() {
+    use bvh_anim::{
+        read::ParseOptions,
+        write::{LineTerminator, WriteOptions},
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_metadata_comments.bvh");
+    const SIMPLE_BYTES: &[u8] = include_bytes!("../data/test_simple.bvh");
+
+    let options = ParseOptions::new().with_metadata_comments(true);
+    let bvh = options.read_bytes(BVH_BYTES).unwrap();
+    let metadata = bvh
+        .metadata()
+        .iter()
+        .map(|(key, value)| (key.to_str().unwrap(), value.to_str().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        metadata,
+        [("retargeted", "yes"), ("source", "studio capture")]
+    );
+
+    let written = WriteOptions::new()
+        .with_line_terminator(LineTerminator::Unix)
+        .with_indent(bvh_anim::write::IndentStyle::with_spaces(4))
+        .with_metadata_comments(true)
+        .write_to_string(&bvh);
+    assert!(written.starts_with(b"# retargeted: yes\n# source: studio capture\nHIERARCHY"));
+    assert_eq!(options.read_bytes(&written).unwrap(), bvh);
+
+    let plain = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    assert!(plain.metadata().is_empty());
+    assert_eq!(plain, bvh_anim::from_bytes(SIMPLE_BYTES).unwrap());
+}
+
+#[test]
+fn uniform_channel_counts_parse_like_wrapped_rows() {
+    const HIERARCHY: &str = "HIERARCHY
+ROOT Hips
+{
+    OFFSET 0.0 0.0 0.0
+    CHANNELS 3 Zrotation Xrotation Yrotation
+    JOINT Chest
+    {
+        OFFSET 0.0 5.0 0.0
+        CHANNELS 3 Zrotation Xrotation Yrotation
+        JOINT Neck
+        {
+            OFFSET 0.0 5.0 0.0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            End Site
+            {
+                OFFSET 0.0 2.0 0.0
+            }
+        }
+    }
+}
+MOTION
+Frames: 4
+Frame Time: 0.033333333
+";
+
+    let values = (0..36).map(|i| format!("{}.25", i)).collect::<Vec<_>>();
+    let rows = values
+        .chunks(9)
+        .map(|row| row.join(" "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let wrapped = values.join("\n");
+
+    let bvh = bvh_anim::from_bytes(format!("{}{}\n", HIERARCHY, rows)).unwrap();
+    let reference = bvh_anim::from_bytes(format!("{}{}\n", HIERARCHY, wrapped)).unwrap();
+    assert_eq!(bvh, reference);
+    assert_eq!(bvh.frame_values(3).unwrap()[8], 35.25);
+}
+
+#[test]
+fn skip_motion_keeps_hierarchy_without_frames() {
+    use bvh_anim::read::ParseOptions;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let full = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let bvh = ParseOptions::new()
+        .with_skip_motion(true)
+        .read_bytes(BVH_BYTES)
+        .unwrap();
+
+    assert_eq!(bvh.num_frames(), 0);
+    assert_eq!(bvh.num_channels(), full.num_channels());
+    assert_eq!(bvh.frame_time(), full.frame_time());
+    assert!(bvh.joints().eq(full.joints()));
+}
+
+#[test]
+fn missing_frame_time_strict_and_lenient() {
+    use bvh_anim::{
+        diagnostic::DiagnosticKind,
+        errors::{LoadErrorKind, LoadMotionError},
+        read::ParseOptions,
+    };
+    use std::time::Duration;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_missing_frame_time.bvh");
+
+    let err = bvh_anim::from_bytes(BVH_BYTES).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Motion(LoadMotionError::MissingFrameTime { .. })
+    ));
+
+    let fallback = Duration::from_millis(40);
+    let (bvh, diagnostics) = ParseOptions::new()
+        .with_lenient(true)
+        .with_fallback_frame_time(fallback)
+        .read_with_diagnostics(BVH_BYTES)
+        .unwrap();
+
+    let expected = bvh_anim::from_bytes(include_bytes!("../data/test_simple.bvh")).unwrap();
+    assert!(bvh.frame_time_was_inferred());
+    assert!(!expected.frame_time_was_inferred());
+    assert_eq!(*bvh.frame_time(), fallback);
+    assert_eq!(bvh.num_frames(), 2);
+    assert!(bvh.frames().eq(expected.frames()));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].kind,
+        DiagnosticKind::MissingFrameTime {
+            frame_time: fallback
+        }
+    );
+}
+
+#[test]
+fn max_frames_truncates_or_errors() {
+    use bvh_anim::{
+        errors::{LoadErrorKind, LoadMotionError},
+        read::{FrameLimitPolicy, ParseOptions},
+    };
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let full = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let options = ParseOptions::new().with_max_frames(Some(5));
+    let bvh = options.read_bytes(BVH_BYTES).unwrap();
+    assert_eq!(bvh.num_frames(), 5);
+    assert!(bvh.frames().eq(full.frames().take(5)));
+
+    let options = options.on_frame_limit(FrameLimitPolicy::Error);
+    let err = options.read_bytes(BVH_BYTES).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Motion(LoadMotionError::TooManyFrames { max_frames: 5, .. })
+    ));
+
+    // A `Frames` line which promises fewer frames than there are rows must not
+    // let the extra rows past the limit.
+    let header = format!("Frames:\t{}", full.num_frames());
+    let lying = BVH_BYTES
+        .to_str()
+        .unwrap()
+        .replacen(&header, "Frames: 5", 1);
+    assert!(!lying.contains(&header));
+    let err = options.read_bytes(&lying).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        LoadErrorKind::Motion(LoadMotionError::TooManyFrames { max_frames: 5, .. })
+    ));
+}
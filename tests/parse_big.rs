@@ -1,7 +1,7 @@
 //! This test is in its own file because the `bvh` macro takes a long time for
 //! a large, real-world `bvh` file like `test_mocapbank.bvh`.
 
-use bvh_anim;
+#![allow(clippy::approx_constant, clippy::excessive_precision)]
 use pretty_assertions::assert_eq;
 
 #[test]
@@ -0,0 +1,267 @@
+use bvh_anim::{math, Axis};
+
+const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+
+fn assert_quat_close(a: &[f32], b: &[f32]) {
+    let dot = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f32>();
+    assert!((dot.abs() - 1.0).abs() < 1.0e-4, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn quaternion_tensor_shape_and_values() {
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let (quats, shape, root_translation) = bvh.to_quaternion_tensor();
+
+    let num_frames = bvh.frames().len();
+    let num_joints = bvh.joints().count();
+    assert_eq!(shape, [num_frames, num_joints, 4]);
+    assert_eq!(quats.len(), num_frames * num_joints * 4);
+    assert_eq!(root_translation.len(), num_frames * 3);
+
+    let frame = bvh.frames().next().unwrap();
+    assert_eq!(&root_translation[..3], &frame.as_slice()[..3]);
+
+    let chest = bvh.joints().find_by_name("Chest").unwrap();
+    let angles = chest
+        .channels()
+        .iter()
+        .map(|channel| frame[channel])
+        .collect::<Vec<_>>();
+    let expected = math::mat_to_quat(&math::euler_to_mat(
+        [Axis::Z, Axis::X, Axis::Y],
+        [angles[0], angles[1], angles[2]],
+    ));
+    let index = chest.index() * 4;
+    assert_quat_close(&quats[index..index + 4], &expected);
+}
+
+#[test]
+fn quaternion_tensor_round_trip() {
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let (quats, shape, root_translation) = bvh.to_quaternion_tensor();
+
+    let rebuilt =
+        bvh_anim::Bvh::from_quaternion_tensor(&bvh, &quats, shape, &root_translation).unwrap();
+    assert_eq!(rebuilt.frames().len(), bvh.frames().len());
+
+    let (rebuilt_quats, rebuilt_shape, rebuilt_translation) = rebuilt.to_quaternion_tensor();
+    assert_eq!(rebuilt_shape, shape);
+    assert_eq!(rebuilt_translation, root_translation);
+    for (a, b) in quats.chunks_exact(4).zip(rebuilt_quats.chunks_exact(4)) {
+        assert_quat_close(a, b);
+    }
+
+    let err = bvh_anim::Bvh::from_quaternion_tensor(&bvh, &quats[4..], shape, &root_translation)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        bvh_anim::errors::QuaternionTensorError::QuaternionsLength { .. }
+    ));
+}
+
+#[test]
+fn quaternion_tensor_of_empty_template_is_an_error() {
+    use bvh_anim::{errors::QuaternionTensorError, Bvh};
+
+    let template = Bvh::new();
+    for &num_frames in &[0, 3] {
+        let root_translation = vec![0.0; num_frames * 3];
+        let err =
+            Bvh::from_quaternion_tensor(&template, &[], [num_frames, 0, 4], &root_translation)
+                .unwrap_err();
+        assert!(matches!(
+            err,
+            QuaternionTensorError::ShapeMismatch { num_joints: 0, .. }
+        ));
+    }
+}
+
+#[test]
+fn quaternion_series_is_continuous() {
+    let bvh = bvh_anim::bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            JOINT Chest
+            {
+                OFFSET 0.0 5.0 0.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 4
+        Frame Time: 0.033333333
+        -100.0 0.0 0.0
+        -115.0 0.0 0.0
+        -125.0 0.0 0.0
+        -140.0 10.0 0.0
+    };
+
+    let (raw, _, _) = bvh.to_quaternion_tensor();
+    let series = bvh.quaternion_series(0).unwrap();
+    assert_eq!(series.len(), 4);
+
+    // The raw quaternions flip sign between -115 and -125 degrees.
+    assert!(
+        math::quat_dot(
+            [raw[8], raw[9], raw[10], raw[11]],
+            [raw[16], raw[17], raw[18], raw[19]]
+        ) < 0.0
+    );
+
+    assert_quat_close(&series[0], &raw[..4]);
+    for (i, pair) in series.windows(2).enumerate() {
+        assert!(math::quat_dot(pair[0], pair[1]) > 0.9, "frame {}", i);
+        // Each quaternion is still the same rotation as the raw one.
+        let raw_quat = [
+            raw[(i + 1) * 8],
+            raw[(i + 1) * 8 + 1],
+            raw[(i + 1) * 8 + 2],
+            raw[(i + 1) * 8 + 3],
+        ];
+        assert!(math::quat_dot(pair[1], raw_quat).abs() > 1.0 - 1.0e-5);
+    }
+}
+
+#[test]
+fn quat_poses_match_root_euler_angles() {
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let pose = bvh.quat_poses().next().unwrap();
+    assert_eq!(pose.num_joints(), bvh.joints().count());
+    assert_eq!(bvh.quat_poses().count(), bvh.frames().len());
+
+    let root = bvh.root_joint().unwrap();
+    let frame = bvh.frame_values(0).unwrap();
+    let mut order = vec![];
+    let mut angles = vec![];
+    let mut translation = [0.0; 3];
+    for channel in root.channels() {
+        let ty = channel.channel_type();
+        if ty.is_rotation() {
+            order.push(ty.axis());
+            angles.push(frame[channel.motion_index()]);
+        } else if ty.is_position() {
+            translation[ty.axis() as usize] = frame[channel.motion_index()];
+        }
+    }
+    let expected = math::mat_to_quat(&math::euler_to_mat(
+        [order[0], order[1], order[2]],
+        [angles[0], angles[1], angles[2]],
+    ));
+
+    assert_quat_close(&pose.rotation_quat(0), &expected);
+    assert_eq!(pose.root_translation(), translation);
+}
+
+#[test]
+fn normalized_frame_vec_round_trip() {
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let frame = bvh.num_frames() / 2;
+
+    let values = bvh.to_normalized_frame_vec(frame).unwrap();
+    assert_eq!(values.len(), 3 + 4 * bvh.joints().count());
+
+    let (quats, shape, root_translation) = bvh.to_quaternion_tensor();
+    let per_frame = shape[1] * 4;
+    assert_eq!(&values[..3], &root_translation[frame * 3..][..3]);
+    assert_eq!(&values[3..], &quats[frame * per_frame..][..per_frame]);
+
+    for value in bvh.frame_values_mut(frame).unwrap() {
+        *value = 0.0;
+    }
+    bvh.apply_normalized_frame_vec(frame, &values).unwrap();
+    let restored = bvh.to_normalized_frame_vec(frame).unwrap();
+    assert_eq!(&restored[..3], &values[..3]);
+    for (a, b) in restored[3..].chunks(4).zip(values[3..].chunks(4)) {
+        assert_quat_close(a, b);
+    }
+
+    assert!(matches!(
+        bvh.apply_normalized_frame_vec(frame, &values[1..]),
+        Err(bvh_anim::errors::NormalizedFrameError::Length { .. })
+    ));
+    assert!(matches!(
+        bvh.apply_normalized_frame_vec(bvh.num_frames(), &values),
+        Err(bvh_anim::errors::NormalizedFrameError::FrameOutOfRange { .. })
+    ));
+}
+
+#[test]
+fn set_frame_from_quat_pose_round_trip() {
+    let mut bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let source = bvh.clone();
+    let pose = source.quat_poses().nth(20).unwrap();
+
+    bvh.set_frame_from_quat_pose(0, &pose).unwrap();
+    let written = bvh.quat_poses().next().unwrap();
+    assert_eq!(written.root_translation(), pose.root_translation());
+    for joint in 0..pose.num_joints() {
+        assert_quat_close(&written.rotation_quat(joint), &pose.rotation_quat(joint));
+    }
+
+    let other = bvh_anim::from_bytes(include_bytes!("../data/test_simple.bvh")).unwrap();
+    let other_pose = other.quat_poses().next().unwrap();
+    assert!(matches!(
+        bvh.set_frame_from_quat_pose(0, &other_pose),
+        Err(bvh_anim::errors::NormalizedFrameError::JointCount { .. })
+    ));
+    assert!(bvh
+        .set_frame_from_quat_pose(bvh.num_frames(), &pose)
+        .is_err());
+}
+
+#[test]
+fn binary_frame_round_trip() {
+    use bvh_anim::{errors::BinaryFrameError, QuatEncoding};
+
+    let source = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let joints = [0, 2, 5, 9];
+    let frame = 10;
+    let expected = source.quat_poses().nth(frame).unwrap();
+
+    for &(encoding, tolerance) in &[(QuatEncoding::F32, 1.0e-4), (QuatEncoding::I16, 1.0e-3)] {
+        let bytes = source
+            .encode_binary_frame(frame, &joints, encoding)
+            .unwrap();
+        let mut bvh = source.clone();
+        bvh.decode_binary_frame(0, &joints, &bytes).unwrap();
+
+        let pose = bvh.quat_poses().next().unwrap();
+        for &joint in &joints {
+            let a = pose.rotation_quat(joint);
+            let b = expected.rotation_quat(joint);
+            let dot = math::quat_dot(a, b).abs();
+            assert!((dot - 1.0).abs() < tolerance, "{:?} != {:?}", a, b);
+        }
+        assert_eq!(pose.root_translation(), expected.root_translation());
+        assert_eq!(
+            bvh.frame_values(0).unwrap()[6..9],
+            source.frame_values(0).unwrap()[6..9]
+        );
+    }
+
+    let num_joints = source.joints().count();
+    assert!(source
+        .encode_binary_frame(frame, &[num_joints], QuatEncoding::F32)
+        .is_none());
+
+    let bytes = source
+        .encode_binary_frame(frame, &joints, QuatEncoding::F32)
+        .unwrap();
+    let mut bvh = source.clone();
+    assert!(matches!(
+        bvh.decode_binary_frame(0, &joints[..2], &bytes),
+        Err(BinaryFrameError::Length { .. })
+    ));
+    assert!(matches!(
+        bvh.decode_binary_frame(0, &joints, &[7]),
+        Err(BinaryFrameError::UnknownEncoding { tag: 7 })
+    ));
+    assert_eq!(bvh, source);
+}
@@ -53,5 +53,692 @@ fn test_load_write_is_identical() {
         .with_line_terminator(LineTerminator::native())
         .write_to_string(&bvh);
 
-    assert_eq!(bvh_string, BVH_STRING);
+    assert_eq!(bvh_string, BVH_STRING.as_bytes());
+}
+
+#[test]
+fn test_write_channels_zero() {
+    const BVH_STRING: &str = include_str!("../data/test_channels_zero.bvh");
+    let bvh = bvh_anim::from_str(BVH_STRING).unwrap();
+    let bvh_string = WriteOptions::new()
+        .with_indent(IndentStyle::with_spaces(4))
+        .with_offset_significant_figures(1)
+        .with_motion_values_significant_figures(1)
+        .with_line_terminator(LineTerminator::native())
+        .write_to_string(&bvh);
+
+    assert_eq!(bvh_string, BVH_STRING.as_bytes());
+}
+
+#[test]
+fn test_write_max_line_bytes() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Base
+        {
+            OFFSET 0.0 0.0 0.0
+            CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+            JOINT Middle
+            {
+                OFFSET 0.0 0.0 15.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                JOINT End
+                {
+                    OFFSET 0.0 0.0 15.0
+                    CHANNELS 3 Zrotation Xrotation Yrotation
+                    End Site
+                    {
+                        OFFSET 0.0 0.0 30.0
+                    }
+                }
+            }
+        }
+        MOTION
+        Frames: 2
+        Frame Time: 0.033333333
+        10.25 -20.5 30.75 -40.125 50.0 -60.5 70.25 -80.5 90.75 -100.125 110.0 -120.5
+        1.0 2.0 3.0 4.0 5.0 6.0 7.0 8.0 9.0 10.0 11.0 12.0
+    };
+
+    const MAX_LINE_BYTES: usize = 20;
+    let bvh_string = WriteOptions::new()
+        .with_line_terminator(LineTerminator::Unix)
+        .with_max_line_bytes(MAX_LINE_BYTES)
+        .write_to_string(&bvh);
+
+    let motion = bvh_string.find("Frame Time").unwrap();
+    for line in bvh_string[motion..].lines().skip(1) {
+        assert!(
+            line.len() <= MAX_LINE_BYTES,
+            "{:?} is too long",
+            line.as_bstr()
+        );
+    }
+
+    let reparsed = bvh_anim::from_bytes(&bvh_string).unwrap();
+    assert_eq!(bvh, reparsed);
+}
+
+#[test]
+fn test_write_preserve_formatting() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let options = WriteOptions::new().with_preserve_formatting(true);
+
+    let mut bvh = bvh_anim::Bvh::parse_preserving(BVH_BYTES).unwrap();
+    assert_eq!(bvh, bvh_anim::from_bytes(BVH_BYTES).unwrap());
+    assert_eq!(options.write_to_string(&bvh), BVH_BYTES);
+
+    let mut written = vec![];
+    options.write(&bvh, &mut written).unwrap();
+    assert_eq!(written, BVH_BYTES);
+
+    let first_value = bvh.frames().next().unwrap().as_slice()[0];
+    bvh.frames_mut().next().unwrap().as_mut_slice()[0] = first_value + 1.5;
+    let edited = options.write_to_string(&bvh);
+    let reparsed = bvh_anim::from_bytes(&edited).unwrap();
+    assert_eq!(
+        reparsed.frames().next().unwrap().as_slice()[0],
+        first_value + 1.5
+    );
+    let differing_lines = edited
+        .lines()
+        .zip(BVH_BYTES.lines())
+        .filter(|(a, b)| a != b)
+        .count();
+    assert_eq!(edited.lines().count(), BVH_BYTES.lines().count());
+    assert_eq!(differing_lines, 1);
+
+    bvh.set_frame_time(std::time::Duration::from_secs(1));
+    let indent = bvh.detected_indent_style().unwrap();
+    assert_eq!(
+        options.write_to_string(&bvh),
+        WriteOptions::new()
+            .with_indent(indent)
+            .write_to_string(&bvh)
+    );
+}
+
+#[test]
+fn test_write_frame() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let mut written = vec![];
+    bvh.write_frame(3, &mut written, &WriteOptions::new())
+        .unwrap();
+    let pose = bvh_anim::from_bytes(&written).unwrap();
+
+    assert_eq!(pose.frames().len(), 1);
+    assert_eq!(pose.num_channels(), bvh.num_channels());
+    assert_eq!(
+        pose.frames().next().unwrap().as_slice(),
+        bvh.frames().nth(3).unwrap().as_slice()
+    );
+
+    let mut written = vec![];
+    let err = bvh
+        .write_frame(bvh.frames().len(), &mut written, &WriteOptions::new())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(written.is_empty());
+}
+
+#[test]
+fn test_write_to_fmt_matches_write_to_string() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    for options in &[
+        WriteOptions::new(),
+        WriteOptions::new()
+            .with_indent(IndentStyle::with_spaces(2))
+            .with_line_terminator(LineTerminator::Windows)
+            .with_max_line_bytes(40),
+    ] {
+        let mut string = String::new();
+        options.write_to_fmt(&bvh, &mut string).unwrap();
+        assert_eq!(string.as_bytes(), &options.write_to_string(&bvh)[..]);
+    }
+}
+
+#[test]
+fn test_write_value_separator() {
+    use bstr::BString;
+
+    const BVH_STRING: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_STRING).unwrap();
+
+    let bvh_string = WriteOptions::new()
+        .with_indent(IndentStyle::with_spaces(4))
+        .with_line_terminator(LineTerminator::Unix)
+        .with_value_separator(BString::from("\t"))
+        .write_to_string(&bvh);
+
+    let motion = bvh_string.find("Frame Time").unwrap();
+    assert!(!bvh_string[..motion].contains_str("\t"));
+
+    let rows = bvh_string[motion..].lines().skip(1).collect::<Vec<_>>();
+    assert_eq!(rows.len(), bvh.num_frames());
+    for row in rows {
+        assert!(!row.contains_str(" "));
+        assert_eq!(row.split_str("\t").count(), bvh.num_channels());
+    }
+
+    let reparsed = bvh_anim::from_bytes(&bvh_string).unwrap();
+    assert_eq!(bvh, reparsed);
+}
+
+#[test]
+fn test_bvh_writer_streams_frames() {
+    use bvh_anim::write::BvhWriter;
+    use std::io::Cursor;
+
+    const BVH_STRING: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let skeleton = bvh_anim::from_bytes(BVH_STRING).unwrap();
+    let num_channels = skeleton.num_channels();
+
+    let mut writer =
+        BvhWriter::new(&skeleton, Cursor::new(Vec::new()), WriteOptions::new()).unwrap();
+    let mut expected = skeleton.clone();
+    expected.extract_frames();
+    for i in 0..1000 {
+        let frame = (0..num_channels)
+            .map(|c| (i * num_channels + c) as f32 * 0.25)
+            .collect::<Vec<_>>();
+        writer.write_frame(&frame).unwrap();
+        expected.push_frame(&frame);
+    }
+    assert_eq!(writer.num_frames(), 1000);
+    assert!(writer.write_frame(&[0.0]).is_err());
+
+    let bytes = writer.finish().unwrap().into_inner();
+    let reparsed = bvh_anim::from_bytes(&bytes).unwrap();
+    assert_eq!(reparsed.num_frames(), 1000);
+    assert_eq!(reparsed, expected);
+}
+
+#[test]
+fn test_write_channel_order() {
+    use bvh_anim::{write::ChannelOrder, Bvh};
+
+    fn assert_same_pose(bvh: &Bvh, order: ChannelOrder) {
+        let written = WriteOptions::new()
+            .with_channel_order(order)
+            .write_to_string(bvh);
+        let reparsed = bvh_anim::from_bytes(&written).unwrap();
+        assert_eq!(reparsed.num_frames(), bvh.num_frames());
+
+        for (frame, reparsed_frame) in bvh.frames().zip(reparsed.frames()) {
+            let expected = bvh.world_positions(&frame);
+            let actual = reparsed.world_positions(&reparsed_frame);
+            for (a, b) in expected.iter().zip(&actual) {
+                for (x, y) in a.iter().zip(b) {
+                    assert!((x - y).abs() < 1.0e-3, "{:?} != {:?}", a, b);
+                }
+            }
+        }
+    }
+
+    fn channel_names(bvh: &Bvh, joint: usize) -> Vec<String> {
+        bvh.joint(joint)
+            .unwrap()
+            .channels()
+            .iter()
+            .map(|channel| channel.channel_type().to_string())
+            .collect()
+    }
+
+    const BVH_STRING: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_STRING).unwrap();
+    for &order in &[ChannelOrder::PositionsFirst, ChannelOrder::RotationsFirst] {
+        assert_same_pose(&bvh, order);
+    }
+
+    // The root's channels can be reordered, because the Z translation commutes
+    // with the Z rotation, but the chest's X translation does not commute with its
+    // rotations, so it is written as stored.
+    let bvh = bvh_anim::from_str(
+        "HIERARCHY
+ROOT Hips
+{
+\tOFFSET 0 0 0
+\tCHANNELS 3 Zposition Zrotation Xposition
+\tJOINT Chest
+\t{
+\t\tOFFSET 0 5 0
+\t\tCHANNELS 3 Xposition Zrotation Yrotation
+\t\tEnd Site
+\t\t{
+\t\t\tOFFSET 0 5 0
+\t\t}
+\t}
+}
+MOTION
+Frames: 2
+Frame Time: 0.033333
+1 30 2 3 45 60
+-1 -30 4 -3 10 20
+",
+    )
+    .unwrap();
+    assert_same_pose(&bvh, ChannelOrder::RotationsFirst);
+
+    let written = WriteOptions::new()
+        .with_channel_order(ChannelOrder::RotationsFirst)
+        .write_to_string(&bvh);
+    let reparsed = bvh_anim::from_bytes(&written).unwrap();
+    assert_eq!(
+        channel_names(&reparsed, 0),
+        ["Zrotation", "Zposition", "Xposition"]
+    );
+    assert_eq!(
+        channel_names(&reparsed, 1),
+        ["Xposition", "Zrotation", "Yrotation"]
+    );
+}
+
+#[test]
+fn test_write_fluent_clip() {
+    use std::time::Duration;
+
+    const BVH_STRING: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let mut skeleton = bvh_anim::from_bytes(BVH_STRING).unwrap();
+    skeleton.extract_frames();
+    let num_channels = skeleton.num_channels();
+
+    let frames = (0..4)
+        .map(|i| vec![i as f32; num_channels])
+        .collect::<Vec<_>>();
+    let bvh = skeleton
+        .with_frame_time(Duration::from_millis(20))
+        .with_frames(frames)
+        .unwrap();
+
+    let bvh_string = WriteOptions::new()
+        .with_line_terminator(LineTerminator::Unix)
+        .write_to_string(&bvh);
+    assert!(bvh_string.contains_str("\nFrames: 4\nFrame Time: 0.02\n"));
+
+    let reparsed = bvh_anim::from_bytes(&bvh_string).unwrap();
+    assert_eq!(reparsed, bvh);
+    assert_eq!(reparsed.frames().nth(3).unwrap().as_slice()[0], 3.0);
+}
+
+#[test]
+fn test_with_frames_without_channels_has_no_frames() {
+    let skeleton = bvh_anim::from_str(
+        "HIERARCHY
+ROOT Hips
+{
+\tOFFSET 0 0 0
+\tCHANNELS 0
+}
+MOTION
+Frames: 0
+Frame Time: 0.033333
+",
+    )
+    .unwrap();
+    assert_eq!(skeleton.num_channels(), 0);
+
+    let bvh = skeleton.clone().with_frames(vec![vec![]; 3]).unwrap();
+    assert_eq!(bvh.num_frames(), 0);
+    assert!(skeleton.with_frames(vec![vec![0.0]]).is_err());
+}
+
+#[test]
+fn test_write_decimal_point_is_locale_independent() {
+    let bvh = bvh! {
+        HIERARCHY
+        ROOT Hips
+        {
+            OFFSET 0.0 1.5 0.0
+            CHANNELS 3 Xposition Yposition Zposition
+            JOINT Chest
+            {
+                OFFSET 0.0 5.0 0.0
+                CHANNELS 0
+                End Site
+                {
+                    OFFSET 0.0 5.0 0.0
+                }
+            }
+        }
+        MOTION
+        Frames: 1
+        Frame Time: 0.033333333
+        1.5 -1234.25 0.0
+    };
+
+    let bvh_string = WriteOptions::new()
+        .with_line_terminator(LineTerminator::Unix)
+        .write_to_string(&bvh);
+    assert!(bvh_string.contains_str("OFFSET 0 1.5 0\n"));
+    assert!(bvh_string.ends_with(b"\n1.5 -1234.25 0\n"));
+    assert!(!bvh_string.contains_str(","));
+
+    let bvh_string = WriteOptions::new()
+        .with_line_terminator(LineTerminator::Unix)
+        .with_motion_values_significant_figures(1)
+        .write_to_string(&bvh);
+    assert!(bvh_string.ends_with(b"\n1.5 -1234.2 0.0\n"));
+
+    let comma = bvh_string.replace("1.5 -1234.2", "1,5 -1234,2");
+    assert!(bvh_anim::from_bytes(&comma).is_err());
+}
+
+#[test]
+fn test_write_to_vec_matches_write_to_string() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let options = WriteOptions::new().with_indent(IndentStyle::with_spaces(2));
+
+    let bytes = options.write_to_vec(&bvh);
+    assert_eq!(bytes, options.write_to_string(&bvh));
+
+    let mut written = vec![];
+    options.write(&bvh, &mut written).unwrap();
+    assert_eq!(bytes, written);
+}
+
+#[test]
+fn test_estimated_size_is_close() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    for options in &[
+        WriteOptions::new(),
+        WriteOptions::new()
+            .with_indent(IndentStyle::with_spaces(4))
+            .with_line_terminator(LineTerminator::Windows)
+            .with_motion_values_significant_figures(3),
+    ] {
+        let estimate = options.estimated_size(&bvh) as f64;
+        let actual = options.write_to_vec(&bvh).len() as f64;
+        assert!(
+            (estimate - actual).abs() <= actual * 0.1,
+            "{} {}",
+            estimate,
+            actual
+        );
+    }
+
+    let preserved = bvh_anim::Bvh::parse_preserving(BVH_BYTES).unwrap();
+    let options = WriteOptions::new().with_preserve_formatting(true);
+    assert_eq!(options.estimated_size(&preserved), BVH_BYTES.len());
+}
+
+#[test]
+fn test_write_asf_bones() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let mut asf = vec![];
+    bvh.write_asf(&mut asf).unwrap();
+    let asf = String::from_utf8(asf).unwrap();
+
+    let num_joints = bvh.joints().count();
+    let num_end_sites = bvh.joints().filter(|joint| joint.has_end_site()).count();
+    let num_bones = asf
+        .lines()
+        .filter(|line| line.starts_with("    id "))
+        .count();
+    assert_eq!(num_bones, num_joints - 1 + num_end_sites);
+    assert_eq!(
+        asf.lines().filter(|line| *line == "  begin").count(),
+        num_bones + 1
+    );
+
+    let joint = bvh.joints().nth(1).unwrap();
+    let length = joint.offset().iter().map(|v| v * v).sum::<f32>().sqrt();
+    let mut lines = asf
+        .lines()
+        .skip_while(|line| line.strip_prefix("    name ") != Some(&*joint.name_lossy()));
+    lines.next().unwrap();
+    lines.next().unwrap();
+    let written = lines.next().unwrap().strip_prefix("    length ").unwrap();
+    assert!((written.parse::<f32>().unwrap() - length).abs() < 1.0e-4);
+}
+
+#[test]
+fn test_write_preserve_formatting_uses_detected_indent() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_simple.bvh");
+
+    let bvh = bvh_anim::Bvh::parse_preserving(BVH_BYTES).unwrap();
+    assert_eq!(
+        bvh.detected_indent_style(),
+        Some(IndentStyle::with_spaces(4))
+    );
+    assert_eq!(
+        bvh_anim::from_bytes(BVH_BYTES)
+            .unwrap()
+            .detected_indent_style(),
+        None
+    );
+
+    let tabbed = BVH_BYTES.replace("    ", "\t");
+    let mut bvh = bvh_anim::Bvh::parse_preserving(&tabbed[..]).unwrap();
+    assert_eq!(bvh.detected_indent_style(), Some(IndentStyle::Tabs));
+
+    bvh.remove_frame(0).unwrap();
+    let options = WriteOptions::new().with_preserve_formatting(true);
+    let written = options.write_to_string(&bvh);
+    assert!(written.lines().any(|line| line.starts_with(b"\tOFFSET")));
+    assert!(!written.lines().any(|line| line.starts_with(b" ")));
+    assert_eq!(bvh_anim::from_bytes(&written).unwrap(), bvh);
+}
+
+#[test]
+fn test_write_frame_range() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+    let options = WriteOptions::new();
+
+    let mut written = vec![];
+    options
+        .write_frame_range(&bvh, 0..10, 2, &mut written)
+        .unwrap();
+    let preview = bvh_anim::from_bytes(&written).unwrap();
+
+    assert_eq!(preview.num_frames(), 5);
+    assert_eq!(*preview.frame_time(), *bvh.frame_time() * 2);
+    assert_eq!(preview.joints().count(), bvh.joints().count());
+    for (index, frame) in (0..10).step_by(2).enumerate() {
+        assert_eq!(preview.frame_values(index), bvh.frame_values(frame));
+    }
+
+    let mut written = vec![];
+    assert!(options
+        .write_frame_range(&bvh, 0..10, 0, &mut written)
+        .is_err());
+    assert!(options
+        .write_frame_range(&bvh, 0..bvh.num_frames() + 1, 1, &mut written)
+        .is_err());
+    assert!(written.is_empty());
+}
+
+#[test]
+fn written_frame_count_follows_edits() {
+    const BVH_STRING: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let mut bvh = bvh_anim::Bvh::parse_preserving(BVH_STRING).unwrap();
+    let num_frames = bvh.num_frames();
+
+    bvh.remove_frame(0).unwrap();
+    bvh.remove_frame(0).unwrap();
+    let last = bvh.frame_values(0).unwrap().to_vec();
+    bvh.push_frame(&last);
+
+    let expected = format!("Frames: {}", num_frames - 1);
+    for options in &[
+        WriteOptions::new(),
+        WriteOptions::new().with_preserve_formatting(true),
+    ] {
+        let written = options.write_to_vec(&bvh);
+        assert!(written.find(&expected).is_some());
+        let reparsed = bvh_anim::from_bytes(&written).unwrap();
+        assert_eq!(reparsed.num_frames(), num_frames - 1);
+    }
+}
+
+#[test]
+fn preserve_formatting_keeps_motion_keyword_spelling() {
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_motion_keyword_spelling.bvh");
+    let options = WriteOptions::new().with_preserve_formatting(true);
+
+    let mut bvh = bvh_anim::Bvh::parse_preserving(BVH_BYTES).unwrap();
+    assert_eq!(
+        bvh,
+        bvh_anim::from_bytes(include_bytes!("../data/test_simple.bvh")).unwrap()
+    );
+    assert_eq!(options.write_to_string(&bvh), BVH_BYTES);
+
+    bvh.frames_mut().next().unwrap().as_mut_slice()[0] = 2.5;
+    let edited = options.write_to_string(&bvh);
+    assert!(edited.find("FRAMES: 2").is_some());
+    assert!(edited.find("FrameTime: 0.033333333").is_some());
+    assert_eq!(bvh_anim::from_bytes(&edited).unwrap(), bvh);
+}
+
+#[test]
+fn preserve_formatting_writes_edited_metadata() {
+    use bvh_anim::read::ParseOptions;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_metadata_comments.bvh");
+    let options = WriteOptions::new()
+        .with_preserve_formatting(true)
+        .with_metadata_comments(true);
+
+    let mut bvh = bvh_anim::Bvh::parse_preserving(BVH_BYTES).unwrap();
+    assert_eq!(options.write_to_string(&bvh), BVH_BYTES);
+
+    bvh.metadata_mut().insert("actor".into(), "A. Smith".into());
+    let edited = options.write_to_string(&bvh);
+    assert!(edited.find("# actor: A. Smith").is_some());
+
+    let reparsed = ParseOptions::new()
+        .with_metadata_comments(true)
+        .read_bytes(&edited)
+        .unwrap();
+    assert_eq!(reparsed.metadata(), bvh.metadata());
+    assert_eq!(reparsed.frames().count(), bvh.frames().count());
+}
+
+#[test]
+fn skeleton_only_bvh_round_trips() {
+    const ROOT_ONLY: &str = "HIERARCHY
+ROOT Hips
+{
+\tOFFSET 1 2 3
+\tCHANNELS 3 Xposition Yposition Zposition
+}
+MOTION
+Frames: 0
+Frame Time: 0.033333
+";
+    let bvh = bvh_anim::from_str(ROOT_ONLY).unwrap();
+    assert_eq!(bvh.joints().count(), 1);
+    assert_eq!(bvh.num_frames(), 0);
+
+    let written = bvh.to_string();
+    assert_eq!(written, ROOT_ONLY.as_bytes());
+    assert_eq!(bvh_anim::from_bytes(&written).unwrap(), bvh);
+
+    // The root's offset is kept, even though a root cannot carry an end site.
+    let with_end_site = ROOT_ONLY.replace(
+        "Zposition\n",
+        "Zposition\n\tEnd Site\n\t{\n\t\tOFFSET 0 1 0\n\t}\n",
+    );
+    assert_eq!(bvh_anim::from_str(&with_end_site).unwrap(), bvh);
+
+    // A leaf joint without an end site is closed before its sibling.
+    let mut skeleton = bvh_anim::from_str(
+        "HIERARCHY
+ROOT Hips
+{
+\tOFFSET 0 0 0
+\tCHANNELS 3 Xposition Yposition Zposition
+\tJOINT Left
+\t{
+\t\tOFFSET 1 0 0
+\t\tCHANNELS 1 Zrotation
+\t}
+\tJOINT Right
+\t{
+\t\tOFFSET -1 0 0
+\t\tCHANNELS 1 Zrotation
+\t\tEnd Site
+\t\t{
+\t\t\tOFFSET -1 0 0
+\t\t}
+\t}
+}
+MOTION
+Frames: 1
+Frame Time: 0.033333
+0 0 0 0 0
+",
+    )
+    .unwrap();
+    skeleton.clear_motion();
+    let written = skeleton.to_string();
+    assert!(written.ends_with(b"Frames: 0\nFrame Time: 0.033333\n"));
+    let reparsed = bvh_anim::from_bytes(&written).unwrap();
+    assert_eq!(reparsed, skeleton);
+    assert_eq!(
+        reparsed
+            .joints()
+            .map(|joint| joint.depth())
+            .collect::<Vec<_>>(),
+        [0, 1, 1]
+    );
+}
+
+#[test]
+fn diff_preview_reports_only_changed_motion_lines() {
+    use bvh_anim::write::LineDiff;
+
+    const BVH_BYTES: &[u8] = include_bytes!("../data/test_mocapbank.bvh");
+    let bvh = bvh_anim::from_bytes(BVH_BYTES).unwrap();
+
+    let original = WriteOptions::new().write_to_vec(&bvh);
+    assert!(WriteOptions::new().diff_preview(&bvh, &original).is_empty());
+
+    let options = WriteOptions::new().with_motion_values_significant_figures(2);
+    let diffs = options.diff_preview(&bvh, &original);
+    let first_motion_line = original
+        .lines()
+        .position(|line| line.starts_with(b"Frame Time"))
+        .unwrap()
+        + 2;
+    assert!(!diffs.is_empty());
+    assert!(diffs.len() <= bvh.num_frames());
+
+    let written = options.write_to_vec(&bvh);
+    let written = written.lines().collect::<Vec<_>>();
+    for diff in &diffs {
+        match diff {
+            LineDiff::Changed {
+                line,
+                original: before,
+                written: after,
+            } => {
+                assert!(*line >= first_motion_line);
+                assert_eq!(original.lines().nth(line - 1).unwrap(), before.as_slice());
+                assert_eq!(written[line - 1], after.as_slice());
+                assert_ne!(before, after);
+            }
+            other => panic!("unexpected diff: {:?}", other),
+        }
+    }
+
+    let mut shorter = bvh.clone();
+    shorter.remove_frame(bvh.num_frames() - 1).unwrap();
+    let diffs = WriteOptions::new().diff_preview(&shorter, &original);
+    assert!(diffs
+        .iter()
+        .any(|diff| matches!(diff, LineDiff::Changed { .. })));
+    assert!(matches!(
+        diffs.last(),
+        Some(LineDiff::Removed { line, .. }) if *line == original.lines().count()
+    ));
 }